@@ -17,7 +17,7 @@
 //! General algorithms working on graphs.
 
 use crate::builder::{Buildable, Builder};
-use crate::traits::{Digraph, Graph, GraphType, IndexDigraph, IndexGraph};
+use crate::traits::{Digraph, Graph, GraphIterator, GraphType, IndexDigraph, IndexGraph};
 
 use std::cmp::{max, min};
 use std::collections::HashSet;
@@ -268,6 +268,185 @@ where
     }
 }
 
+/// Determines the strongly connected components of a digraph.
+///
+/// The function numbers all strongly connected components and assigns
+/// each node the number of its containing component. The number of
+/// components is returned. The components are numbered in topological
+/// order, i.e. if there is an edge from a node in component `i` to a
+/// node in component `j != i`, then `i < j`.
+///
+/// This is an iterative implementation of Tarjan's algorithm, so it
+/// works on large graphs without overflowing the stack.
+///
+/// # Example
+///
+/// ```
+/// use rs_graph::LinkedListGraph;
+/// use rs_graph::builder::{Buildable, Builder};
+/// use rs_graph::traits::*;
+/// use rs_graph::algorithms;
+///
+/// // two cycles 0 -> 1 -> 2 -> 0 and 3 -> 4 -> 3 connected by 2 -> 3
+/// let g = LinkedListGraph::<usize>::new_with(|b| {
+///     let nodes = b.add_nodes(5);
+///     b.add_edge(nodes[0], nodes[1]);
+///     b.add_edge(nodes[1], nodes[2]);
+///     b.add_edge(nodes[2], nodes[0]);
+///     b.add_edge(nodes[2], nodes[3]);
+///     b.add_edge(nodes[3], nodes[4]);
+///     b.add_edge(nodes[4], nodes[3]);
+/// });
+///
+/// let (ncomps, comps) = algorithms::strongly_connected_components(&g);
+/// assert_eq!(ncomps, 2);
+/// assert_eq!(comps, vec![0, 0, 0, 1, 1]);
+/// ```
+pub fn strongly_connected_components<G>(g: &G) -> (usize, Vec<usize>)
+where
+    G: IndexDigraph,
+{
+    let n = g.num_nodes();
+    let mut index = vec![usize::MAX; n];
+    let mut lowlink = vec![0; n];
+    let mut components = vec![usize::MAX; n];
+    let mut ncomponents = 0;
+    let mut cnt = 0;
+    // the nodes whose component has not been determined, yet
+    let mut stack = vec![];
+    // the current dfs path with the iterators over the outgoing edges
+    let mut path = vec![];
+
+    for s in 0..n {
+        if index[s] != usize::MAX {
+            continue;
+        }
+
+        index[s] = cnt;
+        lowlink[s] = cnt;
+        cnt += 1;
+        stack.push(s);
+        path.push((s, g.out_iter(g.id2node(s))));
+
+        while let Some((u, it)) = path.last_mut() {
+            let u = *u;
+            if let Some((_, v)) = it.next(g) {
+                let v = g.node_id(v);
+                if index[v] == usize::MAX {
+                    index[v] = cnt;
+                    lowlink[v] = cnt;
+                    cnt += 1;
+                    stack.push(v);
+                    path.push((v, g.out_iter(g.id2node(v))));
+                } else if components[v] == usize::MAX {
+                    // v is on the stack
+                    lowlink[u] = min(lowlink[u], index[v]);
+                }
+            } else {
+                path.pop();
+                if let Some(&(p, _)) = path.last() {
+                    lowlink[p] = min(lowlink[p], lowlink[u]);
+                }
+                if lowlink[u] == index[u] {
+                    // u is the root of a component
+                    loop {
+                        let v = stack.pop().unwrap();
+                        components[v] = ncomponents;
+                        if v == u {
+                            break;
+                        }
+                    }
+                    ncomponents += 1;
+                }
+            }
+        }
+    }
+
+    // the components have been found in reverse topological order
+    for c in &mut components {
+        *c = ncomponents - 1 - *c;
+    }
+
+    (ncomponents, components)
+}
+
+/// Returns the condensation of a digraph.
+///
+/// The condensation contains a node for each strongly connected
+/// component of `g` and an edge from component `i` to component `j` if
+/// `g` contains an edge from a node in `i` to a node in `j != i`
+/// (parallel edges are merged). Hence, the condensation is acyclic.
+///
+/// The function returns the condensation, the node id in the
+/// condensation of each node of `g` (indexed by node id) and the nodes
+/// of `g` in each component (indexed by node id of the condensation).
+/// The nodes of the condensation are created in topological order (see
+/// [`strongly_connected_components`]).
+///
+/// # Example
+///
+/// ```
+/// use rs_graph::LinkedListGraph;
+/// use rs_graph::builder::{Buildable, Builder};
+/// use rs_graph::traits::*;
+/// use rs_graph::algorithms;
+///
+/// let g = LinkedListGraph::<usize>::new_with(|b| {
+///     let nodes = b.add_nodes(5);
+///     b.add_edge(nodes[0], nodes[1]);
+///     b.add_edge(nodes[1], nodes[0]);
+///     b.add_edge(nodes[1], nodes[2]);
+///     b.add_edge(nodes[0], nodes[2]);
+///     b.add_edge(nodes[3], nodes[2]);
+///     b.add_edge(nodes[4], nodes[3]);
+/// });
+///
+/// let (h, comps, nodes): (LinkedListGraph, _, _) = algorithms::condensation(&g);
+/// assert_eq!(h.num_nodes(), 4);
+/// assert_eq!(h.num_edges(), 3);
+/// assert_eq!(nodes[comps[0]], vec![g.id2node(0), g.id2node(1)]);
+/// for e in h.edges() {
+///     assert!(h.node_id(h.src(e)) < h.node_id(h.snk(e)));
+/// }
+/// ```
+#[allow(clippy::type_complexity)]
+pub fn condensation<'a, G, H>(g: &'a G) -> (H, Vec<usize>, Vec<Vec<G::Node<'a>>>)
+where
+    G: IndexDigraph,
+    H: Digraph + Buildable,
+{
+    let (ncomponents, components) = strongly_connected_components(g);
+
+    let mut members = vec![vec![]; ncomponents];
+    for u in g.nodes() {
+        members[components[g.node_id(u)]].push(u);
+    }
+
+    let mut h = H::Builder::with_capacities(ncomponents, g.num_edges());
+    let hnodes = h.add_nodes(ncomponents);
+    // the last component from which an edge to each component has been added
+    let mut last = vec![usize::MAX; ncomponents];
+    for (c, nodes) in members.iter().enumerate() {
+        for &u in nodes {
+            for (_, v) in g.outedges(u) {
+                let d = components[g.node_id(v)];
+                if d != c && last[d] != c {
+                    last[d] = c;
+                    h.add_edge(hnodes[c], hnodes[d]);
+                }
+            }
+        }
+    }
+
+    let ids = hnodes.iter().map(|&u| h.node2id(u)).collect::<Vec<_>>();
+    let mut nodes = vec![vec![]; ncomponents];
+    for (c, m) in members.into_iter().enumerate() {
+        nodes[ids[c]] = m;
+    }
+
+    (h.into_graph(), components.into_iter().map(|c| ids[c]).collect(), nodes)
+}
+
 /// Either a node or an edge.
 pub enum Item<'a, G>
 where
@@ -337,10 +516,12 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::algorithms::complement;
+    use crate::algorithms::{complement, condensation, strongly_connected_components};
+    use crate::builder::{Buildable, Builder};
     use crate::classes::*;
     use crate::linkedlistgraph::{Edge, LinkedListGraph};
     use crate::traits::*;
+    use crate::Net;
     use std::cmp::{max, min};
 
     #[test]
@@ -367,4 +548,94 @@ mod tests {
         assert_eq!(hedges, vec![(0, 2), (0, 3), (1, 3), (1, 4), (2, 4)]);
         assert_eq!(gedges, ledges);
     }
+
+    #[test]
+    #[allow(clippy::needless_range_loop)]
+    fn test_scc_random() {
+        let mut next = crate::testutil::lcg(13);
+
+        for _ in 0..30 {
+            let n = 1 + next(30) as usize;
+            let p = next(6);
+            let g = Net::new_with(|b| {
+                let nodes = b.add_nodes(n);
+                for u in 0..n {
+                    for v in 0..n {
+                        if next(40) < p {
+                            b.add_edge(nodes[u], nodes[v]);
+                        }
+                    }
+                }
+            });
+
+            // compute the transitive closure
+            let mut reach = vec![vec![false; n]; n];
+            for u in 0..n {
+                reach[u][u] = true;
+                let mut q = vec![u];
+                while let Some(v) = q.pop() {
+                    for (_, w) in g.outedges(g.id2node(v)) {
+                        let w = g.node_id(w);
+                        if !reach[u][w] {
+                            reach[u][w] = true;
+                            q.push(w);
+                        }
+                    }
+                }
+            }
+
+            let (ncomps, comps) = strongly_connected_components(&g);
+            for u in 0..n {
+                assert!(comps[u] < ncomps);
+                for v in 0..n {
+                    assert_eq!(comps[u] == comps[v], reach[u][v] && reach[v][u]);
+                }
+            }
+
+            let (h, hcomps, nodes): (Net, _, _) = condensation(&g);
+            assert_eq!(h.num_nodes(), ncomps);
+            for u in g.nodes() {
+                assert!(nodes[hcomps[g.node_id(u)]].contains(&u));
+            }
+            assert_eq!(nodes.iter().map(|c| c.len()).sum::<usize>(), n);
+            let mut edges = h
+                .edges()
+                .map(|e| (h.node_id(h.src(e)), h.node_id(h.snk(e))))
+                .collect::<Vec<_>>();
+            assert!(edges.iter().all(|&(c, d)| c < d));
+            edges.sort_unstable();
+            let mut expected = g
+                .edges()
+                .map(|e| (hcomps[g.node_id(g.src(e))], hcomps[g.node_id(g.snk(e))]))
+                .filter(|&(c, d)| c != d)
+                .collect::<Vec<_>>();
+            expected.sort_unstable();
+            expected.dedup();
+            assert_eq!(edges, expected);
+        }
+    }
+
+    #[test]
+    fn test_scc_long_path() {
+        // long paths would overflow the stack of a recursive implementation
+        let n = 200_000;
+        let g = Net::new_with(|b| {
+            let nodes = b.add_nodes(n);
+            for i in 1..n {
+                b.add_edge(nodes[i - 1], nodes[i]);
+            }
+        });
+        let (ncomps, comps) = strongly_connected_components(&g);
+        assert_eq!(ncomps, n);
+        assert!(comps.iter().enumerate().all(|(i, &c)| i == c));
+
+        let g = Net::new_with(|b| {
+            let nodes = b.add_nodes(n);
+            for i in 0..n {
+                b.add_edge(nodes[i], nodes[(i + 1) % n]);
+            }
+        });
+        let (ncomps, _) = strongly_connected_components(&g);
+        assert_eq!(ncomps, 1);
+    }
 }
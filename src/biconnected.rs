@@ -0,0 +1,432 @@
+// Copyright (c) 2022 Frank Fischer <frank-fischer@shadow-soft.de>
+//
+// This program is free software: you can redistribute it and/or
+// modify it under the terms of the GNU General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see  <http://www.gnu.org/licenses/>
+//
+
+//! Articulation points, bridges and biconnected components.
+//!
+//! An articulation point (or cut node) of an undirected graph is a node
+//! whose removal increases the number of connected components, a bridge
+//! is such an edge. The blocks (biconnected components) are the maximal
+//! subgraphs without articulation points. Each edge (except loops)
+//! belongs to exactly one block, two blocks share at most one node,
+//! which is an articulation point. An isolated node forms a block
+//! without edges.
+//!
+//! The block-cut tree contains a node for each block and a node for
+//! each articulation point. Each articulation point is connected to the
+//! blocks containing it. The block-cut tree of a connected graph is a
+//! tree, in general it is a forest.
+//!
+//! All functions are based on the (iterative) depth-first search of
+//! [`search::dfs`](crate::search::dfs) and run in linear time.
+//!
+//! # Example
+//!
+//! ```
+//! use rs_graph::{Buildable, Builder, LinkedListGraph};
+//! use rs_graph::biconnected::biconnected_components;
+//! use rs_graph::traits::*;
+//!
+//! // a triangle 0, 1, 2 with a pendant path 2 - 3 - 4
+//! let g = LinkedListGraph::<u32>::new_with(|b| {
+//!     let nodes = b.add_nodes(5);
+//!     b.add_edge(nodes[0], nodes[1]);
+//!     b.add_edge(nodes[1], nodes[2]);
+//!     b.add_edge(nodes[2], nodes[0]);
+//!     b.add_edge(nodes[2], nodes[3]);
+//!     b.add_edge(nodes[3], nodes[4]);
+//! });
+//!
+//! let bc = biconnected_components(&g);
+//! let ids = |nodes: &[_]| nodes.iter().map(|&u| g.node_id(u)).collect::<Vec<_>>();
+//! assert_eq!(ids(&bc.articulation_points), vec![2, 3]);
+//! assert_eq!(bc.bridges.iter().map(|&e| g.edge_id(e)).collect::<Vec<_>>(), vec![3, 4]);
+//! assert_eq!(bc.blocks.len(), 3);
+//! assert_eq!(ids(&bc.blocks[bc.edge_blocks[0].unwrap()]), vec![0, 1, 2]);
+//! ```
+
+use crate::builder::{Buildable, Builder};
+use crate::collections::{ItemMap, NodeVecMap};
+use crate::search::dfs;
+use crate::traits::{Graph, IndexGraph};
+
+use std::cmp::min;
+
+/// The biconnected components of a graph.
+#[derive(Clone, Debug)]
+pub struct Biconnected<N, E> {
+    /// The articulation points sorted by node id.
+    pub articulation_points: Vec<N>,
+    /// The bridges sorted by edge id.
+    pub bridges: Vec<E>,
+    /// The nodes of each block sorted by node id.
+    pub blocks: Vec<Vec<N>>,
+    /// The block of each edge (indexed by edge id, `None` for loops).
+    pub edge_blocks: Vec<Option<usize>>,
+}
+
+/// Compute the articulation points of a graph.
+///
+/// The articulation points are returned sorted by node id.
+pub fn articulation_points<G>(g: &G) -> Vec<G::Node<'_>>
+where
+    G: IndexGraph,
+{
+    biconnected_components(g).articulation_points
+}
+
+/// Compute the bridges of a graph.
+///
+/// The bridges are returned sorted by edge id. Note that an edge with a
+/// parallel edge is never a bridge.
+pub fn bridges<G>(g: &G) -> Vec<G::Edge<'_>>
+where
+    G: IndexGraph,
+{
+    biconnected_components(g).bridges
+}
+
+/// Compute the articulation points, bridges and blocks of a graph.
+pub fn biconnected_components<G>(g: &G) -> Biconnected<G::Node<'_>, G::Edge<'_>>
+where
+    G: IndexGraph,
+{
+    const NONE: usize = usize::MAX;
+
+    let n = g.num_nodes();
+    // the preorder number of each node
+    let mut pre = vec![NONE; n];
+    // the nodes in preorder
+    let mut order = Vec::with_capacity(n);
+    // the parent and the incoming edge of each node in the dfs forest
+    let mut parent = vec![NONE; n];
+    let mut parent_edge = vec![NONE; n];
+
+    let mut data = (Visited(NodeVecMap::new(g)), Vec::new());
+    for s in g.nodes() {
+        let sid = g.node_id(s);
+        if pre[sid] != NONE {
+            continue;
+        }
+        pre[sid] = order.len();
+        order.push(sid);
+        let mut search = dfs::start_with_data(g.neighbors(), s, data);
+        for (v, e) in search.by_ref() {
+            let (vid, eid) = (g.node_id(v), g.edge_id(e));
+            let (x, y) = g.enodes(e);
+            pre[vid] = order.len();
+            order.push(vid);
+            parent[vid] = if x == v { g.node_id(y) } else { g.node_id(x) };
+            parent_edge[vid] = eid;
+        }
+        data = search.into_data();
+    }
+
+    // Compute the lowpoints, i.e. the smallest preorder number
+    // reachable by tree edges followed by one back edge. Children are
+    // handled before their parents.
+    let mut low = pre.clone();
+    for &v in order.iter().rev() {
+        for (e, w) in g.neighs(g.id2node(v)) {
+            if g.edge_id(e) != parent_edge[v] {
+                low[v] = min(low[v], pre[g.node_id(w)]);
+            }
+        }
+        if parent[v] != NONE {
+            low[parent[v]] = min(low[parent[v]], low[v]);
+        }
+    }
+
+    let mut nchildren = vec![0; n];
+    let mut is_cut = vec![false; n];
+    let mut bridges = vec![];
+    for v in 0..n {
+        let p = parent[v];
+        if p == NONE {
+            continue;
+        }
+        nchildren[p] += 1;
+        if parent[p] != NONE && low[v] >= pre[p] {
+            is_cut[p] = true;
+        }
+        if low[v] > pre[p] {
+            bridges.push(parent_edge[v]);
+        }
+    }
+    for v in 0..n {
+        if parent[v] == NONE && nchildren[v] >= 2 {
+            is_cut[v] = true;
+        }
+    }
+
+    // Assign the blocks in preorder. A tree edge starts a new block if
+    // the subtree below it is not connected to proper ancestors of its
+    // parent, otherwise it belongs to the block of the parent's tree
+    // edge.
+    let mut block = vec![NONE; n];
+    let mut blocks: Vec<Vec<usize>> = vec![];
+    for &v in &order {
+        let p = parent[v];
+        if p == NONE {
+            if nchildren[v] == 0 {
+                block[v] = blocks.len();
+                blocks.push(vec![v]);
+            }
+        } else if low[v] >= pre[p] {
+            block[v] = blocks.len();
+            blocks.push(vec![p, v]);
+        } else {
+            block[v] = block[p];
+            blocks[block[v]].push(v);
+        }
+    }
+
+    // Each edge belongs to the block of the tree edge of its end node
+    // that has been visited later.
+    let edge_blocks = g
+        .edges()
+        .map(|e| {
+            let (u, v) = g.enodes(e);
+            let (u, v) = (g.node_id(u), g.node_id(v));
+            if u == v {
+                None
+            } else if pre[u] > pre[v] {
+                Some(block[u])
+            } else {
+                Some(block[v])
+            }
+        })
+        .collect();
+
+    bridges.sort_unstable();
+    Biconnected {
+        articulation_points: (0..n).filter(|&u| is_cut[u]).map(|u| g.id2node(u)).collect(),
+        bridges: bridges.into_iter().map(|e| g.id2edge(e)).collect(),
+        blocks: blocks
+            .into_iter()
+            .map(|mut nodes| {
+                nodes.sort_unstable();
+                nodes.into_iter().map(|u| g.id2node(u)).collect()
+            })
+            .collect(),
+        edge_blocks,
+    }
+}
+
+/// Compute the block-cut tree of a graph.
+///
+/// The function returns the block-cut tree, the node id in the tree of
+/// each block (in the order of [`Biconnected::blocks`]) and the node id
+/// in the tree of each articulation point (indexed by node id of `g`,
+/// `None` for nodes that are not articulation points).
+///
+/// # Example
+///
+/// ```
+/// use rs_graph::{classes, LinkedListGraph};
+/// use rs_graph::biconnected::block_cut_tree;
+/// use rs_graph::traits::*;
+///
+/// // the star has one block for each edge, the center is the only articulation point
+/// let g: LinkedListGraph = classes::star(4);
+/// let (tree, blocks, cuts): (LinkedListGraph, _, _) = block_cut_tree(&g);
+/// assert_eq!(tree.num_nodes(), 5);
+/// assert_eq!(tree.num_edges(), 4);
+/// assert_eq!(blocks.len(), 4);
+/// assert!(cuts[0].is_some());
+/// assert!(cuts[1..].iter().all(|c| c.is_none()));
+/// ```
+pub fn block_cut_tree<G, H>(g: &G) -> (H, Vec<usize>, Vec<Option<usize>>)
+where
+    G: IndexGraph,
+    H: Graph + Buildable,
+{
+    let bc = biconnected_components(g);
+
+    let mut h = H::Builder::with_capacities(
+        bc.blocks.len() + bc.articulation_points.len(),
+        bc.blocks.iter().map(|b| b.len()).sum(),
+    );
+    let blocknodes = h.add_nodes(bc.blocks.len());
+    let mut cutnodes = vec![None; g.num_nodes()];
+    for &u in &bc.articulation_points {
+        cutnodes[g.node_id(u)] = Some(h.add_node());
+    }
+    for (b, nodes) in bc.blocks.iter().enumerate() {
+        for &u in nodes {
+            if let Some(c) = cutnodes[g.node_id(u)] {
+                h.add_edge(blocknodes[b], c);
+            }
+        }
+    }
+
+    let blocks = blocknodes.iter().map(|&u| h.node2id(u)).collect();
+    let cuts = cutnodes.iter().map(|c| c.map(|c| h.node2id(c))).collect();
+    (h.into_graph(), blocks, cuts)
+}
+
+/// The visited nodes of the dfs.
+///
+/// This is a map that is not cleared when a new search is started, so
+/// that the searches from all nodes visit each node only once in total.
+struct Visited<'a, G, E>(NodeVecMap<'a, G, E>);
+
+impl<'a, G, E> ItemMap<G::Node<'a>, E> for Visited<'a, G, E>
+where
+    G: IndexGraph,
+    E: Clone,
+{
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn clear(&mut self) {}
+
+    fn insert(&mut self, key: G::Node<'a>, value: E) -> bool {
+        self.0.insert(key, value)
+    }
+
+    fn insert_or_replace(&mut self, key: G::Node<'a>, value: E) -> bool {
+        self.0.insert_or_replace(key, value)
+    }
+
+    fn remove(&mut self, key: G::Node<'a>) -> bool {
+        self.0.remove(key)
+    }
+
+    fn get(&self, key: G::Node<'a>) -> Option<&E> {
+        self.0.get(key)
+    }
+
+    fn get_mut(&mut self, key: G::Node<'a>) -> Option<&mut E> {
+        self.0.get_mut(key)
+    }
+
+    fn contains(&self, key: G::Node<'a>) -> bool {
+        self.0.contains(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{biconnected_components, block_cut_tree};
+    use crate::algorithms::components;
+    use crate::traits::*;
+    use crate::{Buildable, Builder, LinkedListGraph};
+
+    /// Count the components of the graph without node `x` and edge `f`.
+    fn ncomponents(g: &LinkedListGraph, x: Option<usize>, f: Option<usize>) -> usize {
+        let n = g.num_nodes();
+        let mut comp = vec![usize::MAX; n];
+        let mut cnt = 0;
+        for s in 0..n {
+            if Some(s) == x || comp[s] != usize::MAX {
+                continue;
+            }
+            comp[s] = cnt;
+            let mut q = vec![s];
+            while let Some(u) = q.pop() {
+                for (e, v) in g.neighs(g.id2node(u)) {
+                    let v = g.node_id(v);
+                    if Some(v) != x && Some(g.edge_id(e)) != f && comp[v] == usize::MAX {
+                        comp[v] = cnt;
+                        q.push(v);
+                    }
+                }
+            }
+            cnt += 1;
+        }
+        cnt
+    }
+
+    #[test]
+    fn test_random() {
+        let mut next = crate::testutil::lcg(19);
+
+        for _ in 0..50 {
+            let n = 1 + next(20) as usize;
+            let m = next(2 * n as u64 + 1) as usize;
+            let g = LinkedListGraph::<u32>::new_with(|b| {
+                let nodes = b.add_nodes(n);
+                for _ in 0..m {
+                    b.add_edge(nodes[next(n as u64) as usize], nodes[next(n as u64) as usize]);
+                }
+            });
+            let bc = biconnected_components(&g);
+            let k = ncomponents(&g, None, None);
+
+            let cuts = bc.articulation_points.iter().map(|&u| g.node_id(u)).collect::<Vec<_>>();
+            let expected = (0..n)
+                .filter(|&u| ncomponents(&g, Some(u), None) > k)
+                .collect::<Vec<_>>();
+            assert_eq!(cuts, expected);
+
+            let bridges = bc.bridges.iter().map(|&e| g.edge_id(e)).collect::<Vec<_>>();
+            let expected = (0..g.num_edges())
+                .filter(|&e| ncomponents(&g, None, Some(e)) > k)
+                .collect::<Vec<_>>();
+            assert_eq!(bridges, expected);
+
+            // each non-cut node is in exactly one block, each cut node in several
+            let mut nblocks = vec![0; n];
+            for nodes in &bc.blocks {
+                for &u in nodes {
+                    nblocks[g.node_id(u)] += 1;
+                }
+            }
+            for (u, &cnt) in nblocks.iter().enumerate() {
+                assert_eq!(cnt > 1, cuts.contains(&u));
+                assert!(cnt >= 1);
+            }
+
+            // edges are contained in their blocks, bridges form a block of their own
+            for e in g.edges() {
+                let (u, v) = g.enodes(e);
+                match bc.edge_blocks[g.edge_id(e)] {
+                    None => assert_eq!(u, v),
+                    Some(b) => {
+                        assert!(bc.blocks[b].contains(&u) && bc.blocks[b].contains(&v));
+                        if bc.bridges.contains(&e) {
+                            assert_eq!(bc.blocks[b].len(), 2);
+                            assert_eq!(bc.edge_blocks.iter().filter(|&&c| c == Some(b)).count(), 1);
+                        }
+                    }
+                }
+            }
+
+            // the block-cut tree is a forest with the same number of components
+            let (tree, blocks, _): (LinkedListGraph, _, _) = block_cut_tree(&g);
+            assert_eq!(blocks.len(), bc.blocks.len());
+            let (ntreecomps, _) = components(&tree);
+            assert_eq!(ntreecomps, k);
+            assert_eq!(tree.num_edges() + ntreecomps, tree.num_nodes());
+        }
+    }
+
+    #[test]
+    fn test_long_path() {
+        let n = 200_000;
+        let g = LinkedListGraph::<u32>::new_with(|b| {
+            let nodes = b.add_nodes(n);
+            for i in 1..n {
+                b.add_edge(nodes[i - 1], nodes[i]);
+            }
+        });
+        let bc = biconnected_components(&g);
+        assert_eq!(bc.articulation_points.len(), n - 2);
+        assert_eq!(bc.bridges.len(), n - 1);
+        assert_eq!(bc.blocks.len(), n - 1);
+    }
+}
@@ -0,0 +1,161 @@
+// Copyright (c) 2022 Frank Fischer <frank-fischer@shadow-soft.de>
+//
+// This program is free software: you can redistribute it and/or
+// modify it under the terms of the GNU General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see  <http://www.gnu.org/licenses/>
+//
+
+//! Maximum-weight closures.
+//!
+//! A closure of a digraph is a set of nodes without leaving edges,
+//! i.e. if `u` is in the closure and there is an edge `(u,v)`, then `v`
+//! is in the closure, too. Given weights on the nodes, a closure of
+//! maximal total weight can be computed by a minimal cut computation
+//! (Picard's reduction): each node with positive weight is connected
+//! to an additional source, each node with negative weight to an
+//! additional sink, and each edge gets infinite capacity.
+//!
+//! A typical application is project selection: the nodes are projects
+//! with profits (positive weights) or costs (negative weights) and an
+//! edge `(u,v)` means that project `u` requires project `v`.
+//!
+//! # Example
+//!
+//! ```
+//! use rs_graph::{Buildable, Builder, Net};
+//! use rs_graph::closure::max_weight_closure;
+//! use rs_graph::traits::*;
+//!
+//! // two projects (0 and 1) requiring tools (2, 3 and 4)
+//! let g = Net::new_with(|b| {
+//!     let nodes = b.add_nodes(5);
+//!     b.add_edge(nodes[0], nodes[2]);
+//!     b.add_edge(nodes[0], nodes[3]);
+//!     b.add_edge(nodes[1], nodes[3]);
+//!     b.add_edge(nodes[1], nodes[4]);
+//! });
+//! let weights = [10, 6, -4, -5, -3];
+//!
+//! let (value, mut nodes) = max_weight_closure(&g, |u| weights[g.node_id(u)]);
+//! nodes.sort_by_key(|&u| g.node_id(u));
+//!
+//! // project 1 alone has a loss, but shares tool 3 with project 0
+//! assert_eq!(value, 4);
+//! assert_eq!(nodes.into_iter().map(|u| g.node_id(u)).collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+//! ```
+
+use crate::builder::{Buildable, Builder};
+use crate::maxflow::Dinic;
+use crate::traits::{IndexDigraph, IndexGraph};
+use crate::Net;
+
+use crate::num::traits::NumAssign;
+
+/// Compute a closure of maximal weight.
+///
+/// The weight of each node is given by `weights`. The function returns
+/// the weight of the closure and its nodes. If several closures have
+/// maximal weight, the one with the fewest nodes is returned (in
+/// particular, the empty closure if no closure has positive weight).
+pub fn max_weight_closure<'a, G, W, Ws>(g: &'a G, weights: Ws) -> (W, Vec<G::Node<'a>>)
+where
+    G: IndexDigraph,
+    W: NumAssign + Ord + Copy,
+    Ws: Fn(G::Node<'a>) -> W,
+{
+    let n = g.num_nodes();
+    let weights: Vec<W> = g.nodes().map(weights).collect();
+    let total = weights
+        .iter()
+        .filter(|&&w| w > W::zero())
+        .fold(W::zero(), |total, &w| total + w);
+    // larger than any finite cut
+    let inf = total + W::one();
+
+    let mut caps = Vec::with_capacity(g.num_edges() + n);
+    let net = Net::new_with(|b| {
+        let nodes = b.add_nodes(n + 2);
+        for e in g.edges() {
+            b.add_edge(nodes[g.node_id(g.src(e))], nodes[g.node_id(g.snk(e))]);
+            caps.push(inf);
+        }
+        for (u, &w) in weights.iter().enumerate() {
+            if w > W::zero() {
+                b.add_edge(nodes[n], nodes[u]);
+                caps.push(w);
+            } else if w < W::zero() {
+                b.add_edge(nodes[u], nodes[n + 1]);
+                caps.push(W::zero() - w);
+            }
+        }
+    });
+
+    let mut maxflow = Dinic::new(&net);
+    maxflow.solve(net.id2node(n), net.id2node(n + 1), |e| caps[net.edge_id(e)]);
+
+    let nodes = maxflow
+        .mincut()
+        .into_iter()
+        .map(|u| net.node_id(u))
+        .filter(|&u| u < n)
+        .map(|u| g.id2node(u))
+        .collect();
+    (total - maxflow.value(), nodes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::max_weight_closure;
+    use crate::traits::*;
+    use crate::{Buildable, Builder, Net};
+
+    #[test]
+    fn test_random() {
+        let mut next = crate::testutil::lcg(5);
+
+        for _ in 0..20 {
+            let n = 10;
+            let weights: Vec<i32> = (0..n).map(|_| next(21) as i32 - 10).collect();
+            let g = Net::new_with(|b| {
+                let nodes = b.add_nodes(n);
+                for u in 0..n {
+                    for v in 0..n {
+                        if u != v && next(6) == 0 {
+                            b.add_edge(nodes[u], nodes[v]);
+                        }
+                    }
+                }
+            });
+
+            let (value, nodes) = max_weight_closure(&g, |u| weights[g.node_id(u)]);
+            let mut inclosure = vec![false; n];
+            for &u in &nodes {
+                inclosure[g.node_id(u)] = true;
+            }
+            assert!(g
+                .edges()
+                .all(|e| !inclosure[g.node_id(g.src(e))] || inclosure[g.node_id(g.snk(e))]));
+            assert_eq!(nodes.iter().map(|&u| weights[g.node_id(u)]).sum::<i32>(), value);
+
+            // compare with all subsets
+            let best = (0..1u32 << n)
+                .filter(|&set| {
+                    g.edges()
+                        .all(|e| set & (1 << g.node_id(g.src(e))) == 0 || set & (1 << g.node_id(g.snk(e))) != 0)
+                })
+                .map(|set| (0..n).filter(|&u| set & (1 << u) != 0).map(|u| weights[u]).sum::<i32>())
+                .max()
+                .unwrap();
+            assert_eq!(value, best);
+        }
+    }
+}
@@ -0,0 +1,293 @@
+// Copyright (c) 2022 Frank Fischer <frank-fischer@shadow-soft.de>
+//
+// This program is free software: you can redistribute it and/or
+// modify it under the terms of the GNU General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see  <http://www.gnu.org/licenses/>
+//
+
+//! Maximum sets of edge-disjoint and node-disjoint paths.
+//!
+//! By Menger's theorem the maximal number of edge-disjoint (resp.
+//! internally node-disjoint) paths between two nodes equals the
+//! minimal number of edges (resp. nodes) separating them. The paths
+//! are computed by a maximum flow computation with unit capacities
+//! (on a network with split nodes for the node-disjoint case) followed
+//! by a decomposition of the flow into paths.
+//!
+//! Each path is returned as the list of its edges from the source to
+//! the sink.
+//!
+//! # Example
+//!
+//! ```
+//! use rs_graph::{Buildable, Builder, Net};
+//! use rs_graph::disjointpaths::{edge_disjoint_paths, node_disjoint_paths};
+//! use rs_graph::traits::*;
+//!
+//! let g = Net::new_with(|b| {
+//!     let nodes = b.add_nodes(5);
+//!     b.add_edge(nodes[0], nodes[1]);
+//!     b.add_edge(nodes[0], nodes[2]);
+//!     b.add_edge(nodes[1], nodes[3]);
+//!     b.add_edge(nodes[2], nodes[3]);
+//!     b.add_edge(nodes[3], nodes[4]);
+//!     b.add_edge(nodes[3], nodes[4]);
+//!     b.add_edge(nodes[1], nodes[4]);
+//! });
+//! let s = g.id2node(0);
+//! let t = g.id2node(4);
+//!
+//! let paths = edge_disjoint_paths(&g, s, t);
+//! assert_eq!(paths.len(), 2);
+//!
+//! // only one path may use node 3
+//! let paths = node_disjoint_paths(&g, s, t);
+//! assert_eq!(paths.len(), 2);
+//! assert!(paths.iter().any(|p| p.iter().all(|&e| g.snk(e) != g.id2node(3))));
+//! ```
+
+use crate::builder::{Buildable, Builder};
+use crate::flowdecomposition::decompose_flow;
+use crate::maxflow::Dinic;
+use crate::traits::{FiniteGraph, IndexDigraph, IndexGraph};
+use crate::Net;
+
+/// Return a maximum set of edge-disjoint paths from `src` to `snk` in a digraph.
+pub fn edge_disjoint_paths<'a, G>(g: &'a G, src: G::Node<'_>, snk: G::Node<'_>) -> Vec<Vec<G::Edge<'a>>>
+where
+    G: IndexDigraph,
+{
+    let mut maxflow = Dinic::new(g);
+    maxflow.solve(src, snk, |_| 1usize);
+    decompose_flow(g, |e| maxflow.flow(e))
+        .paths
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect()
+}
+
+/// Return a maximum set of internally node-disjoint paths from `src` to `snk` in a digraph.
+///
+/// Two paths are internally node-disjoint if they have no node in
+/// common except for `src` and `snk`. In particular, parallel edges
+/// from `src` to `snk` form node-disjoint paths.
+pub fn node_disjoint_paths<'a, G>(g: &'a G, src: G::Node<'_>, snk: G::Node<'_>) -> Vec<Vec<G::Edge<'a>>>
+where
+    G: IndexDigraph,
+{
+    let edges = g
+        .edges()
+        .map(|e| (g.node_id(g.src(e)), g.node_id(g.snk(e))))
+        .collect::<Vec<_>>();
+    split_paths(g.num_nodes(), &edges, g.node_id(src), g.node_id(snk))
+        .into_iter()
+        .map(|path| path.into_iter().map(|e| g.id2edge(e)).collect())
+        .collect()
+}
+
+/// Return a maximum set of edge-disjoint paths between `src` and `snk` in an undirected graph.
+pub fn undirected_edge_disjoint_paths<'a, G>(g: &'a G, src: G::Node<'_>, snk: G::Node<'_>) -> Vec<Vec<G::Edge<'a>>>
+where
+    G: IndexGraph,
+{
+    // each edge is replaced by two opposite arcs
+    let net = Net::new_with(|b| {
+        let nodes = b.add_nodes(g.num_nodes());
+        for e in g.edges() {
+            let (u, v) = g.enodes(e);
+            let (u, v) = (nodes[g.node_id(u)], nodes[g.node_id(v)]);
+            b.add_edge(u, v);
+            b.add_edge(v, u);
+        }
+    });
+
+    let mut maxflow = Dinic::new(&net);
+    maxflow.solve(net.id2node(g.node_id(src)), net.id2node(g.node_id(snk)), |_| 1usize);
+
+    // flows in both directions of an edge cancel
+    let mut flow = vec![0; net.num_edges()];
+    for e in g.edges() {
+        let eid = g.edge_id(e);
+        let fwd = maxflow.flow(net.id2edge(2 * eid));
+        let bwd = maxflow.flow(net.id2edge(2 * eid + 1));
+        if fwd > bwd {
+            flow[2 * eid] = fwd - bwd;
+        } else {
+            flow[2 * eid + 1] = bwd - fwd;
+        }
+    }
+
+    decompose_flow(&net, |e| flow[net.edge_id(e)])
+        .paths
+        .into_iter()
+        .map(|(path, _)| path.into_iter().map(|e| g.id2edge(net.edge_id(e) / 2)).collect())
+        .collect()
+}
+
+/// Return a maximum set of internally node-disjoint paths between `src` and `snk` in an undirected graph.
+///
+/// Two paths are internally node-disjoint if they have no node in
+/// common except for `src` and `snk`.
+pub fn undirected_node_disjoint_paths<'a, G>(g: &'a G, src: G::Node<'_>, snk: G::Node<'_>) -> Vec<Vec<G::Edge<'a>>>
+where
+    G: IndexGraph,
+{
+    // each edge is replaced by two opposite arcs
+    let mut edges = Vec::with_capacity(2 * g.num_edges());
+    for e in g.edges() {
+        let (u, v) = g.enodes(e);
+        let (u, v) = (g.node_id(u), g.node_id(v));
+        edges.push((u, v));
+        edges.push((v, u));
+    }
+    split_paths(g.num_nodes(), &edges, g.node_id(src), g.node_id(snk))
+        .into_iter()
+        .map(|path| path.into_iter().map(|e| g.id2edge(e / 2)).collect())
+        .collect()
+}
+
+/// Compute node-disjoint paths in the network with split nodes.
+///
+/// The network contains the arcs `edges` of a graph with `n` nodes.
+/// Each node `u` is split into an in-node `2u` and an out-node
+/// `2u+1` connected by an arc of capacity 1. The paths are returned as
+/// lists of indices into `edges`.
+fn split_paths(n: usize, edges: &[(usize, usize)], src: usize, snk: usize) -> Vec<Vec<usize>> {
+    assert_ne!(src, snk, "Source and sink node must not be equal");
+
+    let net = Net::new_with(|b| {
+        let nodes = b.add_nodes(2 * n);
+        for &(u, v) in edges {
+            b.add_edge(nodes[2 * u + 1], nodes[2 * v]);
+        }
+        for u in 0..n {
+            b.add_edge(nodes[2 * u], nodes[2 * u + 1]);
+        }
+    });
+
+    let mut maxflow = Dinic::new(&net);
+    maxflow.solve(net.id2node(2 * src + 1), net.id2node(2 * snk), |_| 1usize);
+
+    decompose_flow(&net, |e| maxflow.flow(e))
+        .paths
+        .into_iter()
+        .map(|(path, _)| {
+            path.into_iter()
+                .map(|e| net.edge_id(e))
+                .filter(|&e| e < edges.len())
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classes;
+    use crate::traits::*;
+    use crate::LinkedListGraph;
+
+    /// Check that `paths` are paths from `src` to `snk` (in an undirected graph).
+    fn check_paths<G: IndexGraph>(g: &G, paths: &[Vec<G::Edge<'_>>], src: usize, snk: usize, nodes_disjoint: bool) {
+        let mut used_edges = vec![false; g.num_edges()];
+        let mut used_nodes = vec![false; g.num_nodes()];
+        for path in paths {
+            let mut u = src;
+            for (i, &e) in path.iter().enumerate() {
+                let eid = g.edge_id(e);
+                assert!(!used_edges[eid]);
+                used_edges[eid] = true;
+
+                let (v, w) = g.enodes(e);
+                let (v, w) = (g.node_id(v), g.node_id(w));
+                u = if u == v {
+                    w
+                } else {
+                    assert_eq!(u, w);
+                    v
+                };
+                if nodes_disjoint && i + 1 < path.len() {
+                    assert!(!used_nodes[u]);
+                    used_nodes[u] = true;
+                }
+            }
+            assert_eq!(u, snk);
+        }
+    }
+
+    #[test]
+    fn test_directed() {
+        // the complete digraph on 6 nodes has 5 node-disjoint paths
+        let g: LinkedListGraph = classes::complete_graph(6);
+        let g = LinkedListGraph::<usize>::new_with(|b| {
+            let nodes = b.add_nodes(g.num_nodes());
+            for e in g.edges() {
+                let (u, v) = g.enodes(e);
+                b.add_edge(nodes[g.node_id(u)], nodes[g.node_id(v)]);
+                b.add_edge(nodes[g.node_id(v)], nodes[g.node_id(u)]);
+            }
+        });
+        let (s, t) = (g.id2node(0), g.id2node(5));
+
+        let paths = edge_disjoint_paths(&g, s, t);
+        assert_eq!(paths.len(), 5);
+        check_paths(&g, &paths, 0, 5, false);
+        assert!(paths.iter().all(|p| p.iter().all(|&e| g.src(e) != t && g.snk(e) != s)));
+
+        let paths = node_disjoint_paths(&g, s, t);
+        assert_eq!(paths.len(), 5);
+        check_paths(&g, &paths, 0, 5, true);
+    }
+
+    #[test]
+    fn test_undirected() {
+        // in a grid the corners have degree 2 ...
+        let g: LinkedListGraph = classes::grid(4, 5);
+        let (s, t) = (g.id2node(0), g.id2node(19));
+        let paths = undirected_edge_disjoint_paths(&g, s, t);
+        assert_eq!(paths.len(), 2);
+        check_paths(&g, &paths, 0, 19, false);
+
+        let paths = undirected_node_disjoint_paths(&g, s, t);
+        assert_eq!(paths.len(), 2);
+        check_paths(&g, &paths, 0, 19, true);
+
+        // ... inner nodes have degree 4
+        let (s, t) = (g.id2node(5), g.id2node(14));
+        let paths = undirected_edge_disjoint_paths(&g, s, t);
+        assert_eq!(paths.len(), 4);
+        check_paths(&g, &paths, 5, 14, false);
+
+        let paths = undirected_node_disjoint_paths(&g, s, t);
+        assert_eq!(paths.len(), 4);
+        check_paths(&g, &paths, 5, 14, true);
+    }
+
+    #[test]
+    fn test_cut_node() {
+        // two triangles sharing node 2
+        let g = Net::new_with(|b| {
+            let nodes = b.add_nodes(5);
+            for &(u, v) in &[(0, 1), (1, 2), (0, 2), (2, 3), (3, 4), (2, 4)] {
+                b.add_edge(nodes[u], nodes[v]);
+            }
+        });
+        let (s, t) = (g.id2node(0), g.id2node(4));
+        let paths = undirected_edge_disjoint_paths(&g, s, t);
+        assert_eq!(paths.len(), 2);
+        check_paths(&g, &paths, 0, 4, false);
+
+        let paths = undirected_node_disjoint_paths(&g, s, t);
+        assert_eq!(paths.len(), 1);
+        check_paths(&g, &paths, 0, 4, true);
+    }
+}
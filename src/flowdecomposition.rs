@@ -0,0 +1,316 @@
+// Copyright (c) 2022 Frank Fischer <frank-fischer@shadow-soft.de>
+//
+// This program is free software: you can redistribute it and/or
+// modify it under the terms of the GNU General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see  <http://www.gnu.org/licenses/>
+//
+
+//! Decomposition of flows into paths and cycles.
+//!
+//! Each non-negative flow can be decomposed into weighted paths and
+//! cycles. The paths start at nodes with positive net outflow (e.g.
+//! the source of an s-t-flow) and end at nodes with positive net
+//! inflow (e.g. the sink). If the flow has a single source and a single
+//! sink (or is a circulation), the decomposition consists of at most
+//! `m` parts, otherwise of at most `n + m` parts.
+//!
+//! # Example
+//!
+//! ```
+//! use rs_graph::{Buildable, Builder, Net};
+//! use rs_graph::flowdecomposition::decompose_flow;
+//! use rs_graph::maxflow::dinic;
+//! use rs_graph::traits::*;
+//!
+//! let g = Net::new_with(|b| {
+//!     let nodes = b.add_nodes(4);
+//!     b.add_edge(nodes[0], nodes[1]);
+//!     b.add_edge(nodes[0], nodes[2]);
+//!     b.add_edge(nodes[1], nodes[2]);
+//!     b.add_edge(nodes[1], nodes[3]);
+//!     b.add_edge(nodes[2], nodes[3]);
+//! });
+//! let upper = [4, 2, 3, 1, 5];
+//! let s = g.id2node(0);
+//! let t = g.id2node(3);
+//!
+//! let (value, flow, _) = dinic(&g, s, t, |e| upper[g.edge_id(e)]);
+//! let decomp = decompose_flow(&g, |e| flow[g.edge_id(e)].1);
+//!
+//! assert!(decomp.cycles.is_empty());
+//! assert_eq!(decomp.paths.iter().map(|&(_, f)| f).sum::<i32>(), value);
+//! for (path, _) in &decomp.paths {
+//!     assert_eq!(g.src(path[0]), s);
+//!     assert_eq!(g.snk(path[path.len() - 1]), t);
+//! }
+//! ```
+
+use crate::traits::IndexDigraph;
+
+use crate::num::traits::NumAssign;
+
+use std::cmp::min;
+
+/// A decomposition of a flow into paths and cycles.
+///
+/// Each part is a list of edges along with its flow amount.
+#[derive(Clone, Debug)]
+pub struct FlowDecomposition<E, F> {
+    /// The paths from nodes with positive net outflow to nodes with
+    /// positive net inflow.
+    pub paths: Vec<(Vec<E>, F)>,
+    /// The cycles.
+    pub cycles: Vec<(Vec<E>, F)>,
+}
+
+/// Decompose a flow into paths and cycles.
+///
+/// The (non-negative) flow on each edge is given by `flow`. Edges with
+/// zero flow are ignored. The sum of the flows of all parts containing
+/// an edge equals the flow on that edge.
+pub fn decompose_flow<'a, G, F, Fs>(g: &'a G, flow: Fs) -> FlowDecomposition<G::Edge<'a>, F>
+where
+    G: IndexDigraph,
+    F: NumAssign + Ord + Copy,
+    Fs: Fn(G::Edge<'a>) -> F,
+{
+    let n = g.num_nodes();
+    let flows: Vec<F> = g.edges().map(&flow).collect();
+    let outs: Vec<Vec<(usize, usize)>> = g
+        .nodes()
+        .map(|u| g.outedges(u).map(|(e, v)| (g.edge_id(e), g.node_id(v))).collect())
+        .collect();
+
+    // the net outflow (supply) and net inflow (demand) of each node
+    let mut outflow = vec![F::zero(); n];
+    let mut inflow = vec![F::zero(); n];
+    for e in g.edges() {
+        let f = flows[g.edge_id(e)];
+        assert!(f >= F::zero(), "Flow must be non-negative");
+        outflow[g.node_id(g.src(e))] += f;
+        inflow[g.node_id(g.snk(e))] += f;
+    }
+    let mut supply = vec![F::zero(); n];
+    let mut demand = vec![F::zero(); n];
+    for u in 0..n {
+        if outflow[u] > inflow[u] {
+            supply[u] = outflow[u] - inflow[u];
+        } else {
+            demand[u] = inflow[u] - outflow[u];
+        }
+    }
+
+    let mut decomposer = Decomposer {
+        outs,
+        flows,
+        supply,
+        demand,
+        current: vec![0; n],
+        pos: vec![usize::MAX; n],
+        nodes: Vec::with_capacity(n),
+        edges: Vec::with_capacity(n),
+        paths: vec![],
+        cycles: vec![],
+    };
+
+    // first extract the paths ...
+    for u in 0..n {
+        while decomposer.supply[u] > F::zero() {
+            decomposer.walk(u, true);
+        }
+    }
+
+    // ... then the remaining flow is a circulation
+    for u in 0..n {
+        while decomposer.next_edge(u).is_some() {
+            decomposer.walk(u, false);
+        }
+    }
+
+    let to_edges = |parts: Vec<(Vec<usize>, F)>| {
+        parts
+            .into_iter()
+            .map(|(edges, f)| (edges.into_iter().map(|e| g.id2edge(e)).collect(), f))
+            .collect()
+    };
+
+    FlowDecomposition {
+        paths: to_edges(decomposer.paths),
+        cycles: to_edges(decomposer.cycles),
+    }
+}
+
+/// The state of the decomposition algorithm.
+struct Decomposer<F> {
+    /// The outgoing edges and their sink nodes of each node.
+    outs: Vec<Vec<(usize, usize)>>,
+    /// The remaining flow on each edge.
+    flows: Vec<F>,
+    /// The remaining net outflow of each node.
+    supply: Vec<F>,
+    /// The remaining net inflow of each node.
+    demand: Vec<F>,
+    /// The index of the first outgoing edge with possibly positive flow.
+    current: Vec<usize>,
+    /// The position of each node on the current walk.
+    pos: Vec<usize>,
+    /// The nodes of the current walk.
+    nodes: Vec<usize>,
+    /// The edges of the current walk.
+    edges: Vec<usize>,
+    paths: Vec<(Vec<usize>, F)>,
+    cycles: Vec<(Vec<usize>, F)>,
+}
+
+impl<F> Decomposer<F>
+where
+    F: NumAssign + Ord + Copy,
+{
+    /// Return the first outgoing edge of `u` with positive flow.
+    fn next_edge(&mut self, u: usize) -> Option<(usize, usize)> {
+        let outs = &self.outs[u];
+        while self.current[u] < outs.len() {
+            let (e, v) = outs[self.current[u]];
+            if self.flows[e] > F::zero() {
+                return Some((e, v));
+            }
+            self.current[u] += 1;
+        }
+        None
+    }
+
+    /// Walk along edges with positive flow starting at `u`.
+    ///
+    /// If `path` is `true` the walk stops at the first node with
+    /// positive net inflow and the path is extracted. Cycles found on
+    /// the way are extracted, too. If `path` is `false` the walk stops
+    /// after the first cycle has been extracted.
+    fn walk(&mut self, u: usize, path: bool) {
+        self.nodes.push(u);
+        self.pos[u] = 0;
+        loop {
+            let x = *self.nodes.last().unwrap();
+            if path && self.demand[x] > F::zero() {
+                let mut df = min(self.supply[u], self.demand[x]);
+                df = self.edges.iter().fold(df, |df, &e| min(df, self.flows[e]));
+                for &e in &self.edges {
+                    self.flows[e] -= df;
+                }
+                self.supply[u] -= df;
+                self.demand[x] -= df;
+                self.paths.push((self.edges.clone(), df));
+                break;
+            }
+
+            let (e, y) = self.next_edge(x).expect("Flow conservation violated");
+            if self.pos[y] == usize::MAX {
+                self.pos[y] = self.nodes.len();
+                self.nodes.push(y);
+                self.edges.push(e);
+                continue;
+            }
+
+            // found a cycle
+            let i = self.pos[y];
+            let mut cycle = self.edges.split_off(i);
+            cycle.push(e);
+            let df = cycle
+                .iter()
+                .skip(1)
+                .fold(self.flows[cycle[0]], |df, &e| min(df, self.flows[e]));
+            for &e in &cycle {
+                self.flows[e] -= df;
+            }
+            self.cycles.push((cycle, df));
+            for &z in &self.nodes[i + 1..] {
+                self.pos[z] = usize::MAX;
+            }
+            self.nodes.truncate(i + 1);
+
+            if !path {
+                break;
+            }
+        }
+
+        for &z in &self.nodes {
+            self.pos[z] = usize::MAX;
+        }
+        self.nodes.clear();
+        self.edges.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decompose_flow;
+    use crate::traits::*;
+    use crate::{Buildable, Builder, Net};
+
+    #[test]
+    fn test_paths_and_cycles() {
+        let edges = [
+            (0, 1, 3),
+            (1, 2, 5),
+            (2, 3, 2),
+            (3, 1, 2),
+            (2, 4, 3),
+            (4, 4, 1),
+            (5, 6, 4),
+            (6, 5, 4),
+        ];
+        let g = Net::new_with(|b| {
+            let nodes = b.add_nodes(7);
+            for &(u, v, _) in &edges {
+                b.add_edge(nodes[u], nodes[v]);
+            }
+        });
+        let decomp = decompose_flow(&g, |e| edges[g.edge_id(e)].2);
+
+        assert_eq!(decomp.paths.len(), 1);
+        assert_eq!(decomp.paths[0].1, 3);
+        assert_eq!(
+            decomp.paths[0].0.iter().map(|&e| g.edge_id(e)).collect::<Vec<_>>(),
+            vec![0, 1, 4]
+        );
+
+        let mut cycles = decomp
+            .cycles
+            .iter()
+            .map(|(c, f)| {
+                let mut c = c.iter().map(|&e| g.edge_id(e)).collect::<Vec<_>>();
+                c.sort();
+                (c, *f)
+            })
+            .collect::<Vec<_>>();
+        cycles.sort();
+        assert_eq!(cycles, vec![(vec![1, 2, 3], 2), (vec![5], 1), (vec![6, 7], 4)]);
+    }
+
+    #[test]
+    fn test_unsigned() {
+        let edges = [(0, 1, 4u32), (1, 2, 1), (1, 3, 3), (3, 2, 1), (2, 4, 2), (3, 4, 2)];
+        let g = Net::new_with(|b| {
+            let nodes = b.add_nodes(5);
+            for &(u, v, _) in &edges {
+                b.add_edge(nodes[u], nodes[v]);
+            }
+        });
+        let decomp = decompose_flow(&g, |e| edges[g.edge_id(e)].2);
+
+        assert!(decomp.cycles.is_empty());
+        assert_eq!(decomp.paths.iter().map(|&(_, f)| f).sum::<u32>(), 4);
+        for (p, _) in &decomp.paths {
+            assert_eq!(g.src(p[0]), g.id2node(0));
+            assert_eq!(g.snk(*p.last().unwrap()), g.id2node(4));
+        }
+    }
+}
@@ -62,12 +62,19 @@ pub mod collections;
 // # Algorithms
 
 pub mod algorithms;
+pub mod biconnected;
 pub mod branching;
+pub mod closure;
+pub mod disjointpaths;
+pub mod flowdecomposition;
+pub mod matching;
 pub mod maxflow;
 pub mod mcf;
+pub mod mincut;
 pub mod mst;
 pub mod search;
 pub mod shortestpath;
+pub mod verify;
 
 // # Drawing
 
@@ -80,3 +87,6 @@ pub mod dimacs;
 pub mod mps;
 #[cfg(any(feature = "steinlib"))]
 pub mod steinlib;
+
+#[cfg(test)]
+mod testutil;
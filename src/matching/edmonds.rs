@@ -0,0 +1,442 @@
+// Copyright (c) 2022 Frank Fischer <frank-fischer@shadow-soft.de>
+//
+// This program is free software: you can redistribute it and/or
+// modify it under the terms of the GNU General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see  <http://www.gnu.org/licenses/>
+//
+
+//! Maximum cardinality matchings in general graphs.
+//!
+//! Edmonds' blossom algorithm grows alternating trees from free nodes
+//! and shrinks odd cycles (blossoms) whenever two even nodes of a tree
+//! are adjacent. This implementation runs in `O(n^3)` time.
+//!
+//! The Gallai–Edmonds decomposition partitions the nodes into three
+//! sets:
+//!
+//! - `D`, the nodes not covered by at least one maximum matching,
+//! - `A`, the nodes not in `D` that are adjacent to `D`,
+//! - `C`, all remaining nodes.
+//!
+//! Each maximum matching matches the nodes in `C` among themselves, the
+//! nodes in `A` to distinct components of `D` and contains a near
+//! perfect matching of each component of `D`. In particular, the size
+//! of a maximum matching is `(n + |A| - k) / 2`, where `k` is the number
+//! of components of `D`, which certifies optimality (Tutte–Berge
+//! formula).
+//!
+//! # Example
+//!
+//! ```
+//! use rs_graph::{Buildable, Builder, LinkedListGraph};
+//! use rs_graph::matching::edmonds;
+//! use rs_graph::traits::*;
+//!
+//! // two triangles connected by a path 2 - 3 - 4
+//! let g = LinkedListGraph::<u32>::new_with(|b| {
+//!     let nodes = b.add_nodes(7);
+//!     b.add_edge(nodes[0], nodes[1]);
+//!     b.add_edge(nodes[1], nodes[2]);
+//!     b.add_edge(nodes[2], nodes[0]);
+//!     b.add_edge(nodes[2], nodes[3]);
+//!     b.add_edge(nodes[3], nodes[4]);
+//!     b.add_edge(nodes[4], nodes[5]);
+//!     b.add_edge(nodes[5], nodes[6]);
+//!     b.add_edge(nodes[6], nodes[4]);
+//! });
+//!
+//! let (matching, dec) = edmonds(&g);
+//! assert_eq!(matching.len(), 3);
+//!
+//! // node 3 is always matched to one of the triangles, each other
+//! // node is left free by some maximum matching
+//! let ids = |nodes: &[_]| nodes.iter().map(|&u| g.node_id(u)).collect::<Vec<_>>();
+//! assert_eq!(ids(&dec.a), vec![3]);
+//! assert_eq!(ids(&dec.d), vec![0, 1, 2, 4, 5, 6]);
+//! assert!(dec.c.is_empty());
+//! ```
+
+use crate::traits::IndexGraph;
+
+use std::collections::VecDeque;
+
+/// The Gallai–Edmonds decomposition of a graph.
+#[derive(Clone, Debug)]
+pub struct Decomposition<N> {
+    /// The nodes that are not covered by some maximum matching.
+    pub d: Vec<N>,
+    /// The neighbors of `D` that are not in `D`.
+    pub a: Vec<N>,
+    /// The remaining nodes.
+    pub c: Vec<N>,
+}
+
+/// Compute a maximum cardinality matching with Edmonds' blossom
+/// algorithm.
+///
+/// The function returns the edges of a maximum matching and the
+/// Gallai–Edmonds decomposition of the graph. The nodes in each set of
+/// the decomposition are sorted by id.
+pub fn edmonds<G>(g: &G) -> (Vec<G::Edge<'_>>, Decomposition<G::Node<'_>>)
+where
+    G: IndexGraph,
+{
+    let mut blossom = Blossom::new(g);
+
+    // start with a greedy matching
+    for u in 0..blossom.n {
+        if blossom.mate[u].is_none() {
+            if let Some(&(e, v)) = blossom.adj[u].iter().find(|&&(_, v)| blossom.mate[v].is_none()) {
+                blossom.mate[u] = Some((v, e));
+                blossom.mate[v] = Some((u, e));
+            }
+        }
+    }
+
+    for u in 0..blossom.n {
+        if blossom.mate[u].is_none() {
+            if let Some(v) = blossom.search(&[u]) {
+                blossom.augment(v);
+            }
+        }
+    }
+
+    // The matching is maximum, hence a search from all free nodes at
+    // once does not find an augmenting path. The even nodes of this
+    // search form D, the odd nodes form A.
+    let free = (0..blossom.n)
+        .filter(|&u| blossom.mate[u].is_none())
+        .collect::<Vec<_>>();
+    let augmenting = blossom.search(&free);
+    debug_assert!(augmenting.is_none());
+
+    let matching = (0..blossom.n)
+        .filter_map(|u| match blossom.mate[u] {
+            Some((v, e)) if u < v => Some(g.id2edge(e)),
+            _ => None,
+        })
+        .collect();
+
+    let mut dec = Decomposition {
+        d: vec![],
+        a: vec![],
+        c: vec![],
+    };
+    for u in 0..blossom.n {
+        if blossom.even[u] {
+            dec.d.push(g.id2node(u));
+        } else if blossom.parent[u].is_some() {
+            dec.a.push(g.id2node(u));
+        } else {
+            dec.c.push(g.id2node(u));
+        }
+    }
+
+    (matching, dec)
+}
+
+/// The data of the blossom algorithm.
+///
+/// Nodes and edges are represented by their ids.
+struct Blossom {
+    n: usize,
+    /// The adjacent edges `(edge, node)` of each node.
+    adj: Vec<Vec<(usize, usize)>>,
+    /// The mate and matching edge `(node, edge)` of each node.
+    mate: Vec<Option<(usize, usize)>>,
+    /// The parent and connecting edge `(node, edge)` of each node in the
+    /// alternating forest.
+    ///
+    /// For odd nodes this is the even node from which it has been
+    /// reached. For even nodes in a blossom this is a neighbor along
+    /// the blossom so that the augmenting path can be reconstructed.
+    parent: Vec<Option<(usize, usize)>>,
+    /// The base of the (outermost) blossom containing each node.
+    base: Vec<usize>,
+    /// Whether a node is even (in particular, reached).
+    even: Vec<bool>,
+    /// Temporary flags for the bases of a new blossom.
+    inblossom: Vec<bool>,
+    /// Temporary flags for the lca computation.
+    onpath: Vec<bool>,
+    queue: VecDeque<usize>,
+}
+
+impl Blossom {
+    fn new<G: IndexGraph>(g: &G) -> Self {
+        let n = g.num_nodes();
+        let mut adj = vec![vec![]; n];
+        for e in g.edges() {
+            let (u, v) = g.enodes(e);
+            let (u, v) = (g.node_id(u), g.node_id(v));
+            // loops are never part of a matching
+            if u != v {
+                adj[u].push((g.edge_id(e), v));
+                adj[v].push((g.edge_id(e), u));
+            }
+        }
+
+        Blossom {
+            n,
+            adj,
+            mate: vec![None; n],
+            parent: vec![None; n],
+            base: (0..n).collect(),
+            even: vec![false; n],
+            inblossom: vec![false; n],
+            onpath: vec![false; n],
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Grow alternating trees from the given free nodes.
+    ///
+    /// Returns the free end node of an augmenting path if one has been
+    /// found. If the search is started from several roots, the matching
+    /// must be maximum.
+    fn search(&mut self, roots: &[usize]) -> Option<usize> {
+        for u in 0..self.n {
+            self.parent[u] = None;
+            self.base[u] = u;
+            self.even[u] = false;
+        }
+        self.queue.clear();
+        for &r in roots {
+            self.even[r] = true;
+            self.queue.push_back(r);
+        }
+
+        while let Some(u) = self.queue.pop_front() {
+            for i in 0..self.adj[u].len() {
+                let (e, v) = self.adj[u][i];
+                if self.base[u] == self.base[v] || self.mate[u].map(|(w, _)| w) == Some(v) {
+                    continue;
+                }
+                if self.even[v] {
+                    // two adjacent even nodes, shrink the blossom
+                    let b = self.lca(u, v);
+                    for w in 0..self.n {
+                        self.inblossom[w] = false;
+                    }
+                    self.mark_path(u, b, (v, e));
+                    self.mark_path(v, b, (u, e));
+                    for w in 0..self.n {
+                        if self.inblossom[self.base[w]] {
+                            self.base[w] = b;
+                            if !self.even[w] {
+                                self.even[w] = true;
+                                self.queue.push_back(w);
+                            }
+                        }
+                    }
+                } else if self.parent[v].is_none() {
+                    self.parent[v] = Some((u, e));
+                    match self.mate[v] {
+                        None => return Some(v),
+                        Some((w, _)) => {
+                            self.even[w] = true;
+                            self.queue.push_back(w);
+                        }
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Return the base of the lowest common blossom of two even nodes
+    /// in the same tree.
+    fn lca(&mut self, mut u: usize, mut v: usize) -> usize {
+        for w in 0..self.n {
+            self.onpath[w] = false;
+        }
+        loop {
+            u = self.base[u];
+            self.onpath[u] = true;
+            match self.mate[u] {
+                Some((w, _)) => u = self.parent[w].unwrap().0,
+                None => break,
+            }
+        }
+        loop {
+            v = self.base[v];
+            if self.onpath[v] {
+                return v;
+            }
+            let (w, _) = self.mate[v].expect("Even nodes in different trees");
+            v = self.parent[w].unwrap().0;
+        }
+    }
+
+    /// Mark the blossoms on the tree path from `u` to the base `b` and
+    /// redirect the parents along the path towards `child`.
+    fn mark_path(&mut self, mut u: usize, b: usize, mut child: (usize, usize)) {
+        while self.base[u] != b {
+            let (w, _) = self.mate[u].unwrap();
+            self.inblossom[self.base[u]] = true;
+            self.inblossom[self.base[w]] = true;
+            self.parent[u] = Some(child);
+            let (p, e) = self.parent[w].unwrap();
+            child = (w, e);
+            u = p;
+        }
+    }
+
+    /// Augment the matching along the path ending at the free node `v`.
+    fn augment(&mut self, mut v: usize) {
+        loop {
+            let (u, e) = self.parent[v].unwrap();
+            let next = self.mate[u];
+            self.mate[v] = Some((u, e));
+            self.mate[u] = Some((v, e));
+            match next {
+                Some((w, _)) => v = w,
+                None => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::edmonds;
+    use crate::classes;
+    use crate::matching::bipartite_matching;
+    use crate::traits::*;
+    use crate::{Buildable, Builder, LinkedListGraph};
+
+    /// Compute the size of a maximum matching by enumeration.
+    fn brute_force(n: usize, edges: &[(usize, usize)]) -> usize {
+        // best[s] is the size of a maximum matching on the node set s
+        let mut best = vec![0; 1 << n];
+        for s in 1..1usize << n {
+            let u = s.trailing_zeros() as usize;
+            let rest = s & !(1 << u);
+            best[s] = best[rest];
+            for &(x, y) in edges {
+                let v = if x == u {
+                    y
+                } else if y == u {
+                    x
+                } else {
+                    continue;
+                };
+                if v != u && rest & (1 << v) != 0 {
+                    best[s] = best[s].max(1 + best[rest & !(1 << v)]);
+                }
+            }
+        }
+        best[(1 << n) - 1]
+    }
+
+    /// Check the matching and the properties of the decomposition.
+    fn check(g: &LinkedListGraph) -> usize {
+        let n = g.num_nodes();
+        let (matching, dec) = edmonds(g);
+
+        let mut mate = vec![None; n];
+        for &e in &matching {
+            let (u, v) = g.enodes(e);
+            let (u, v) = (g.node_id(u), g.node_id(v));
+            assert!(u != v && mate[u].is_none() && mate[v].is_none());
+            mate[u] = Some(v);
+            mate[v] = Some(u);
+        }
+
+        // 0 = D, 1 = A, 2 = C
+        let mut set = vec![3; n];
+        for (i, nodes) in [&dec.d, &dec.a, &dec.c].iter().enumerate() {
+            for &u in nodes.iter() {
+                assert_eq!(set[g.node_id(u)], 3);
+                set[g.node_id(u)] = i;
+            }
+        }
+        assert!(set.iter().all(|&s| s < 3));
+
+        // no edges between D and C, C is perfectly matched, A is matched into D
+        for e in g.edges() {
+            let (u, v) = g.enodes(e);
+            let (su, sv) = (set[g.node_id(u)], set[g.node_id(v)]);
+            assert!(su.min(sv) != 0 || su.max(sv) != 2);
+        }
+        for u in 0..n {
+            match set[u] {
+                1 => assert_eq!(set[mate[u].unwrap()], 0),
+                2 => assert_eq!(set[mate[u].unwrap()], 2),
+                _ => {}
+            }
+        }
+
+        // Tutte-Berge formula
+        let mut comp = vec![usize::MAX; n];
+        let mut k = 0;
+        for s in 0..n {
+            if set[s] != 0 || comp[s] != usize::MAX {
+                continue;
+            }
+            comp[s] = k;
+            let mut stack = vec![s];
+            while let Some(u) = stack.pop() {
+                for (_, v) in g.neighs(g.id2node(u)) {
+                    let v = g.node_id(v);
+                    if set[v] == 0 && comp[v] == usize::MAX {
+                        comp[v] = k;
+                        stack.push(v);
+                    }
+                }
+            }
+            k += 1;
+        }
+        assert_eq!(2 * matching.len(), n + dec.a.len() - k);
+
+        matching.len()
+    }
+
+    #[test]
+    fn test_classes() {
+        assert_eq!(check(&classes::cycle(7)), 3);
+        assert_eq!(check(&classes::complete_graph(9)), 4);
+        assert_eq!(check(&classes::peterson()), 5);
+        assert_eq!(check(&classes::star(5)), 1);
+    }
+
+    #[test]
+    fn test_random() {
+        let mut next = crate::testutil::lcg(3);
+
+        for _ in 0..50 {
+            let n = 4 + next(11) as usize;
+            let p = 1 + next(6);
+            let mut edges = vec![];
+            for u in 0..n {
+                for v in u..n {
+                    if next(20) < p {
+                        edges.push((u, v));
+                    }
+                }
+            }
+            let g = LinkedListGraph::<u32>::new_with(|b| {
+                let nodes = b.add_nodes(n);
+                for &(u, v) in &edges {
+                    b.add_edge(nodes[u], nodes[v]);
+                }
+            });
+
+            let size = check(&g);
+            assert_eq!(size, brute_force(n, &edges));
+            if let Some((matching, _)) = bipartite_matching(&g) {
+                assert_eq!(size, matching.len());
+            }
+        }
+    }
+}
@@ -0,0 +1,380 @@
+// Copyright (c) 2022 Frank Fischer <frank-fischer@shadow-soft.de>
+//
+// This program is free software: you can redistribute it and/or
+// modify it under the terms of the GNU General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see  <http://www.gnu.org/licenses/>
+//
+
+//! Maximum cardinality matchings in bipartite graphs.
+//!
+//! The algorithm of Hopcroft and Karp augments the matching along a
+//! maximal set of node-disjoint shortest augmenting paths in each
+//! phase. It runs in `O(m sqrt(n))` time.
+//!
+//! By König's theorem the size of a maximum matching in a bipartite
+//! graph equals the size of a minimum vertex cover. Such a cover is
+//! returned together with the matching as a certificate of optimality.
+//!
+//! # Example
+//!
+//! ```
+//! use rs_graph::{Buildable, Builder, LinkedListGraph};
+//! use rs_graph::matching::hopcroft_karp;
+//! use rs_graph::traits::*;
+//!
+//! // three employees (0, 1, 2) and three shifts (3, 4, 5)
+//! let g = LinkedListGraph::<u32>::new_with(|b| {
+//!     let nodes = b.add_nodes(6);
+//!     b.add_edge(nodes[0], nodes[3]);
+//!     b.add_edge(nodes[1], nodes[3]);
+//!     b.add_edge(nodes[2], nodes[3]);
+//!     b.add_edge(nodes[2], nodes[4]);
+//!     b.add_edge(nodes[2], nodes[5]);
+//! });
+//!
+//! let (matching, cover) = hopcroft_karp(&g, |u| g.node_id(u) < 3);
+//!
+//! // only shift 3 or employee 2 can be assigned to the others
+//! assert_eq!(matching.len(), 2);
+//! let mut cover = cover.into_iter().map(|u| g.node_id(u)).collect::<Vec<_>>();
+//! cover.sort();
+//! assert_eq!(cover, vec![2, 3]);
+//! ```
+
+use crate::traits::{GraphType, IndexGraph};
+
+use std::collections::VecDeque;
+
+/// The edges of a matching and the nodes of a vertex cover.
+type MatchingCover<'a, G> = (Vec<<G as GraphType>::Edge<'a>>, Vec<<G as GraphType>::Node<'a>>);
+
+/// Compute a bipartition of a graph.
+///
+/// Returns the nodes of one side of a bipartition, i.e. each edge
+/// connects a returned node with a node that is not returned. In each
+/// connected component the node with the smallest id is on the returned
+/// side. If the graph is not bipartite, `None` is returned.
+pub fn bipartition<G>(g: &G) -> Option<Vec<G::Node<'_>>>
+where
+    G: IndexGraph,
+{
+    let left = colors(g)?;
+    Some(g.nodes().filter(|&u| left[g.node_id(u)]).collect())
+}
+
+/// Compute a maximum matching in a bipartite graph.
+///
+/// The bipartition is given by `left`, which must return `true` for
+/// the nodes on one side and `false` for the nodes on the other side.
+/// The function returns the edges of a maximum matching and the nodes
+/// of a minimum vertex cover, both sets have the same size.
+///
+/// # Panics
+///
+/// Panics if some edge has both end nodes on the same side.
+pub fn hopcroft_karp<'a, G, P>(g: &'a G, left: P) -> MatchingCover<'a, G>
+where
+    G: IndexGraph,
+    P: Fn(G::Node<'a>) -> bool,
+{
+    let left = g.nodes().map(left).collect::<Vec<_>>();
+    solve(g, &left)
+}
+
+/// Compute a maximum matching in a bipartite graph.
+///
+/// This is the same as [`hopcroft_karp`] but the bipartition is
+/// computed automatically (see [`bipartition`]). If the graph is not
+/// bipartite, `None` is returned.
+pub fn bipartite_matching<G>(g: &G) -> Option<MatchingCover<'_, G>>
+where
+    G: IndexGraph,
+{
+    let left = colors(g)?;
+    Some(solve(g, &left))
+}
+
+/// Return the sides of a bipartition indexed by node id.
+fn colors<G>(g: &G) -> Option<Vec<bool>>
+where
+    G: IndexGraph,
+{
+    let n = g.num_nodes();
+    let mut left = vec![false; n];
+    let mut seen = vec![false; n];
+    let mut queue = VecDeque::new();
+
+    for s in 0..n {
+        if seen[s] {
+            continue;
+        }
+        seen[s] = true;
+        left[s] = true;
+        queue.push_back(s);
+        while let Some(u) = queue.pop_front() {
+            for (_, v) in g.neighs(g.id2node(u)) {
+                let v = g.node_id(v);
+                if !seen[v] {
+                    seen[v] = true;
+                    left[v] = !left[u];
+                    queue.push_back(v);
+                } else if left[v] == left[u] {
+                    return None;
+                }
+            }
+        }
+    }
+
+    Some(left)
+}
+
+fn solve<'a, G>(g: &'a G, left: &[bool]) -> MatchingCover<'a, G>
+where
+    G: IndexGraph,
+{
+    const INF: usize = usize::MAX;
+
+    let n = g.num_nodes();
+    // the adjacent edges (edge id, node id) of the left nodes
+    let mut adj = vec![vec![]; n];
+    for e in g.edges() {
+        let (u, v) = g.enodes(e);
+        let (u, v) = (g.node_id(u), g.node_id(v));
+        assert!(
+            left[u] != left[v],
+            "Edge does not connect both sides of the bipartition"
+        );
+        if left[u] {
+            adj[u].push((g.edge_id(e), v));
+        } else {
+            adj[v].push((g.edge_id(e), u));
+        }
+    }
+    let lefts = (0..n).filter(|&u| left[u]).collect::<Vec<_>>();
+
+    // the matching edge and the mate of each node
+    let mut mate: Vec<Option<(usize, usize)>> = vec![None; n];
+    // the layer of each left node in the current phase
+    let mut dist = vec![INF; n];
+    // the next adjacent edge to be considered by the search
+    let mut it = vec![0; n];
+    let mut queue = VecDeque::new();
+    let mut stack = vec![];
+    let mut path = vec![];
+
+    loop {
+        // compute the layers of shortest alternating paths starting
+        // at free left nodes
+        for &u in &lefts {
+            if mate[u].is_none() {
+                dist[u] = 0;
+                queue.push_back(u);
+            } else {
+                dist[u] = INF;
+            }
+        }
+        // the layer at which the first free right node is reachable
+        let mut limit = INF;
+        while let Some(u) = queue.pop_front() {
+            if dist[u] >= limit {
+                continue;
+            }
+            for &(_, v) in &adj[u] {
+                match mate[v] {
+                    None => limit = dist[u],
+                    Some((_, w)) if dist[w] == INF => {
+                        dist[w] = dist[u] + 1;
+                        queue.push_back(w);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if limit == INF {
+            break;
+        }
+
+        // augment along a maximal set of node-disjoint shortest paths
+        for &u in &lefts {
+            it[u] = 0;
+        }
+        for &r in &lefts {
+            if mate[r].is_some() {
+                continue;
+            }
+            stack.clear();
+            path.clear();
+            stack.push(r);
+            while let Some(&u) = stack.last() {
+                if it[u] == adj[u].len() {
+                    // dead end, remove the node from the layered network
+                    dist[u] = INF;
+                    stack.pop();
+                    path.pop();
+                    continue;
+                }
+                let (e, v) = adj[u][it[u]];
+                it[u] += 1;
+                match mate[v] {
+                    None if dist[u] == limit => {
+                        path.push((e, v));
+                        for (&u, &(e, v)) in stack.iter().zip(&path) {
+                            mate[u] = Some((e, v));
+                            mate[v] = Some((e, u));
+                            // the path must not be used again in this phase
+                            dist[u] = INF;
+                        }
+                        break;
+                    }
+                    Some((_, w)) if dist[w] == dist[u] + 1 => {
+                        stack.push(w);
+                        path.push((e, v));
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    // König's theorem: let Z be the set of nodes reachable from free
+    // left nodes by alternating paths, then (L \ Z) + (R & Z) is a
+    // minimum vertex cover.
+    let mut reached = vec![false; n];
+    for &u in &lefts {
+        if mate[u].is_none() {
+            reached[u] = true;
+            queue.push_back(u);
+        }
+    }
+    while let Some(u) = queue.pop_front() {
+        for &(_, v) in &adj[u] {
+            if !reached[v] {
+                reached[v] = true;
+                if let Some((_, w)) = mate[v] {
+                    reached[w] = true;
+                    queue.push_back(w);
+                }
+            }
+        }
+    }
+
+    let matching = lefts
+        .iter()
+        .filter_map(|&u| mate[u].map(|(e, _)| g.id2edge(e)))
+        .collect();
+    let cover = (0..n)
+        .filter(|&u| left[u] != reached[u])
+        .map(|u| g.id2node(u))
+        .collect();
+    (matching, cover)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bipartite_matching, bipartition, hopcroft_karp};
+    use crate::classes;
+    use crate::maxflow::dinic;
+    use crate::traits::*;
+    use crate::{Buildable, Builder, LinkedListGraph, Net};
+
+    /// Check that `matching` is a matching and `cover` a vertex cover of the same size.
+    fn check<G: IndexGraph>(g: &G, matching: &[G::Edge<'_>], cover: &[G::Node<'_>]) {
+        let mut matched = vec![false; g.num_nodes()];
+        for &e in matching {
+            let (u, v) = g.enodes(e);
+            assert!(!matched[g.node_id(u)] && !matched[g.node_id(v)]);
+            matched[g.node_id(u)] = true;
+            matched[g.node_id(v)] = true;
+        }
+
+        let mut incover = vec![false; g.num_nodes()];
+        for &u in cover {
+            incover[g.node_id(u)] = true;
+        }
+        for e in g.edges() {
+            let (u, v) = g.enodes(e);
+            assert!(incover[g.node_id(u)] || incover[g.node_id(v)]);
+        }
+        assert_eq!(matching.len(), cover.len());
+    }
+
+    #[test]
+    fn test_complete_bipartite() {
+        let g: LinkedListGraph = classes::complete_bipartite(4, 7);
+        let (matching, cover) = hopcroft_karp(&g, |u| g.node_id(u) < 4);
+        check(&g, &matching, &cover);
+        assert_eq!(matching.len(), 4);
+        assert!(cover.iter().all(|&u| g.node_id(u) < 4));
+    }
+
+    #[test]
+    fn test_not_bipartite() {
+        let g: LinkedListGraph = classes::cycle(5);
+        assert!(bipartition(&g).is_none());
+        assert!(bipartite_matching(&g).is_none());
+
+        let g: LinkedListGraph = classes::cycle(6);
+        let left = bipartition(&g).unwrap();
+        assert_eq!(left.len(), 3);
+        let (matching, cover) = bipartite_matching(&g).unwrap();
+        check(&g, &matching, &cover);
+        assert_eq!(matching.len(), 3);
+    }
+
+    #[test]
+    fn test_random() {
+        let mut next = crate::testutil::lcg(17);
+
+        for _ in 0..30 {
+            let (n, m) = (5 + next(20) as usize, 5 + next(20) as usize);
+            let p = 1 + next(5);
+            let mut edges = vec![];
+            for u in 0..n {
+                for v in 0..m {
+                    if next(20) < p {
+                        edges.push((u, n + v));
+                    }
+                }
+            }
+
+            let g = LinkedListGraph::<u32>::new_with(|b| {
+                let nodes = b.add_nodes(n + m);
+                for &(u, v) in &edges {
+                    b.add_edge(nodes[u], nodes[v]);
+                }
+            });
+            let (matching, cover) = hopcroft_karp(&g, |u| g.node_id(u) < n);
+            check(&g, &matching, &cover);
+
+            let (matching2, cover2) = bipartite_matching(&g).unwrap();
+            check(&g, &matching2, &cover2);
+            assert_eq!(matching.len(), matching2.len());
+
+            // compare with a maximum flow
+            let net = Net::new_with(|b| {
+                let nodes = b.add_nodes(n + m + 2);
+                for u in 0..n {
+                    b.add_edge(nodes[n + m], nodes[u]);
+                }
+                for v in n..n + m {
+                    b.add_edge(nodes[v], nodes[n + m + 1]);
+                }
+                for &(u, v) in &edges {
+                    b.add_edge(nodes[u], nodes[v]);
+                }
+            });
+            let (value, _, _) = dinic(&net, net.id2node(n + m), net.id2node(n + m + 1), |_| 1);
+            assert_eq!(matching.len(), value);
+        }
+    }
+}
@@ -0,0 +1,394 @@
+// Copyright (c) 2022 Frank Fischer <frank-fischer@shadow-soft.de>
+//
+// This program is free software: you can redistribute it and/or
+// modify it under the terms of the GNU General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see  <http://www.gnu.org/licenses/>
+//
+
+//! The Hungarian method for the linear assignment problem.
+//!
+//! Given an `n x m` cost matrix, the task is to assign each row to a
+//! different column (or each column to a different row if `n > m`) such
+//! that the total cost is minimal. The algorithm of Kuhn and Munkres
+//! (with the shortest path implementation of Jonker and Volgenant) runs
+//! in `O(n^2 m)` time for `n <= m` and only needs the cost matrix, which
+//! makes it suitable for many small dense problems.
+//!
+//! Optimality is certified by potentials `u` of the rows and `v` of
+//! the columns with `u[i] + v[j] <= c[i][j]` for all allowed pairs,
+//! equality for assigned pairs and the following sign condition: the
+//! potentials of the larger side are non-positive and zero for each
+//! unassigned element (if `n == m` the potentials are unrestricted).
+//!
+//! The potentials may become negative even for non-negative costs,
+//! hence the cost type must be signed.
+//!
+//! # Example
+//!
+//! ```
+//! use rs_graph::matching::hungarian;
+//!
+//! let costs = [[4, 1, 3, 7], [2, 0, 5, 3], [3, 2, 6, 4]];
+//! // row 0 must not be assigned to column 1
+//! let a = hungarian(3, 4, |i, j| if (i, j) == (0, 1) { None } else { Some(costs[i][j]) }).unwrap();
+//!
+//! assert_eq!(a.value, 3 + 0 + 3);
+//! assert_eq!(a.rows, vec![Some(2), Some(1), Some(0)]);
+//! assert_eq!(a.cols, vec![Some(2), Some(1), Some(0), None]);
+//! ```
+
+use crate::traits::IndexGraph;
+
+use crate::num::traits::{NumAssign, Signed};
+
+/// The solution of an assignment problem.
+#[derive(Clone, Debug)]
+pub struct Assignment<W> {
+    /// The total cost of the assignment.
+    pub value: W,
+    /// The column assigned to each row.
+    pub rows: Vec<Option<usize>>,
+    /// The row assigned to each column.
+    pub cols: Vec<Option<usize>>,
+    /// The potential of each row.
+    pub row_potentials: Vec<W>,
+    /// The potential of each column.
+    pub col_potentials: Vec<W>,
+}
+
+/// Solve an assignment problem with the Hungarian method.
+///
+/// The matrix has `n` rows and `m` columns, `costs(i, j)` is the cost of
+/// assigning row `i` to column `j` or `None` if this pair is forbidden.
+/// The cost function is evaluated several times for each pair, so it
+/// should be cheap.
+///
+/// If `n <= m` each row gets a column, otherwise each column gets a
+/// row. If no such assignment exists due to forbidden pairs, `None` is
+/// returned.
+pub fn hungarian<W, Cs>(n: usize, m: usize, costs: Cs) -> Option<Assignment<W>>
+where
+    W: NumAssign + Signed + Ord + Copy,
+    Cs: Fn(usize, usize) -> Option<W>,
+{
+    if n <= m {
+        let (rows, cols, row_potentials, col_potentials) = solve(n, m, &costs)?;
+        let value = rows
+            .iter()
+            .enumerate()
+            .fold(W::zero(), |value, (i, &j)| value + costs(i, j.unwrap()).unwrap());
+        Some(Assignment {
+            value,
+            rows,
+            cols,
+            row_potentials,
+            col_potentials,
+        })
+    } else {
+        let (cols, rows, col_potentials, row_potentials) = solve(m, n, &|j, i| costs(i, j))?;
+        let value = cols
+            .iter()
+            .enumerate()
+            .fold(W::zero(), |value, (j, &i)| value + costs(i.unwrap(), j).unwrap());
+        Some(Assignment {
+            value,
+            rows,
+            cols,
+            row_potentials,
+            col_potentials,
+        })
+    }
+}
+
+/// Solve an assignment problem on a bipartite graph.
+///
+/// The first `n` nodes of the graph are the rows, the remaining nodes
+/// are the columns (this is the layout of
+/// [`complete_bipartite`](crate::classes::complete_bipartite)). Each
+/// edge must connect a row with a column, missing edges are forbidden
+/// pairs. The cost of each edge is given by `costs`.
+///
+/// The function returns the cost of the assignment, its edges and the
+/// potential of each node (indexed by node id). If the smaller side
+/// cannot be assigned completely, `None` is returned.
+///
+/// # Example
+///
+/// ```
+/// use rs_graph::{classes, LinkedListGraph};
+/// use rs_graph::matching::hungarian_graph;
+/// use rs_graph::traits::*;
+///
+/// let g: LinkedListGraph = classes::complete_bipartite(3, 3);
+/// let costs = [3, 1, 2, 8, 5, 9, 2, 1, 6];
+/// let (value, edges, _) = hungarian_graph(&g, 3, |e| costs[g.edge_id(e)]).unwrap();
+/// assert_eq!(value, 2 + 5 + 2);
+/// assert_eq!(edges.len(), 3);
+/// ```
+#[allow(clippy::type_complexity)]
+pub fn hungarian_graph<'a, G, W, Cs>(g: &'a G, n: usize, costs: Cs) -> Option<(W, Vec<G::Edge<'a>>, Vec<W>)>
+where
+    G: IndexGraph,
+    W: NumAssign + Signed + Ord + Copy,
+    Cs: Fn(G::Edge<'a>) -> W,
+{
+    let m = g.num_nodes() - n;
+    // the cheapest edge for each pair
+    let mut matrix: Vec<Option<(W, usize)>> = vec![None; n * m];
+    for e in g.edges() {
+        let (u, v) = g.enodes(e);
+        let (u, v) = (g.node_id(u), g.node_id(v));
+        let (i, j) = if u < n { (u, v) } else { (v, u) };
+        assert!(i < n && j >= n, "Edge does not connect a row and a column");
+        let c = costs(e);
+        let entry = &mut matrix[i * m + j - n];
+        if !matches!(*entry, Some((d, _)) if d <= c) {
+            *entry = Some((c, g.edge_id(e)));
+        }
+    }
+
+    let a = hungarian(n, m, |i, j| matrix[i * m + j].map(|(c, _)| c))?;
+    let edges = a
+        .rows
+        .iter()
+        .enumerate()
+        .filter_map(|(i, &j)| j.map(|j| g.id2edge(matrix[i * m + j].unwrap().1)))
+        .collect();
+    let mut potentials = a.row_potentials;
+    potentials.extend(a.col_potentials);
+    Some((a.value, edges, potentials))
+}
+
+/// Solve the assignment problem for `n <= m`.
+///
+/// Returns the assigned columns of the rows, the assigned rows of the
+/// columns and the potentials of rows and columns.
+#[allow(clippy::type_complexity)]
+fn solve<W, Cs>(n: usize, m: usize, costs: &Cs) -> Option<(Vec<Option<usize>>, Vec<Option<usize>>, Vec<W>, Vec<W>)>
+where
+    W: NumAssign + Signed + Ord + Copy,
+    Cs: Fn(usize, usize) -> Option<W>,
+{
+    debug_assert!(n <= m);
+
+    // Rows and columns are numbered starting at 1, column 0 is an
+    // artificial column assigned to the row being inserted.
+    let mut u = vec![W::zero(); n + 1];
+    let mut v = vec![W::zero(); m + 1];
+    // the row assigned to each column (0 if unassigned)
+    let mut p = vec![0; m + 1];
+    // the predecessor column on the shortest path
+    let mut way = vec![0; m + 1];
+    // the reduced path length to each column (None = infinite)
+    let mut minv: Vec<Option<W>> = vec![None; m + 1];
+    let mut used = vec![false; m + 1];
+
+    for i in 1..=n {
+        p[0] = i;
+        let mut j0 = 0;
+        for j in 0..=m {
+            minv[j] = None;
+            used[j] = false;
+        }
+
+        // grow shortest paths until a free column is reached
+        loop {
+            used[j0] = true;
+            let i0 = p[j0];
+            let mut delta = None;
+            let mut j1 = 0;
+            for j in 1..=m {
+                if used[j] {
+                    continue;
+                }
+                if let Some(c) = costs(i0 - 1, j - 1) {
+                    let cur = c - u[i0] - v[j];
+                    if !matches!(minv[j], Some(d) if d <= cur) {
+                        minv[j] = Some(cur);
+                        way[j] = j0;
+                    }
+                }
+                if let Some(d) = minv[j] {
+                    if !matches!(delta, Some(delta) if delta <= d) {
+                        delta = Some(d);
+                        j1 = j;
+                    }
+                }
+            }
+
+            // no free column is reachable
+            let delta = delta?;
+
+            for j in 0..=m {
+                if used[j] {
+                    u[p[j]] += delta;
+                    v[j] -= delta;
+                } else if let Some(d) = minv[j].as_mut() {
+                    *d -= delta;
+                }
+            }
+
+            j0 = j1;
+            if p[j0] == 0 {
+                break;
+            }
+        }
+
+        // augment along the shortest path
+        loop {
+            let j1 = way[j0];
+            p[j0] = p[j1];
+            j0 = j1;
+            if j0 == 0 {
+                break;
+            }
+        }
+    }
+
+    let mut rows = vec![None; n];
+    let mut cols = vec![None; m];
+    for j in 1..=m {
+        if p[j] != 0 {
+            rows[p[j] - 1] = Some(j - 1);
+            cols[j - 1] = Some(p[j] - 1);
+        }
+    }
+
+    Some((rows, cols, u.split_off(1), v.split_off(1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hungarian, hungarian_graph};
+    use crate::classes;
+    use crate::traits::*;
+    use crate::LinkedListGraph;
+
+    /// Compute the optimal value by enumeration.
+    fn brute_force(n: usize, m: usize, costs: &[Vec<Option<i64>>]) -> Option<i64> {
+        // best[s] is the optimal cost of assigning the first rows to the columns in s
+        let mut best = vec![None; 1 << m];
+        best[0] = Some(0);
+        for s in 0usize..1 << m {
+            let i = s.count_ones() as usize;
+            if i >= n {
+                continue;
+            }
+            if let Some(b) = best[s] {
+                for (j, &c) in costs[i].iter().enumerate() {
+                    if s & (1 << j) == 0 {
+                        if let Some(c) = c {
+                            let t = s | (1 << j);
+                            best[t] = Some(best[t].map_or(b + c, |x: i64| x.min(b + c)));
+                        }
+                    }
+                }
+            }
+        }
+        (0usize..1 << m)
+            .filter(|s| s.count_ones() as usize == n)
+            .filter_map(|s| best[s])
+            .min()
+    }
+
+    #[test]
+    #[allow(clippy::needless_range_loop)]
+    fn test_random() {
+        let mut next = crate::testutil::lcg(7);
+
+        let mut nfeasible = 0;
+        for _ in 0..300 {
+            let n = 1 + next(7) as usize;
+            let m = 1 + next(7) as usize;
+            let forbidden = next(4);
+            let costs = (0..n)
+                .map(|_| {
+                    (0..m)
+                        .map(|_| {
+                            if next(10) < forbidden {
+                                None
+                            } else {
+                                Some(next(50) as i64 - 10)
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+
+            let best = if n <= m {
+                brute_force(n, m, &costs)
+            } else {
+                let transposed = (0..m)
+                    .map(|j| (0..n).map(|i| costs[i][j]).collect())
+                    .collect::<Vec<_>>();
+                brute_force(m, n, &transposed)
+            };
+
+            let a = match hungarian(n, m, |i, j| costs[i][j]) {
+                Some(a) => a,
+                None => {
+                    assert_eq!(best, None);
+                    continue;
+                }
+            };
+            nfeasible += 1;
+            assert_eq!(Some(a.value), best);
+
+            // check the assignment and the potentials
+            assert_eq!(a.rows.iter().filter(|j| j.is_some()).count(), n.min(m));
+            for i in 0..n {
+                if let Some(j) = a.rows[i] {
+                    assert_eq!(a.cols[j], Some(i));
+                }
+                for j in 0..m {
+                    if let Some(c) = costs[i][j] {
+                        assert!(a.row_potentials[i] + a.col_potentials[j] <= c);
+                        if a.rows[i] == Some(j) {
+                            assert_eq!(a.row_potentials[i] + a.col_potentials[j], c);
+                        }
+                    }
+                }
+            }
+            if n < m {
+                for j in 0..m {
+                    assert!(a.col_potentials[j] <= 0);
+                    assert!(a.cols[j].is_some() || a.col_potentials[j] == 0);
+                }
+            } else if n > m {
+                for i in 0..n {
+                    assert!(a.row_potentials[i] <= 0);
+                    assert!(a.rows[i].is_some() || a.row_potentials[i] == 0);
+                }
+            }
+        }
+        assert!(nfeasible > 100);
+    }
+
+    #[test]
+    fn test_complete_bipartite() {
+        let g: LinkedListGraph = classes::complete_bipartite(4, 6);
+        let cost = |e| {
+            let (u, v) = g.enodes(e);
+            let (i, j) = (g.node_id(u) as i64, g.node_id(v) as i64 - 4);
+            (3 * i + 5 * j) % 7
+        };
+
+        let (value, edges, potentials) = hungarian_graph(&g, 4, cost).unwrap();
+        let a = hungarian(4, 6, |i, j| Some((3 * i as i64 + 5 * j as i64) % 7)).unwrap();
+        assert_eq!(value, a.value);
+        assert_eq!(edges.iter().map(|&e| cost(e)).sum::<i64>(), value);
+        for e in g.edges() {
+            let (u, v) = g.enodes(e);
+            assert!(potentials[g.node_id(u)] + potentials[g.node_id(v)] <= cost(e));
+        }
+    }
+}
@@ -0,0 +1,29 @@
+// Copyright (c) 2022 Frank Fischer <frank-fischer@shadow-soft.de>
+//
+// This program is free software: you can redistribute it and/or
+// modify it under the terms of the GNU General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see  <http://www.gnu.org/licenses/>
+//
+
+//! Matching algorithms.
+
+pub mod edmonds;
+pub use self::edmonds::{edmonds, Decomposition};
+
+pub mod hopcroftkarp;
+pub use self::hopcroftkarp::{bipartite_matching, bipartition, hopcroft_karp};
+
+pub mod hungarian;
+pub use self::hungarian::{hungarian, hungarian_graph, Assignment};
+
+pub mod weighted;
+pub use self::weighted::{max_weight_matching, min_cost_perfect_matching, MatchingDuals};
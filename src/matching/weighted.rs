@@ -0,0 +1,945 @@
+// Copyright (c) 2022 Frank Fischer <frank-fischer@shadow-soft.de>
+//
+// This program is free software: you can redistribute it and/or
+// modify it under the terms of the GNU General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see  <http://www.gnu.org/licenses/>
+//
+
+//! Weighted matchings in general graphs.
+//!
+//! This is the primal-dual blossom algorithm of Edmonds with the
+//! implementation techniques of Gabow and of Galil, Micali and Gabow.
+//! It runs in `O(n^3)` time. Note that this is not one of the faster
+//! but much more involved variants like Blossom V by Kolmogorov.
+//!
+//! The implementation follows the Python module `mwmatching` by Joris
+//! van Rantwijk.
+//!
+//! The algorithm maintains a dual solution consisting of a value `y(v)`
+//! for each node and a value `z(B) >= 0` for each blossom `B` (an odd
+//! set of nodes). In order to keep all computations integral, the dual
+//! values are doubled, i.e. the dual constraint of an edge `e = {u,v}`
+//! of a maximum-weight matching problem reads
+//!
+//! ```text
+//! y(u) + y(v) + sum { z(B) : u,v in B } >= 2 w(e)
+//! ```
+//!
+//! and is satisfied with equality for each matching edge. Furthermore
+//! `y(v) >= 0` with equality for each unmatched node and each blossom
+//! `B` with `z(B) > 0` contains exactly `(|B| - 1) / 2` matching edges.
+//! In particular, twice the weight of the matching equals
+//! `sum y(v) + sum z(B) (|B| - 1) / 2`.
+//!
+//! # Example
+//!
+//! ```
+//! use rs_graph::{Buildable, Builder, LinkedListGraph};
+//! use rs_graph::matching::{max_weight_matching, min_cost_perfect_matching};
+//! use rs_graph::traits::*;
+//!
+//! let g = LinkedListGraph::<u32>::new_with(|b| {
+//!     let nodes = b.add_nodes(4);
+//!     b.add_edge(nodes[0], nodes[1]);
+//!     b.add_edge(nodes[1], nodes[2]);
+//!     b.add_edge(nodes[2], nodes[3]);
+//!     b.add_edge(nodes[3], nodes[0]);
+//!     b.add_edge(nodes[0], nodes[2]);
+//! });
+//! let weights = [3, 8, 3, -1, 5];
+//!
+//! // edge {3,0} has negative weight, so {1,2} alone is optimal
+//! let (value, matching, _) = max_weight_matching(&g, |e| weights[g.edge_id(e)]);
+//! assert_eq!(value, 8);
+//! assert_eq!(matching.len(), 1);
+//!
+//! let (value, matching, _) = min_cost_perfect_matching(&g, |e| weights[g.edge_id(e)]).unwrap();
+//! assert_eq!(value, 6);
+//! assert_eq!(matching.len(), 2);
+//! ```
+
+use crate::traits::IndexGraph;
+
+use crate::num::traits::{NumAssign, Signed};
+
+const NONE: usize = usize::MAX;
+
+/// The (doubled) dual solution of a weighted matching problem.
+#[derive(Clone, Debug)]
+pub struct MatchingDuals<N, W> {
+    /// The dual value of each node, indexed by node id.
+    pub nodes: Vec<W>,
+    /// The blossoms with non-zero dual value.
+    ///
+    /// Each blossom is given by its nodes and its dual value.
+    pub blossoms: Vec<(Vec<N>, W)>,
+}
+
+/// Compute a matching of maximal weight.
+///
+/// The weight of each edge is given by `weights`. The function returns
+/// the weight of the matching, its edges and an optimal dual solution
+/// as described in the [module documentation](self). Edges with
+/// negative weight are never part of the matching, loops are ignored.
+pub fn max_weight_matching<'a, G, W, Ws>(g: &'a G, weights: Ws) -> (W, Vec<G::Edge<'a>>, MatchingDuals<G::Node<'a>, W>)
+where
+    G: IndexGraph,
+    W: NumAssign + Ord + Copy,
+    Ws: Fn(G::Edge<'a>) -> W,
+{
+    let mut solver = Solver::new(g, weights);
+    solver.solve(false);
+    let (matching, duals) = solver.solution(g, false);
+    let value = matching.iter().fold(W::zero(), |value, &(_, w)| value + w);
+    (value, matching.into_iter().map(|(e, _)| e).collect(), duals)
+}
+
+/// Compute a perfect matching of minimal cost.
+///
+/// The cost of each edge is given by `costs`. The function returns the
+/// cost of the matching, its edges and an optimal dual solution. If
+/// the graph has no perfect matching, `None` is returned.
+///
+/// The dual solution is the negation of the dual solution of the
+/// maximum-weight perfect matching problem with weights `-c(e)`, i.e.
+/// the dual constraint of an edge `e = {u,v}` reads
+///
+/// ```text
+/// y(u) + y(v) - sum { z(B) : u,v in B } <= 2 c(e)
+/// ```
+///
+/// with `z(B) >= 0` (the node values are not restricted in sign). It is
+/// satisfied with equality for each matching edge and twice the cost of
+/// the matching equals `sum y(v) - sum z(B) (|B| - 1) / 2`.
+///
+/// The costs are negated internally, hence the cost type must be signed.
+#[allow(clippy::type_complexity)]
+pub fn min_cost_perfect_matching<'a, G, W, Cs>(
+    g: &'a G,
+    costs: Cs,
+) -> Option<(W, Vec<G::Edge<'a>>, MatchingDuals<G::Node<'a>, W>)>
+where
+    G: IndexGraph,
+    W: NumAssign + Signed + Ord + Copy,
+    Cs: Fn(G::Edge<'a>) -> W,
+{
+    let mut solver = Solver::new(g, |e| W::zero() - costs(e));
+    solver.solve(true);
+    let (matching, duals) = solver.solution(g, true);
+    if 2 * matching.len() != g.num_nodes() {
+        return None;
+    }
+    let value = matching.iter().fold(W::zero(), |value, &(_, w)| value - w);
+    Some((value, matching.into_iter().map(|(e, _)| e).collect(), duals))
+}
+
+/// Return the element at position `j` of a cyclic sequence.
+///
+/// Negative positions count from the end.
+fn at(v: &[usize], j: isize) -> usize {
+    if j < 0 {
+        v[(v.len() as isize + j) as usize]
+    } else {
+        v[j as usize]
+    }
+}
+
+/// The data of the primal-dual algorithm.
+///
+/// Nodes are numbered `0..n`, blossoms `n..2n`. Each edge `k` has two
+/// endpoints `2k` and `2k+1`, endpoint `p` belongs to node
+/// `endpoint[p]` and the other endpoint of the same edge is `p ^ 1`.
+struct Solver<W> {
+    n: usize,
+    /// The edges `(u, v, weight)` (loops removed).
+    edges: Vec<(usize, usize, W)>,
+    /// The ids of the edges in the original graph.
+    edge_ids: Vec<usize>,
+    /// The node of each endpoint.
+    endpoint: Vec<usize>,
+    /// The remote endpoints of the edges incident to each node.
+    neighbend: Vec<Vec<usize>>,
+    /// The remote endpoint of the matching edge of each node.
+    mate: Vec<usize>,
+    /// The label of each node and top-level blossom (0 = free, 1 = S, 2 = T).
+    ///
+    /// Bit 4 is used as temporary mark in `scan_blossom`.
+    label: Vec<u8>,
+    /// The endpoint through which a labelled node or blossom has been reached.
+    labelend: Vec<usize>,
+    /// The top-level blossom containing each node.
+    inblossom: Vec<usize>,
+    /// The parent of each node or blossom in the blossom hierarchy.
+    blossomparent: Vec<usize>,
+    /// The sub-blossoms of each blossom, starting at the base.
+    blossomchilds: Vec<Vec<usize>>,
+    /// The base node of each blossom.
+    blossombase: Vec<usize>,
+    /// The endpoints of the edges connecting the sub-blossoms.
+    blossomendps: Vec<Vec<usize>>,
+    /// The least-slack edge to a different S-blossom (for S-blossoms) or
+    /// to an S-blossom (for free nodes).
+    bestedge: Vec<usize>,
+    /// The least-slack edges to neighboring S-blossoms of each S-blossom.
+    blossombestedges: Vec<Option<Vec<usize>>>,
+    /// The unused blossom numbers.
+    unusedblossoms: Vec<usize>,
+    /// The dual values of the nodes and (half of) the blossoms.
+    dualvar: Vec<W>,
+    /// Whether an edge is known to have zero slack.
+    allowedge: Vec<bool>,
+    /// The S-nodes to be scanned.
+    queue: Vec<usize>,
+}
+
+impl<W> Solver<W>
+where
+    W: NumAssign + Ord + Copy,
+{
+    fn new<'a, G, Ws>(g: &'a G, weights: Ws) -> Self
+    where
+        G: IndexGraph,
+        Ws: Fn(G::Edge<'a>) -> W,
+    {
+        let n = g.num_nodes();
+        let mut edges = vec![];
+        let mut edge_ids = vec![];
+        for e in g.edges() {
+            let (u, v) = g.enodes(e);
+            let (u, v) = (g.node_id(u), g.node_id(v));
+            if u != v {
+                edges.push((u, v, weights(e)));
+                edge_ids.push(g.edge_id(e));
+            }
+        }
+
+        let maxweight = edges.iter().fold(W::zero(), |maxw, &(_, _, w)| maxw.max(w));
+        let mut endpoint = Vec::with_capacity(2 * edges.len());
+        let mut neighbend = vec![vec![]; n];
+        for (k, &(u, v, _)) in edges.iter().enumerate() {
+            endpoint.push(u);
+            endpoint.push(v);
+            neighbend[u].push(2 * k + 1);
+            neighbend[v].push(2 * k);
+        }
+
+        let mut dualvar = vec![maxweight; n];
+        dualvar.resize(2 * n, W::zero());
+
+        Solver {
+            n,
+            allowedge: vec![false; edges.len()],
+            edges,
+            edge_ids,
+            endpoint,
+            neighbend,
+            mate: vec![NONE; n],
+            label: vec![0; 2 * n],
+            labelend: vec![NONE; 2 * n],
+            inblossom: (0..n).collect(),
+            blossomparent: vec![NONE; 2 * n],
+            blossomchilds: vec![vec![]; 2 * n],
+            blossombase: (0..n).chain((0..n).map(|_| NONE)).collect(),
+            blossomendps: vec![vec![]; 2 * n],
+            bestedge: vec![NONE; 2 * n],
+            blossombestedges: vec![None; 2 * n],
+            unusedblossoms: (n..2 * n).collect(),
+            dualvar,
+            queue: vec![],
+        }
+    }
+
+    /// Return the matching edges with their weights and the dual solution.
+    #[allow(clippy::type_complexity)]
+    fn solution<'a, G>(&self, g: &'a G, negate: bool) -> (Vec<(G::Edge<'a>, W)>, MatchingDuals<G::Node<'a>, W>)
+    where
+        G: IndexGraph,
+    {
+        let two = W::one() + W::one();
+        let sign = |x: W| if negate { W::zero() - x } else { x };
+
+        let matching = (0..self.n)
+            .filter(|&v| self.mate[v] != NONE && v < self.endpoint[self.mate[v]])
+            .map(|v| self.mate[v] / 2)
+            .map(|k| (g.id2edge(self.edge_ids[k]), self.edges[k].2))
+            .collect();
+
+        let duals = MatchingDuals {
+            nodes: self.dualvar[..self.n].iter().map(|&y| sign(y)).collect(),
+            blossoms: (self.n..2 * self.n)
+                .filter(|&b| self.blossombase[b] != NONE && self.dualvar[b] != W::zero())
+                .map(|b| {
+                    let mut nodes = self.leaves(b);
+                    nodes.sort_unstable();
+                    (nodes.into_iter().map(|v| g.id2node(v)).collect(), two * self.dualvar[b])
+                })
+                .collect(),
+        };
+
+        (matching, duals)
+    }
+
+    /// The slack of edge `k` (without blossom duals).
+    fn slack(&self, k: usize) -> W {
+        let (u, v, w) = self.edges[k];
+        self.dualvar[u] + self.dualvar[v] - (w + w)
+    }
+
+    /// Return the nodes contained in blossom `b`.
+    fn leaves(&self, b: usize) -> Vec<usize> {
+        let mut leaves = vec![];
+        let mut stack = vec![b];
+        while let Some(t) = stack.pop() {
+            if t < self.n {
+                leaves.push(t);
+            } else {
+                stack.extend(self.blossomchilds[t].iter().rev());
+            }
+        }
+        leaves
+    }
+
+    /// Assign label `t` to the top-level blossom containing node `w`,
+    /// which has been reached via endpoint `p`.
+    ///
+    /// If the label is T, the mate of the base is labelled S.
+    fn assign_label(&mut self, mut w: usize, mut t: u8, mut p: usize) {
+        loop {
+            let b = self.inblossom[w];
+            debug_assert!(self.label[w] == 0 && self.label[b] == 0);
+            self.label[w] = t;
+            self.label[b] = t;
+            self.labelend[w] = p;
+            self.labelend[b] = p;
+            self.bestedge[w] = NONE;
+            self.bestedge[b] = NONE;
+            if t == 1 {
+                let leaves = self.leaves(b);
+                self.queue.extend(leaves);
+                return;
+            }
+            let base = self.blossombase[b];
+            let m = self.mate[base];
+            w = self.endpoint[m];
+            t = 1;
+            p = m ^ 1;
+        }
+    }
+
+    /// Trace back from two S-nodes to find a new blossom or an
+    /// augmenting path.
+    ///
+    /// Returns the base of the new blossom or `NONE` if an augmenting
+    /// path has been found.
+    fn scan_blossom(&mut self, mut v: usize, mut w: usize) -> usize {
+        let mut path = vec![];
+        let mut base = NONE;
+        while v != NONE {
+            let b = self.inblossom[v];
+            if self.label[b] & 4 != 0 {
+                base = self.blossombase[b];
+                break;
+            }
+            path.push(b);
+            self.label[b] = 5;
+            if self.labelend[b] == NONE {
+                // reached the root
+                v = NONE;
+            } else {
+                v = self.endpoint[self.labelend[b]];
+                let b = self.inblossom[v];
+                v = self.endpoint[self.labelend[b]];
+            }
+            // alternate between both paths
+            if w != NONE {
+                std::mem::swap(&mut v, &mut w);
+            }
+        }
+        for b in path {
+            self.label[b] = 1;
+        }
+        base
+    }
+
+    /// Construct a new blossom with base `base` closed by edge `k`.
+    fn add_blossom(&mut self, base: usize, k: usize) {
+        let (mut v, mut w, _) = self.edges[k];
+        let bb = self.inblossom[base];
+        let mut bv = self.inblossom[v];
+        let mut bw = self.inblossom[w];
+
+        let b = self.unusedblossoms.pop().unwrap();
+        self.blossombase[b] = base;
+        self.blossomparent[b] = NONE;
+        self.blossomparent[bb] = b;
+
+        // trace back from v to the base
+        let mut path = vec![];
+        let mut endps = vec![];
+        while bv != bb {
+            self.blossomparent[bv] = b;
+            path.push(bv);
+            endps.push(self.labelend[bv]);
+            v = self.endpoint[self.labelend[bv]];
+            bv = self.inblossom[v];
+        }
+        path.push(bb);
+        path.reverse();
+        endps.reverse();
+        endps.push(2 * k);
+        // trace back from w to the base
+        while bw != bb {
+            self.blossomparent[bw] = b;
+            path.push(bw);
+            endps.push(self.labelend[bw] ^ 1);
+            w = self.endpoint[self.labelend[bw]];
+            bw = self.inblossom[w];
+        }
+        self.blossomchilds[b] = path;
+        self.blossomendps[b] = endps;
+
+        self.label[b] = 1;
+        self.labelend[b] = self.labelend[bb];
+        self.dualvar[b] = W::zero();
+
+        // former T-nodes become S-nodes
+        for v in self.leaves(b) {
+            if self.label[self.inblossom[v]] == 2 {
+                self.queue.push(v);
+            }
+            self.inblossom[v] = b;
+        }
+
+        // compute the least-slack edges to neighboring S-blossoms
+        let mut bestedgeto = vec![NONE; 2 * self.n];
+        for i in 0..self.blossomchilds[b].len() {
+            let bv = self.blossomchilds[b][i];
+            let nblist = match self.blossombestedges[bv].take() {
+                Some(nblist) => nblist,
+                None => self
+                    .leaves(bv)
+                    .into_iter()
+                    .flat_map(|v| self.neighbend[v].iter().map(|&p| p / 2))
+                    .collect(),
+            };
+            for k in nblist {
+                let (mut i, mut j, _) = self.edges[k];
+                if self.inblossom[j] == b {
+                    std::mem::swap(&mut i, &mut j);
+                }
+                let bj = self.inblossom[j];
+                if bj != b
+                    && self.label[bj] == 1
+                    && (bestedgeto[bj] == NONE || self.slack(k) < self.slack(bestedgeto[bj]))
+                {
+                    bestedgeto[bj] = k;
+                }
+            }
+            self.bestedge[bv] = NONE;
+        }
+        let bestedges = bestedgeto.into_iter().filter(|&k| k != NONE).collect::<Vec<_>>();
+        self.bestedge[b] = NONE;
+        for &k in &bestedges {
+            if self.bestedge[b] == NONE || self.slack(k) < self.slack(self.bestedge[b]) {
+                self.bestedge[b] = k;
+            }
+        }
+        self.blossombestedges[b] = Some(bestedges);
+    }
+
+    /// Expand the top-level blossom `b`.
+    ///
+    /// If `endstage` is true, all sub-blossoms with zero dual value are
+    /// expanded recursively.
+    fn expand_blossom(&mut self, b: usize, endstage: bool) {
+        for i in 0..self.blossomchilds[b].len() {
+            let s = self.blossomchilds[b][i];
+            self.blossomparent[s] = NONE;
+            if s < self.n {
+                self.inblossom[s] = s;
+            } else if endstage && self.dualvar[s] == W::zero() {
+                self.expand_blossom(s, endstage);
+            } else {
+                for v in self.leaves(s) {
+                    self.inblossom[v] = s;
+                }
+            }
+        }
+
+        if !endstage && self.label[b] == 2 {
+            // Relabel the sub-blossoms on the even length path from
+            // the entry child to the base as T and S alternatingly.
+            let childs = std::mem::take(&mut self.blossomchilds[b]);
+            let endps = std::mem::take(&mut self.blossomendps[b]);
+            let len = childs.len() as isize;
+            let entrychild = self.inblossom[self.endpoint[self.labelend[b] ^ 1]];
+            let mut j = childs.iter().position(|&c| c == entrychild).unwrap() as isize;
+            let (jstep, endptrick) = if j & 1 != 0 {
+                j -= len;
+                (1, 0)
+            } else {
+                (-1, 1)
+            };
+
+            let mut p = self.labelend[b];
+            while j != 0 {
+                self.label[self.endpoint[p ^ 1]] = 0;
+                let q = at(&endps, j - endptrick as isize) ^ endptrick ^ 1;
+                self.label[self.endpoint[q]] = 0;
+                self.assign_label(self.endpoint[p ^ 1], 2, p);
+                self.allowedge[at(&endps, j - endptrick as isize) / 2] = true;
+                j += jstep;
+                p = at(&endps, j - endptrick as isize) ^ endptrick;
+                self.allowedge[p / 2] = true;
+                j += jstep;
+            }
+
+            // the base gets the label of the blossom
+            let bv = at(&childs, j);
+            self.label[self.endpoint[p ^ 1]] = 2;
+            self.label[bv] = 2;
+            self.labelend[self.endpoint[p ^ 1]] = p;
+            self.labelend[bv] = p;
+            self.bestedge[bv] = NONE;
+
+            // the remaining sub-blossoms on the odd length path may have
+            // been reached from outside
+            j += jstep;
+            while at(&childs, j) != entrychild {
+                let bv = at(&childs, j);
+                j += jstep;
+                if self.label[bv] == 1 {
+                    continue;
+                }
+                let reached = self.leaves(bv).into_iter().find(|&v| self.label[v] != 0);
+                if let Some(v) = reached {
+                    debug_assert_eq!(self.label[v], 2);
+                    debug_assert_eq!(self.inblossom[v], bv);
+                    self.label[v] = 0;
+                    let m = self.mate[self.blossombase[bv]];
+                    self.label[self.endpoint[m]] = 0;
+                    self.assign_label(v, 2, self.labelend[v]);
+                }
+            }
+        }
+
+        self.label[b] = 0;
+        self.labelend[b] = NONE;
+        self.blossomchilds[b].clear();
+        self.blossomendps[b].clear();
+        self.blossombase[b] = NONE;
+        self.blossombestedges[b] = None;
+        self.bestedge[b] = NONE;
+        self.unusedblossoms.push(b);
+    }
+
+    /// Swap matched and unmatched edges along the path from node `v`
+    /// to the base of blossom `b`, making `v` the new base.
+    fn augment_blossom(&mut self, b: usize, v: usize) {
+        let mut t = v;
+        while self.blossomparent[t] != b {
+            t = self.blossomparent[t];
+        }
+        if t >= self.n {
+            self.augment_blossom(t, v);
+        }
+
+        let len = self.blossomchilds[b].len() as isize;
+        let i = self.blossomchilds[b].iter().position(|&c| c == t).unwrap();
+        let mut j = i as isize;
+        let (jstep, endptrick) = if j & 1 != 0 {
+            j -= len;
+            (1, 0)
+        } else {
+            (-1, 1)
+        };
+
+        while j != 0 {
+            j += jstep;
+            let t = at(&self.blossomchilds[b], j);
+            let p = at(&self.blossomendps[b], j - endptrick as isize) ^ endptrick;
+            if t >= self.n {
+                self.augment_blossom(t, self.endpoint[p]);
+            }
+            j += jstep;
+            let t = at(&self.blossomchilds[b], j);
+            if t >= self.n {
+                self.augment_blossom(t, self.endpoint[p ^ 1]);
+            }
+            self.mate[self.endpoint[p]] = p ^ 1;
+            self.mate[self.endpoint[p ^ 1]] = p;
+        }
+
+        // rotate the sub-blossoms so that the new base is first
+        self.blossomchilds[b].rotate_left(i);
+        self.blossomendps[b].rotate_left(i);
+        self.blossombase[b] = self.blossombase[self.blossomchilds[b][0]];
+        debug_assert_eq!(self.blossombase[b], v);
+    }
+
+    /// Augment the matching along the path through edge `k` between two
+    /// S-nodes in different trees.
+    fn augment_matching(&mut self, k: usize) {
+        let (v, w, _) = self.edges[k];
+        for &(s, p) in &[(v, 2 * k + 1), (w, 2 * k)] {
+            let (mut s, mut p) = (s, p);
+            loop {
+                let bs = self.inblossom[s];
+                if bs >= self.n {
+                    self.augment_blossom(bs, s);
+                }
+                self.mate[s] = p;
+                if self.labelend[bs] == NONE {
+                    // reached the root
+                    break;
+                }
+                let t = self.endpoint[self.labelend[bs]];
+                let bt = self.inblossom[t];
+                s = self.endpoint[self.labelend[bt]];
+                let j = self.endpoint[self.labelend[bt] ^ 1];
+                if bt >= self.n {
+                    self.augment_blossom(bt, j);
+                }
+                self.mate[j] = self.labelend[bt];
+                p = self.labelend[bt] ^ 1;
+            }
+        }
+    }
+
+    /// Run the algorithm.
+    ///
+    /// If `maxcardinality` is true, a maximum-weight matching among all
+    /// maximum cardinality matchings is computed.
+    fn solve(&mut self, maxcardinality: bool) {
+        let n = self.n;
+        let two = W::one() + W::one();
+
+        // each stage augments the matching by one edge
+        for _ in 0..n {
+            for x in self.label.iter_mut() {
+                *x = 0;
+            }
+            for x in self.bestedge.iter_mut() {
+                *x = NONE;
+            }
+            for x in self.blossombestedges[n..].iter_mut() {
+                *x = None;
+            }
+            for x in self.allowedge.iter_mut() {
+                *x = false;
+            }
+            self.queue.clear();
+
+            for v in 0..n {
+                if self.mate[v] == NONE && self.label[self.inblossom[v]] == 0 {
+                    self.assign_label(v, 1, NONE);
+                }
+            }
+
+            let mut augmented = false;
+            loop {
+                // grow the alternating forest along tight edges
+                while let Some(v) = self.queue.pop() {
+                    for i in 0..self.neighbend[v].len() {
+                        let p = self.neighbend[v][i];
+                        let k = p / 2;
+                        let w = self.endpoint[p];
+                        if self.inblossom[v] == self.inblossom[w] {
+                            continue;
+                        }
+                        let mut kslack = W::zero();
+                        if !self.allowedge[k] {
+                            kslack = self.slack(k);
+                            if kslack <= W::zero() {
+                                self.allowedge[k] = true;
+                            }
+                        }
+                        if self.allowedge[k] {
+                            if self.label[self.inblossom[w]] == 0 {
+                                self.assign_label(w, 2, p ^ 1);
+                            } else if self.label[self.inblossom[w]] == 1 {
+                                let base = self.scan_blossom(v, w);
+                                if base != NONE {
+                                    self.add_blossom(base, k);
+                                } else {
+                                    self.augment_matching(k);
+                                    augmented = true;
+                                    break;
+                                }
+                            } else if self.label[w] == 0 {
+                                // w is inside a T-blossom but not yet reached
+                                self.label[w] = 2;
+                                self.labelend[w] = p ^ 1;
+                            }
+                        } else if self.label[self.inblossom[w]] == 1 {
+                            let b = self.inblossom[v];
+                            if self.bestedge[b] == NONE || kslack < self.slack(self.bestedge[b]) {
+                                self.bestedge[b] = k;
+                            }
+                        } else if self.label[w] == 0
+                            && (self.bestedge[w] == NONE || kslack < self.slack(self.bestedge[w]))
+                        {
+                            self.bestedge[w] = k;
+                        }
+                    }
+                    if augmented {
+                        break;
+                    }
+                }
+                if augmented {
+                    break;
+                }
+
+                // compute the dual change
+                let mut deltatype = 0;
+                let mut delta = W::zero();
+                let mut deltaedge = NONE;
+                let mut deltablossom = NONE;
+
+                if !maxcardinality {
+                    // the dual of some S-node becomes zero
+                    deltatype = 1;
+                    delta = self.dualvar[..n].iter().cloned().min().unwrap_or_else(W::zero);
+                }
+                for v in 0..n {
+                    // an edge between an S-node and a free node becomes tight
+                    if self.label[self.inblossom[v]] == 0 && self.bestedge[v] != NONE {
+                        let d = self.slack(self.bestedge[v]);
+                        if deltatype == 0 || d < delta {
+                            delta = d;
+                            deltatype = 2;
+                            deltaedge = self.bestedge[v];
+                        }
+                    }
+                }
+                for b in 0..2 * n {
+                    // an edge between two S-blossoms becomes tight
+                    if self.blossomparent[b] == NONE && self.label[b] == 1 && self.bestedge[b] != NONE {
+                        let d = self.slack(self.bestedge[b]) / two;
+                        if deltatype == 0 || d < delta {
+                            delta = d;
+                            deltatype = 3;
+                            deltaedge = self.bestedge[b];
+                        }
+                    }
+                }
+                for b in n..2 * n {
+                    // the dual of some T-blossom becomes zero
+                    if self.blossombase[b] != NONE
+                        && self.blossomparent[b] == NONE
+                        && self.label[b] == 2
+                        && (deltatype == 0 || self.dualvar[b] < delta)
+                    {
+                        delta = self.dualvar[b];
+                        deltatype = 4;
+                        deltablossom = b;
+                    }
+                }
+                if deltatype == 0 {
+                    // no further improvement possible, do a final update
+                    // to make the solution dual feasible
+                    debug_assert!(maxcardinality);
+                    deltatype = 1;
+                    delta = self.dualvar[..n]
+                        .iter()
+                        .cloned()
+                        .min()
+                        .unwrap_or_else(W::zero)
+                        .max(W::zero());
+                }
+
+                // update the duals
+                for v in 0..n {
+                    match self.label[self.inblossom[v]] {
+                        1 => self.dualvar[v] -= delta,
+                        2 => self.dualvar[v] += delta,
+                        _ => {}
+                    }
+                }
+                for b in n..2 * n {
+                    if self.blossombase[b] != NONE && self.blossomparent[b] == NONE {
+                        match self.label[b] {
+                            1 => self.dualvar[b] += delta,
+                            2 => self.dualvar[b] -= delta,
+                            _ => {}
+                        }
+                    }
+                }
+
+                match deltatype {
+                    1 => break,
+                    2 | 3 => {
+                        self.allowedge[deltaedge] = true;
+                        let (mut i, j, _) = self.edges[deltaedge];
+                        if self.label[self.inblossom[i]] == 0 {
+                            i = j;
+                        }
+                        self.queue.push(i);
+                    }
+                    _ => self.expand_blossom(deltablossom, false),
+                }
+            }
+
+            if !augmented {
+                break;
+            }
+
+            // expand all S-blossoms with zero dual value
+            for b in n..2 * n {
+                if self.blossomparent[b] == NONE
+                    && self.blossombase[b] != NONE
+                    && self.label[b] == 1
+                    && self.dualvar[b] == W::zero()
+                {
+                    self.expand_blossom(b, true);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{max_weight_matching, min_cost_perfect_matching, MatchingDuals};
+    use crate::traits::*;
+    use crate::{Buildable, Builder, LinkedListGraph};
+
+    /// Compute the optimal weight by enumeration.
+    ///
+    /// If `perfect` is true, only perfect matchings are considered.
+    fn brute_force(n: usize, edges: &[(usize, usize, i64)], perfect: bool) -> Option<i64> {
+        // best[s] is the weight of an optimal matching on the node set s
+        let mut best = vec![None; 1 << n];
+        best[0] = Some(0);
+        for s in 1..1usize << n {
+            let u = s.trailing_zeros() as usize;
+            let rest = s & !(1 << u);
+            let mut b = if perfect { None } else { best[rest] };
+            for &(x, y, w) in edges {
+                let v = if x == u {
+                    y
+                } else if y == u {
+                    x
+                } else {
+                    continue;
+                };
+                if v != u && rest & (1 << v) != 0 {
+                    if let Some(r) = best[rest & !(1 << v)] {
+                        b = Some(b.map_or(r + w, |b: i64| b.max(r + w)));
+                    }
+                }
+            }
+            best[s] = b;
+        }
+        best[(1 << n) - 1]
+    }
+
+    /// Check the dual solution for the (negated if `sign == -1`) weights.
+    fn check_duals(
+        g: &LinkedListGraph,
+        weights: &[i64],
+        matching: &[<LinkedListGraph as GraphType>::Edge<'_>],
+        duals: &MatchingDuals<<LinkedListGraph as GraphType>::Node<'_>, i64>,
+        sign: i64,
+        perfect: bool,
+    ) {
+        let y = duals.nodes.iter().map(|&y| sign * y).collect::<Vec<_>>();
+        let mut matched = vec![false; g.num_nodes()];
+        for &e in matching {
+            let (u, v) = g.enodes(e);
+            matched[g.node_id(u)] = true;
+            matched[g.node_id(v)] = true;
+        }
+        if !perfect {
+            for u in 0..g.num_nodes() {
+                assert!(y[u] >= 0);
+                assert!(matched[u] || y[u] == 0);
+            }
+        }
+
+        let blossoms = duals
+            .blossoms
+            .iter()
+            .map(|(nodes, z)| (nodes.iter().map(|&u| g.node_id(u)).collect::<Vec<_>>(), *z))
+            .collect::<Vec<_>>();
+        let mut dual = y.iter().sum::<i64>();
+        for (nodes, z) in &blossoms {
+            assert!(*z > 0);
+            assert_eq!(nodes.len() % 2, 1);
+            dual += z * (nodes.len() as i64 - 1) / 2;
+        }
+
+        for e in g.edges() {
+            let (u, v) = g.enodes(e);
+            let (u, v) = (g.node_id(u), g.node_id(v));
+            if u == v {
+                continue;
+            }
+            let z = blossoms
+                .iter()
+                .filter(|(nodes, _)| nodes.contains(&u) && nodes.contains(&v))
+                .map(|(_, z)| z)
+                .sum::<i64>();
+            let w = sign * weights[g.edge_id(e)];
+            assert!(y[u] + y[v] + z >= 2 * w);
+            if matching.contains(&e) {
+                assert_eq!(y[u] + y[v] + z, 2 * w);
+            }
+        }
+
+        let value = matching.iter().map(|&e| sign * weights[g.edge_id(e)]).sum::<i64>();
+        assert_eq!(2 * value, dual);
+    }
+
+    #[test]
+    fn test_random() {
+        let mut next = crate::testutil::lcg(11);
+
+        let mut nperfect = 0;
+        for _ in 0..200 {
+            let n = 2 + next(11) as usize;
+            let p = 2 + next(10);
+            let mut edges = vec![];
+            for u in 0..n {
+                for v in u + 1..n {
+                    if next(20) < p {
+                        edges.push((u, v, next(30) as i64 - 5));
+                    }
+                }
+            }
+            let g = LinkedListGraph::<u32>::new_with(|b| {
+                let nodes = b.add_nodes(n);
+                for &(u, v, _) in &edges {
+                    b.add_edge(nodes[u], nodes[v]);
+                }
+            });
+            let weights = edges.iter().map(|&(_, _, w)| w).collect::<Vec<_>>();
+
+            let (value, matching, duals) = max_weight_matching(&g, |e| weights[g.edge_id(e)]);
+            assert_eq!(Some(value), brute_force(n, &edges, false));
+            check_duals(&g, &weights, &matching, &duals, 1, false);
+
+            let costs = edges.iter().map(|&(u, v, w)| (u, v, -w)).collect::<Vec<_>>();
+            let best = brute_force(n, &costs, true).map(|c| -c);
+            match min_cost_perfect_matching(&g, |e| weights[g.edge_id(e)]) {
+                Some((value, matching, duals)) => {
+                    nperfect += 1;
+                    assert_eq!(Some(value), best);
+                    assert_eq!(2 * matching.len(), n);
+                    check_duals(&g, &weights, &matching, &duals, -1, true);
+                }
+                None => assert_eq!(best, None),
+            }
+        }
+        assert!(nperfect > 20);
+    }
+}
@@ -0,0 +1,521 @@
+// Copyright (c) 2022 Frank Fischer <frank-fischer@shadow-soft.de>
+//
+// This program is free software: you can redistribute it and/or
+// modify it under the terms of the GNU General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see  <http://www.gnu.org/licenses/>
+//
+
+//! This module implements the max flow algorithm of Boykov and Kolmogorov.
+//!
+//! The algorithm grows two search trees, one from the source and one
+//! from the sink, and augments along the path found when both trees
+//! touch. In contrast to other augmenting path algorithms the search
+//! trees are not rebuilt from scratch after each augmentation but
+//! repaired. This is typically very fast on grid-like graphs as they
+//! arise in computer vision.
+//!
+//! See Y. Boykov, V. Kolmogorov: An experimental comparison of
+//! min-cut/max-flow algorithms for energy minimization in vision.
+//! IEEE Transactions on Pattern Analysis and Machine Intelligence 26
+//! (2004), 1124–1137.
+//!
+//! # Example
+//!
+//! ```
+//! use rs_graph::traits::*;
+//! use rs_graph::maxflow::boykovkolmogorov;
+//! use rs_graph::Net;
+//! use rs_graph::string::{Data, from_ascii};
+//!
+//! let Data { graph: g, weights: upper, nodes } = from_ascii::<Net>(r"
+//!      a---2-->b
+//!     @|\      ^\
+//!    / | \     | 4
+//!   5  |  \    |  \
+//!  /   |   |   |   @
+//! s    1   1   2    t
+//!  \   |   |   |   @
+//!   5  |    \  |  /
+//!    \ |     \ | 5
+//!     @v      @|/
+//!      c---2-->d
+//!     ").unwrap();
+//!
+//! let s = g.id2node(nodes[&'s']);
+//! let t = g.id2node(nodes[&'t']);
+//! let v1 = g.id2node(nodes[&'a']);
+//! let v2 = g.id2node(nodes[&'b']);
+//! let v3 = g.id2node(nodes[&'c']);
+//! let v4 = g.id2node(nodes[&'d']);
+//!
+//! let (value, flow, mut mincut) = boykovkolmogorov(&g, s, t, |e| upper[e.index()]);
+//!
+//! assert_eq!(value, 5);
+//! assert!(flow.iter().all(|&(e, f)| f >= 0 && f <= upper[e.index()]));
+//! assert!(g.nodes().filter(|&u| u != s && u != t).all(|u| {
+//!     g.outedges(u).map(|(e,_)| flow[g.edge_id(e)].1).sum::<usize>() ==
+//!     g.inedges(u).map(|(e,_)| flow[g.edge_id(e)].1).sum::<usize>()
+//! }));
+//!
+//! mincut.sort_by_key(|u| u.index());
+//! assert_eq!(mincut, vec![v1, s, v3]);
+//! ```
+//!
+//! ```
+//! use rs_graph::traits::*;
+//! use rs_graph::maxflow::boykovkolmogorov;
+//! use rs_graph::Net;
+//! use rs_graph::string::{Data, from_ascii};
+//!
+//! let Data { graph: g, weights: upper, nodes } = from_ascii::<Net>(r"
+//!                ---8-->a---10---
+//!               /       |        \
+//!              /        1  --3--  |
+//!             /         | /     \ |
+//!            /          v@       \v
+//!      ---->b-----9---->c----8--->d----
+//!     /      \         @         @^    \
+//!   18        ---6--  /         / |     33
+//!   /               \/         /  |      \
+//!  /                /\    -----   |       @
+//! s           --5--- |   /        |        t
+//!  \         /       |  /         |       @
+//!   27      |  ----2-|--         /       /
+//!    \      | /      |  /----8---       6
+//!     \     |/       @  |              /
+//!      ---->e----9-->f------6---->g----
+//!            \          |        @
+//!             \         |       /
+//!              --5----->h---4---
+//!     ").unwrap();
+//!
+//! let s = g.id2node(nodes[&'s']);
+//! let t = g.id2node(nodes[&'t']);
+//!
+//! assert_eq!(g.num_edges(), 18);
+//!
+//! let (value, flow, mut mincut) = boykovkolmogorov(&g, s, t, |e| upper[e.index()]);
+//! assert_eq!(value, 29);
+//!
+//! mincut.sort_by_key(|u| u.index());
+//! assert_eq!(mincut, "bcsef".chars().map(|v| g.id2node(nodes[&v])).collect::<Vec<_>>());
+//! ```
+
+use super::{solve_with, MaxFlow};
+use crate::traits::IndexDigraph;
+
+use std::collections::VecDeque;
+
+use crate::num::traits::NumAssign;
+
+/// The search tree a node belongs to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Tree {
+    /// The node is in no search tree.
+    Free,
+    /// The node is in the search tree of the source.
+    Source,
+    /// The node is in the search tree of the sink.
+    Sink,
+}
+
+#[derive(Clone)]
+struct NodeInfo {
+    /// The search tree containing this node.
+    tree: Tree,
+    /// The arc to the parent in the search tree and the parent node.
+    ///
+    /// The arc is directed away from the source in the source tree
+    /// and towards the sink in the sink tree.
+    parent: Option<(usize, usize)>,
+    /// Whether the node is in the queue of active nodes.
+    active: bool,
+    /// The time stamp at which `dist` has been computed.
+    time: usize,
+    /// The (approximate) distance to the root of the search tree.
+    dist: usize,
+}
+
+impl NodeInfo {
+    fn reset(&mut self) {
+        self.tree = Tree::Free;
+        self.parent = None;
+        self.active = false;
+        self.time = 0;
+        self.dist = 0;
+    }
+}
+
+/// The max-flow algorithm of Boykov and Kolmogorov.
+pub struct BoykovKolmogorov<'a, G, F>
+where
+    G: IndexDigraph,
+{
+    g: &'a G,
+    nodes: Vec<NodeInfo>,
+    neighs: Vec<Vec<(usize, usize)>>,
+    flow: Vec<F>,
+    active: VecDeque<usize>,
+    orphans: Vec<usize>,
+    time: usize,
+    value: F,
+}
+
+impl<'a, G, F> BoykovKolmogorov<'a, G, F>
+where
+    G: IndexDigraph,
+    F: NumAssign + Ord + Copy,
+{
+    /// Create a new Boykov-Kolmogorov algorithm instance for a graph.
+    pub fn new(g: &'a G) -> Self {
+        BoykovKolmogorov {
+            g,
+            nodes: vec![
+                NodeInfo {
+                    tree: Tree::Free,
+                    parent: None,
+                    active: false,
+                    time: 0,
+                    dist: 0,
+                };
+                g.num_nodes()
+            ],
+            neighs: g
+                .nodes()
+                .map(|u| {
+                    g.outedges(u)
+                        .map(|(e, v)| (g.edge_id(e) << 1, g.node_id(v)))
+                        .chain(g.inedges(u).map(|(e, v)| ((g.edge_id(e) << 1) | 1, g.node_id(v))))
+                        .collect()
+                })
+                .collect(),
+            flow: vec![F::zero(); g.num_edges() * 2],
+            active: VecDeque::with_capacity(g.num_nodes()),
+            orphans: Vec::with_capacity(g.num_nodes()),
+            time: 0,
+            value: F::zero(),
+        }
+    }
+
+    /// Return the underlying graph.
+    pub fn as_graph(&self) -> &'a G {
+        self.g
+    }
+
+    /// Return the value of the latest computed maximum flow.
+    pub fn value(&self) -> F {
+        self.value
+    }
+
+    /// Return the flow value on edge `e`
+    pub fn flow(&self, e: G::Edge<'_>) -> F {
+        self.flow[self.g.edge_id(e) << 1]
+    }
+
+    /// Return an iterator over all (edge, flow) pairs.
+    pub fn flow_iter<'b>(&'b self) -> impl Iterator<Item = (G::Edge<'a>, F)> + 'b {
+        self.g.edges().enumerate().map(move |(i, e)| (e, self.flow[i << 1]))
+    }
+
+    /// Solve the maxflow problem.
+    ///
+    /// The method solved the max flow problem from the source node
+    /// `src` to the sink node `snk` with the given `upper` bounds on
+    /// the edges.
+    pub fn solve<Us>(&mut self, src: G::Node<'_>, snk: G::Node<'_>, upper: Us)
+    where
+        Us: Fn(G::Edge<'a>) -> F,
+    {
+        let src = self.g.node_id(src);
+        let snk = self.g.node_id(snk);
+        assert_ne!(src, snk, "Source and sink node must not be equal");
+
+        // initialize network flow of reverse edges
+        for (e, flw) in self.flow.iter_mut().enumerate() {
+            *flw = if (e & 1) == 0 {
+                F::zero()
+            } else {
+                upper(self.g.id2edge(e >> 1))
+            };
+        }
+
+        for node in &mut self.nodes {
+            node.reset();
+        }
+        self.nodes[src].tree = Tree::Source;
+        self.nodes[snk].tree = Tree::Sink;
+        self.active.clear();
+        self.orphans.clear();
+        self.activate(src);
+        self.activate(snk);
+        self.time = 0;
+
+        self.value = F::zero();
+        while let Some((arc, u, v)) = self.grow() {
+            self.time += 1;
+            self.nodes[src].time = self.time;
+            self.nodes[snk].time = self.time;
+            let df = self.augment(arc, u, v);
+            self.value += df;
+            self.adopt();
+        }
+    }
+
+    /// Return the minimal cut associated with the last maximum flow.
+    pub fn mincut(&self) -> Vec<G::Node<'a>> {
+        self.g
+            .nodes()
+            .filter(|&u| self.nodes[self.g.node_id(u)].tree == Tree::Source)
+            .collect()
+    }
+
+    /// Add the node `u` to the queue of active nodes.
+    fn activate(&mut self, u: usize) {
+        if !self.nodes[u].active {
+            self.nodes[u].active = true;
+            self.active.push_back(u);
+        }
+    }
+
+    /// Grow the search trees until they touch.
+    ///
+    /// Returns the arc connecting both trees, directed from the source
+    /// tree to the sink tree, and its end nodes in the source and sink
+    /// tree, or `None` if the trees cannot grow anymore.
+    fn grow(&mut self) -> Option<(usize, usize, usize)> {
+        while let Some(&u) = self.active.front() {
+            let tree = self.nodes[u].tree;
+            if tree != Tree::Free {
+                for &(e, v) in &self.neighs[u] {
+                    // the arc in the direction of the flow
+                    let a = if tree == Tree::Source { e } else { e ^ 1 };
+                    if self.flow[a ^ 1].is_zero() {
+                        continue;
+                    }
+                    let vtree = self.nodes[v].tree;
+                    if vtree == Tree::Free {
+                        let (time, dist) = (self.nodes[u].time, self.nodes[u].dist);
+                        let node = &mut self.nodes[v];
+                        node.tree = tree;
+                        node.parent = Some((a, u));
+                        node.time = time;
+                        node.dist = dist + 1;
+                        if !node.active {
+                            node.active = true;
+                            self.active.push_back(v);
+                        }
+                    } else if vtree != tree {
+                        // u stays active, it may have further neighbors
+                        return Some(if tree == Tree::Source { (a, u, v) } else { (a, v, u) });
+                    }
+                }
+            }
+            self.active.pop_front();
+            self.nodes[u].active = false;
+        }
+        None
+    }
+
+    /// Augment along the path through the arc `arc` from `u` to `v`.
+    ///
+    /// Returns the amount of flow sent along the path. Nodes whose
+    /// parent arc has been saturated become orphans.
+    fn augment(&mut self, arc: usize, u: usize, v: usize) -> F {
+        // compute the bottleneck capacity
+        let mut df = self.flow[arc ^ 1];
+        for &start in &[u, v] {
+            let mut x = start;
+            while let Some((a, p)) = self.nodes[x].parent {
+                if self.flow[a ^ 1] < df {
+                    df = self.flow[a ^ 1];
+                }
+                x = p;
+            }
+        }
+
+        // send the flow
+        self.flow[arc] += df;
+        self.flow[arc ^ 1] -= df;
+        for &start in &[u, v] {
+            let mut x = start;
+            while let Some((a, p)) = self.nodes[x].parent {
+                self.flow[a] += df;
+                self.flow[a ^ 1] -= df;
+                if self.flow[a ^ 1].is_zero() {
+                    self.nodes[x].parent = None;
+                    self.orphans.push(x);
+                }
+                x = p;
+            }
+        }
+
+        df
+    }
+
+    /// Find new parents for all orphans.
+    ///
+    /// Orphans without a valid parent are removed from their search
+    /// tree, their children become orphans, too.
+    fn adopt(&mut self) {
+        while let Some(x) = self.orphans.pop() {
+            let tree = self.nodes[x].tree;
+
+            // search a new parent with minimal distance to the root
+            let mut best = None;
+            for i in 0..self.neighs[x].len() {
+                let (e, y) = self.neighs[x][i];
+                // the arc between y and x in the direction of the flow
+                let a = if tree == Tree::Source { e ^ 1 } else { e };
+                if self.nodes[y].tree != tree || self.flow[a ^ 1].is_zero() {
+                    continue;
+                }
+                if let Some(d) = self.root_dist(y) {
+                    if best.map(|(_, _, bestd)| d < bestd).unwrap_or(true) {
+                        best = Some((a, y, d));
+                    }
+                }
+            }
+
+            if let Some((a, y, d)) = best {
+                let node = &mut self.nodes[x];
+                node.parent = Some((a, y));
+                node.time = self.time;
+                node.dist = d + 1;
+                continue;
+            }
+
+            // no parent found, remove x from the tree
+            for i in 0..self.neighs[x].len() {
+                let (e, y) = self.neighs[x][i];
+                if self.nodes[y].tree != tree {
+                    continue;
+                }
+                let a = if tree == Tree::Source { e ^ 1 } else { e };
+                if !self.flow[a ^ 1].is_zero() {
+                    // y might become a parent of x later
+                    self.activate(y);
+                }
+                if self.nodes[y].parent.map(|(_, p)| p == x).unwrap_or(false) {
+                    self.nodes[y].parent = None;
+                    self.orphans.push(y);
+                }
+            }
+            self.nodes[x].tree = Tree::Free;
+        }
+    }
+
+    /// Return the distance of `y` to the root of its search tree.
+    ///
+    /// Returns `None` if the path to the root is broken, i.e. `y` is
+    /// (a descendent of) an orphan. All nodes on a valid path get the
+    /// current time stamp. The roots always have the current time
+    /// stamp.
+    fn root_dist(&mut self, y: usize) -> Option<usize> {
+        let mut d = 0;
+        let mut x = y;
+        loop {
+            if self.nodes[x].time == self.time {
+                d += self.nodes[x].dist;
+                break;
+            }
+            let (_, p) = self.nodes[x].parent?;
+            x = p;
+            d += 1;
+        }
+
+        // update the distances on the path
+        let mut x = y;
+        let mut dx = d;
+        while self.nodes[x].time != self.time {
+            let node = &mut self.nodes[x];
+            node.time = self.time;
+            node.dist = dx;
+            dx -= 1;
+            x = node.parent.unwrap().1;
+        }
+
+        Some(d)
+    }
+}
+
+impl<'a, G, F> MaxFlow<'a> for BoykovKolmogorov<'a, G, F>
+where
+    G: IndexDigraph,
+    F: 'a + NumAssign + Ord + Copy,
+{
+    type Graph = G;
+
+    type Flow = F;
+
+    type Solver<'b, H>
+        = BoykovKolmogorov<'b, H, F>
+    where
+        H: 'b + IndexDigraph,
+        F: 'b;
+
+    fn new(g: &'a G) -> Self {
+        BoykovKolmogorov::new(g)
+    }
+
+    fn as_graph(&self) -> &'a G {
+        BoykovKolmogorov::as_graph(self)
+    }
+
+    fn value(&self) -> F {
+        BoykovKolmogorov::value(self)
+    }
+
+    fn flow(&self, e: G::Edge<'_>) -> F {
+        BoykovKolmogorov::flow(self, e)
+    }
+
+    fn flow_iter<'b>(&'b self) -> impl Iterator<Item = (G::Edge<'a>, F)> + 'b
+    where
+        'a: 'b,
+    {
+        BoykovKolmogorov::flow_iter(self)
+    }
+
+    fn solve<Us>(&mut self, src: G::Node<'_>, snk: G::Node<'_>, upper: Us)
+    where
+        Us: Fn(G::Edge<'a>) -> F,
+    {
+        BoykovKolmogorov::solve(self, src, snk, upper)
+    }
+
+    fn mincut(&self) -> Vec<G::Node<'a>> {
+        BoykovKolmogorov::mincut(self)
+    }
+}
+
+/// Solve the maxflow problem using the algorithm of Boykov and Kolmogorov.
+///
+/// The function solves the max flow problem from the source nodes
+/// `src` to the sink node `snk` with the given `upper` bounds on
+/// the edges.
+///
+/// The function returns the flow value, the flow on each edge and the
+/// nodes in a minimal cut.
+#[allow(clippy::type_complexity)]
+pub fn boykovkolmogorov<'a, G, F, Us>(
+    g: &'a G,
+    src: G::Node<'_>,
+    snk: G::Node<'_>,
+    upper: Us,
+) -> (F, Vec<(G::Edge<'a>, F)>, Vec<G::Node<'a>>)
+where
+    G: IndexDigraph,
+    F: 'a + NumAssign + Ord + Copy,
+    Us: Fn(G::Edge<'_>) -> F,
+{
+    solve_with::<BoykovKolmogorov<_, _>>(g, src, snk, upper)
+}
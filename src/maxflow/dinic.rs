@@ -98,6 +98,7 @@
 //! assert_eq!(mincut, "bcsef".chars().map(|v| g.id2node(nodes[&v])).collect::<Vec<_>>());
 //! ```
 
+use super::{repair_flow, solve_with, MaxFlow};
 use crate::traits::IndexDigraph;
 
 use std::cmp::min;
@@ -113,7 +114,8 @@ where
     g: &'a G,
     nodes: Vec<NodeInfo>,
     neighs: Vec<Vec<(usize, usize)>>,
-    edges: Vec<EdgeInfo<F>>,
+    flow: Vec<F>,
+    next_lvl: Vec<(usize, usize)>,
     queue: VecDeque<usize>,
     value: F,
 }
@@ -124,12 +126,6 @@ struct NodeInfo {
     first_lvl: (usize, usize),
 }
 
-#[derive(Clone)]
-struct EdgeInfo<F> {
-    flow: F,
-    next_lvl: (usize, usize),
-}
-
 impl<'a, G, F> Dinic<'a, G, F>
 where
     G: IndexDigraph,
@@ -155,13 +151,8 @@ where
                         .collect()
                 })
                 .collect(),
-            edges: vec![
-                EdgeInfo {
-                    flow: F::zero(),
-                    next_lvl: (usize::max_value(), usize::max_value()),
-                };
-                g.num_edges() * 2
-            ],
+            flow: vec![F::zero(); g.num_edges() * 2],
+            next_lvl: vec![(usize::max_value(), usize::max_value()); g.num_edges() * 2],
             queue: VecDeque::with_capacity(g.num_nodes()),
             value: F::zero(),
         }
@@ -179,15 +170,12 @@ where
 
     /// Return the flow value on edge `e`
     pub fn flow(&self, e: G::Edge<'_>) -> F {
-        self.edges[self.g.edge_id(e) << 1].flow
+        self.flow[self.g.edge_id(e) << 1]
     }
 
     /// Return an iterator over all (edge, flow) pairs.
     pub fn flow_iter<'b>(&'b self) -> impl Iterator<Item = (G::Edge<'a>, F)> + 'b {
-        self.g
-            .edges()
-            .enumerate()
-            .map(move |(i, e)| (e, self.edges[i << 1].flow))
+        self.g.edges().enumerate().map(move |(i, e)| (e, self.flow[i << 1]))
     }
 
     /// Solve the maxflow problem.
@@ -204,8 +192,8 @@ where
         assert_ne!(src, snk, "Source and sink node must not be equal");
 
         // initialize network flow of reverse edges
-        for (e, einfo) in self.edges.iter_mut().enumerate() {
-            einfo.flow = if (e & 1) == 0 {
+        for (e, flw) in self.flow.iter_mut().enumerate() {
+            *flw = if (e & 1) == 0 {
                 F::zero()
             } else {
                 upper(self.g.id2edge(e >> 1))
@@ -219,6 +207,43 @@ where
         }
     }
 
+    /// Solve the maxflow problem starting from the current flow.
+    ///
+    /// This is like [`Dinic::solve`], but the flow of the latest
+    /// computation is used as starting point for the (possibly
+    /// changed) `upper` bounds. Flows exceeding their new upper bound
+    /// are reduced and rerouted, then the flow is augmented as usual.
+    /// This is usually much faster than a new computation if only a
+    /// few bounds have changed.
+    ///
+    /// The flow value and the minimal cut are the same as for
+    /// [`Dinic::solve`], the flow on the edges may differ.
+    pub fn resolve<Us>(&mut self, src: G::Node<'_>, snk: G::Node<'_>, upper: Us)
+    where
+        Us: Fn(G::Edge<'a>) -> F,
+    {
+        let src = self.g.node_id(src);
+        let snk = self.g.node_id(snk);
+        assert_ne!(src, snk, "Source and sink node must not be equal");
+
+        let g = self.g;
+        repair_flow(&self.neighs, &mut self.flow, src, snk, |i| upper(g.id2edge(i)));
+
+        self.value = F::zero();
+        for &(e, _) in &self.neighs[snk] {
+            if (e & 1) == 0 {
+                self.value -= self.flow[e];
+            } else {
+                self.value += self.flow[e ^ 1];
+            }
+        }
+
+        while self.search(src, snk) {
+            let v = self.augment(src, snk, None);
+            self.value += v;
+        }
+    }
+
     /// Return the minimal cut associated with the last maximum flow.
     pub fn mincut(&self) -> Vec<G::Node<'a>> {
         let n = self.g.num_nodes();
@@ -249,7 +274,7 @@ where
             }
 
             for &(e, v) in &self.neighs[u] {
-                if self.edges[e ^ 1].flow > F::zero() {
+                if self.flow[e ^ 1] > F::zero() {
                     if self.nodes[v].dist == n {
                         self.nodes[v].dist = d + 1;
                         self.queue.push_back(v);
@@ -259,7 +284,7 @@ where
                     } else if self.nodes[v].dist != d + 1 {
                         continue;
                     }
-                    self.edges[e].next_lvl = self.nodes[u].first_lvl;
+                    self.next_lvl[e] = self.nodes[u].first_lvl;
                     self.nodes[u].first_lvl = (e, v);
                 }
             }
@@ -282,13 +307,13 @@ where
             }
             let f = e ^ 1;
             let rem_cap = match target_flow {
-                Some(target_flow) => min(self.edges[f].flow, target_flow - df),
-                None => self.edges[f].flow,
+                Some(target_flow) => min(self.flow[f], target_flow - df),
+                None => self.flow[f],
             };
             if rem_cap > F::zero() {
                 let cf = self.augment(v, snk, Some(rem_cap));
-                self.edges[e].flow += cf;
-                self.edges[f].flow -= cf;
+                self.flow[e] += cf;
+                self.flow[f] -= cf;
                 df += cf;
                 if target_flow.map(|t| df == t).unwrap_or(false) {
                     break;
@@ -296,7 +321,7 @@ where
             }
 
             // edge is saturated or blocked
-            self.nodes[src].first_lvl = self.edges[e].next_lvl;
+            self.nodes[src].first_lvl = self.next_lvl[e];
         }
 
         if df.is_zero() {
@@ -310,6 +335,56 @@ where
     }
 }
 
+impl<'a, G, F> MaxFlow<'a> for Dinic<'a, G, F>
+where
+    G: IndexDigraph,
+    F: 'a + NumAssign + Ord + Copy,
+{
+    type Graph = G;
+
+    type Flow = F;
+
+    type Solver<'b, H>
+        = Dinic<'b, H, F>
+    where
+        H: 'b + IndexDigraph,
+        F: 'b;
+
+    fn new(g: &'a G) -> Self {
+        Dinic::new(g)
+    }
+
+    fn as_graph(&self) -> &'a G {
+        Dinic::as_graph(self)
+    }
+
+    fn value(&self) -> F {
+        Dinic::value(self)
+    }
+
+    fn flow(&self, e: G::Edge<'_>) -> F {
+        Dinic::flow(self, e)
+    }
+
+    fn flow_iter<'b>(&'b self) -> impl Iterator<Item = (G::Edge<'a>, F)> + 'b
+    where
+        'a: 'b,
+    {
+        Dinic::flow_iter(self)
+    }
+
+    fn solve<Us>(&mut self, src: G::Node<'_>, snk: G::Node<'_>, upper: Us)
+    where
+        Us: Fn(G::Edge<'a>) -> F,
+    {
+        Dinic::solve(self, src, snk, upper)
+    }
+
+    fn mincut(&self) -> Vec<G::Node<'a>> {
+        Dinic::mincut(self)
+    }
+}
+
 /// Solve the maxflow problem using the algorithm of Dinic.
 ///
 /// The function solves the max flow problem from the source nodes
@@ -329,7 +404,5 @@ where
     F: 'a + NumAssign + Ord + Copy,
     Us: Fn(G::Edge<'_>) -> F,
 {
-    let mut maxflow = Dinic::new(g);
-    maxflow.solve(src, snk, upper);
-    (maxflow.value(), maxflow.flow_iter().collect(), maxflow.mincut())
+    solve_with::<Dinic<_, _>>(g, src, snk, upper)
 }
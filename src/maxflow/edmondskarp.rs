@@ -99,6 +99,7 @@
 //! assert_eq!(mincut, "bcsef".chars().map(|v| g.id2node(nodes[&v])).collect::<Vec<_>>());
 //! ```
 
+use super::{solve_with, MaxFlow};
 use crate::traits::IndexDigraph;
 
 use std::cmp::min;
@@ -245,6 +246,56 @@ where
     }
 }
 
+impl<'a, G, F> MaxFlow<'a> for EdmondsKarp<'a, G, F>
+where
+    G: IndexDigraph,
+    F: 'a + NumAssign + Ord + Copy,
+{
+    type Graph = G;
+
+    type Flow = F;
+
+    type Solver<'b, H>
+        = EdmondsKarp<'b, H, F>
+    where
+        H: 'b + IndexDigraph,
+        F: 'b;
+
+    fn new(g: &'a G) -> Self {
+        EdmondsKarp::new(g)
+    }
+
+    fn as_graph(&self) -> &'a G {
+        EdmondsKarp::as_graph(self)
+    }
+
+    fn value(&self) -> F {
+        EdmondsKarp::value(self)
+    }
+
+    fn flow(&self, e: G::Edge<'_>) -> F {
+        EdmondsKarp::flow(self, e)
+    }
+
+    fn flow_iter<'b>(&'b self) -> impl Iterator<Item = (G::Edge<'a>, F)> + 'b
+    where
+        'a: 'b,
+    {
+        EdmondsKarp::flow_iter(self)
+    }
+
+    fn solve<Us>(&mut self, src: G::Node<'_>, snk: G::Node<'_>, upper: Us)
+    where
+        Us: Fn(G::Edge<'a>) -> F,
+    {
+        EdmondsKarp::solve(self, src, snk, upper)
+    }
+
+    fn mincut(&self) -> Vec<G::Node<'a>> {
+        EdmondsKarp::mincut(self)
+    }
+}
+
 /// Solve the maxflow problem using the algorithm of Edmonds-Karp.
 ///
 /// The function solves the max flow problem from the source nodes
@@ -264,7 +315,5 @@ where
     F: 'a + NumAssign + Ord + Copy,
     Us: Fn(G::Edge<'_>) -> F,
 {
-    let mut maxflow = EdmondsKarp::new(g);
-    maxflow.solve(src, snk, upper);
-    (maxflow.value(), maxflow.flow_iter().collect(), maxflow.mincut())
+    solve_with::<EdmondsKarp<_, _>>(g, src, snk, upper)
 }
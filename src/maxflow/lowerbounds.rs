@@ -0,0 +1,423 @@
+// Copyright (c) 2022 Frank Fischer <frank-fischer@shadow-soft.de>
+//
+// This program is free software: you can redistribute it and/or
+// modify it under the terms of the GNU General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see  <http://www.gnu.org/licenses/>
+//
+
+//! Maximum flows and circulations with lower bounds on the edges.
+//!
+//! A feasible flow is computed by the standard demand transformation:
+//! the lower bounds are subtracted from the flow, which yields a
+//! demand at each node. These demands are satisfied by a maximum flow
+//! from an additional super source to an additional super sink. If
+//! this is not possible, the minimal cut of this auxiliary problem
+//! provides a certificate for the infeasibility of the problem.
+//!
+//! Starting from a feasible flow, the maximum flow is computed by a
+//! second maximum flow computation in the residual network.
+//!
+//! # Example
+//!
+//! ```
+//! use rs_graph::{Buildable, Builder, Net};
+//! use rs_graph::maxflow::lowerbounds::{solve_with_lower_bounds, Infeasibility};
+//! use rs_graph::maxflow::Dinic;
+//! use rs_graph::traits::*;
+//!
+//! let g = Net::new_with(|b| {
+//!     let nodes = b.add_nodes(4);
+//!     b.add_edge(nodes[0], nodes[1]);
+//!     b.add_edge(nodes[0], nodes[2]);
+//!     b.add_edge(nodes[1], nodes[2]);
+//!     b.add_edge(nodes[1], nodes[3]);
+//!     b.add_edge(nodes[2], nodes[3]);
+//! });
+//! let s = g.id2node(0);
+//! let t = g.id2node(3);
+//! let upper = [4, 2, 3, 1, 5];
+//!
+//! // the edge from 1 to 2 must carry at least 3 units of flow
+//! let lower = [0, 0, 3, 0, 0];
+//! let (value, flow, _) =
+//!     solve_with_lower_bounds::<Dinic<_, _>>(&g, s, t, |e| lower[g.edge_id(e)], |e| upper[g.edge_id(e)]).unwrap();
+//! assert_eq!(value, 6);
+//! assert!(flow.iter().all(|&(e, f)| lower[g.edge_id(e)] <= f && f <= upper[g.edge_id(e)]));
+//!
+//! // the edge from 2 to 3 cannot carry 6 units of flow because at
+//! // most 5 units can enter node 2
+//! let upper = [4, 2, 3, 1, 7];
+//! let lower = [0, 0, 0, 0, 6];
+//! match solve_with_lower_bounds::<Dinic<_, _>>(&g, s, t, |e| lower[g.edge_id(e)], |e| upper[g.edge_id(e)]) {
+//!     Err(Infeasibility::Cut(mut cut)) => {
+//!         cut.sort_by_key(|&u| g.node_id(u));
+//!         assert_eq!(cut, vec![g.id2node(0), g.id2node(1), g.id2node(3)]);
+//!     }
+//!     _ => unreachable!(),
+//! }
+//! ```
+
+use super::MaxFlow;
+use crate::builder::{Buildable, Builder};
+use crate::traits::{FiniteDigraph, FiniteGraph, GraphType, IndexDigraph, IndexGraph};
+use crate::Net;
+
+use crate::num::traits::{NumAssign, Zero};
+
+pub use crate::mcf::Infeasibility;
+
+/// Compute a maximum flow respecting lower and upper bounds.
+///
+/// The function solves the max flow problem from the source node `src`
+/// to the sink node `snk` with the given `lower` and `upper` bounds
+/// on the edges. The flow is computed by two max flow computations
+/// with the algorithm `M`.
+///
+/// The function returns the flow value, the flow on each edge and the
+/// nodes in a minimal cut, or a certificate of infeasibility if no
+/// flow satisfying the bounds exists. The node set of an
+/// [`Infeasibility::Cut`] never contains the sink without the source.
+#[allow(clippy::type_complexity)]
+pub fn solve_with_lower_bounds<'a, M>(
+    g: &'a M::Graph,
+    src: <M::Graph as GraphType>::Node<'_>,
+    snk: <M::Graph as GraphType>::Node<'_>,
+    lower: impl Fn(<M::Graph as GraphType>::Edge<'a>) -> M::Flow,
+    upper: impl Fn(<M::Graph as GraphType>::Edge<'a>) -> M::Flow,
+) -> Result<
+    (
+        M::Flow,
+        Vec<(<M::Graph as GraphType>::Edge<'a>, M::Flow)>,
+        Vec<<M::Graph as GraphType>::Node<'a>>,
+    ),
+    Infeasibility<<M::Graph as GraphType>::Node<'a>, <M::Graph as GraphType>::Edge<'a>>,
+>
+where
+    M: MaxFlow<'a>,
+{
+    let src = g.node_id(src);
+    let snk = g.node_id(snk);
+    assert_ne!(src, snk, "Source and sink node must not be equal");
+
+    let lower: Vec<M::Flow> = g.edges().map(&lower).collect();
+    let upper: Vec<M::Flow> = g.edges().map(&upper).collect();
+    let mut flow = feasible_flow::<M, _, _>(g, Some((src, snk)), &lower, &upper)?;
+
+    // augment the flow in the residual network
+    let residual = Net::new_with(|b| {
+        let nodes = b.add_nodes(g.num_nodes());
+        for e in g.edges() {
+            let u = nodes[g.node_id(g.src(e))];
+            let v = nodes[g.node_id(g.snk(e))];
+            b.add_edge(u, v);
+            b.add_edge(v, u);
+        }
+    });
+    let mut maxflow = M::Solver::<'_, Net>::new(&residual);
+    maxflow.solve(residual.id2node(src), residual.id2node(snk), |e| {
+        let eid = residual.edge_id(e);
+        if eid & 1 == 0 {
+            upper[eid >> 1] - flow[eid >> 1]
+        } else {
+            flow[eid >> 1] - lower[eid >> 1]
+        }
+    });
+    for (e, f) in maxflow.flow_iter() {
+        let eid = residual.edge_id(e);
+        if eid & 1 == 0 {
+            flow[eid >> 1] += f;
+        } else {
+            flow[eid >> 1] -= f;
+        }
+    }
+
+    let mut outflow = M::Flow::zero();
+    let mut inflow = M::Flow::zero();
+    for e in g.edges() {
+        if g.node_id(g.src(e)) == src {
+            outflow += flow[g.edge_id(e)];
+        }
+        if g.node_id(g.snk(e)) == src {
+            inflow += flow[g.edge_id(e)];
+        }
+    }
+    let value = outflow - inflow;
+
+    let mincut = maxflow
+        .mincut()
+        .into_iter()
+        .map(|u| g.id2node(residual.node_id(u)))
+        .collect();
+
+    Ok((value, g.edges().zip(flow).collect(), mincut))
+}
+
+/// Compute a feasible circulation respecting lower and upper bounds.
+///
+/// The circulation is computed by a max flow computation with the
+/// algorithm `M`.
+///
+/// The function returns the flow on each edge, or a certificate of
+/// infeasibility if no circulation satisfying the bounds exists.
+#[allow(clippy::type_complexity)]
+pub fn feasible_circulation<'a, M>(
+    g: &'a M::Graph,
+    lower: impl Fn(<M::Graph as GraphType>::Edge<'a>) -> M::Flow,
+    upper: impl Fn(<M::Graph as GraphType>::Edge<'a>) -> M::Flow,
+) -> Result<
+    Vec<(<M::Graph as GraphType>::Edge<'a>, M::Flow)>,
+    Infeasibility<<M::Graph as GraphType>::Node<'a>, <M::Graph as GraphType>::Edge<'a>>,
+>
+where
+    M: MaxFlow<'a>,
+{
+    let lower: Vec<M::Flow> = g.edges().map(&lower).collect();
+    let upper: Vec<M::Flow> = g.edges().map(&upper).collect();
+    let flow = feasible_flow::<M, _, _>(g, None, &lower, &upper)?;
+    Ok(g.edges().zip(flow).collect())
+}
+
+/// Compute a feasible flow by the demand transformation.
+///
+/// If `st` is `Some((src, snk))` the flow must only satisfy flow
+/// conservation at nodes other than `src` and `snk` (and the net
+/// outflow of the source is non-negative), otherwise a circulation is
+/// computed.
+fn feasible_flow<'a, M, G, F>(
+    g: &'a G,
+    st: Option<(usize, usize)>,
+    lower: &[F],
+    upper: &[F],
+) -> Result<Vec<F>, Infeasibility<G::Node<'a>, G::Edge<'a>>>
+where
+    M: MaxFlow<'a, Graph = G, Flow = F>,
+    G: IndexDigraph,
+    F: NumAssign + Ord + Copy,
+{
+    let n = g.num_nodes();
+
+    // compute the demands from the lower bounds leaving and entering each node
+    let mut outlower = vec![F::zero(); n];
+    let mut inlower = vec![F::zero(); n];
+    for e in g.edges() {
+        let eid = g.edge_id(e);
+        if lower[eid] > upper[eid] {
+            return Err(Infeasibility::Bounds(e));
+        }
+        outlower[g.node_id(g.src(e))] += lower[eid];
+        inlower[g.node_id(g.snk(e))] += lower[eid];
+    }
+    let total = (0..n)
+        .filter(|&u| inlower[u] > outlower[u])
+        .fold(F::zero(), |total, u| total + (inlower[u] - outlower[u]));
+
+    // The auxiliary network contains the original edges (with reduced
+    // capacities), edges from the super source to nodes with positive
+    // demand, edges to the super sink from nodes with negative demand
+    // and, for source-sink flows, an edge from the sink to the source.
+    // The capacity of the latter exceeds the total demand, hence it
+    // is never contained in a minimal cut of value smaller than the
+    // total demand.
+    let mut caps = Vec::with_capacity(g.num_edges() + n + 1);
+    let aux = Net::new_with(|b| {
+        let nodes = b.add_nodes(n + 2);
+        for e in g.edges() {
+            b.add_edge(nodes[g.node_id(g.src(e))], nodes[g.node_id(g.snk(e))]);
+            caps.push(upper[g.edge_id(e)] - lower[g.edge_id(e)]);
+        }
+        for u in 0..n {
+            if inlower[u] > outlower[u] {
+                b.add_edge(nodes[n], nodes[u]);
+                caps.push(inlower[u] - outlower[u]);
+            } else if inlower[u] < outlower[u] {
+                b.add_edge(nodes[u], nodes[n + 1]);
+                caps.push(outlower[u] - inlower[u]);
+            }
+        }
+        if let Some((src, snk)) = st {
+            b.add_edge(nodes[snk], nodes[src]);
+            caps.push(total + F::one());
+        }
+    });
+
+    let mut maxflow = M::Solver::<'_, Net>::new(&aux);
+    maxflow.solve(aux.id2node(n), aux.id2node(n + 1), |e| caps[aux.edge_id(e)]);
+
+    if maxflow.value() < total {
+        let cut = maxflow
+            .mincut()
+            .into_iter()
+            .map(|u| aux.node_id(u))
+            .filter(|&u| u < n)
+            .map(|u| g.id2node(u))
+            .collect();
+        return Err(Infeasibility::Cut(cut));
+    }
+
+    Ok(g.edges()
+        .map(|e| lower[g.edge_id(e)] + maxflow.flow(aux.id2edge(g.edge_id(e))))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{feasible_circulation, solve_with_lower_bounds, Infeasibility};
+    use crate::maxflow::{BoykovKolmogorov, Dinic, EdmondsKarp, MaxFlow, PushRelabel};
+    use crate::traits::*;
+    use crate::{Buildable, Builder, Net};
+
+    fn network(n: usize, edges: &[(usize, usize, i32, i32)]) -> (Net, Vec<i32>, Vec<i32>) {
+        let g = Net::new_with(|b| {
+            let nodes = b.add_nodes(n);
+            for &(u, v, _, _) in edges {
+                b.add_edge(nodes[u], nodes[v]);
+            }
+        });
+        let lower = edges.iter().map(|e| e.2).collect();
+        let upper = edges.iter().map(|e| e.3).collect();
+        (g, lower, upper)
+    }
+
+    /// Check that `cut` is a valid certificate of infeasibility.
+    fn check_cut(g: &Net, cut: &[<Net as GraphType>::Node<'_>], lower: &[i32], upper: &[i32]) {
+        let mut incut = vec![false; g.num_nodes()];
+        for &u in cut {
+            incut[g.node_id(u)] = true;
+        }
+        let mut inflow = 0;
+        let mut outcap = 0;
+        for e in g.edges() {
+            match (incut[g.node_id(g.src(e))], incut[g.node_id(g.snk(e))]) {
+                (false, true) => inflow += lower[g.edge_id(e)],
+                (true, false) => outcap += upper[g.edge_id(e)],
+                _ => (),
+            }
+        }
+        assert!(inflow > outcap);
+    }
+
+    fn check_maxflow<'a, M>(g: &'a Net, lower: &[i32], upper: &[i32])
+    where
+        M: MaxFlow<'a, Graph = Net, Flow = i32>,
+    {
+        let (s, t) = (g.id2node(0), g.id2node(4));
+        let (value, flow, mincut) =
+            solve_with_lower_bounds::<M>(g, s, t, |e| lower[g.edge_id(e)], |e| upper[g.edge_id(e)])
+                .expect("Problem should be feasible");
+        assert_eq!(value, 9);
+        for &(e, f) in &flow {
+            assert!(lower[g.edge_id(e)] <= f && f <= upper[g.edge_id(e)]);
+        }
+        for u in g.nodes().filter(|&u| u != s && u != t) {
+            let inflow: i32 = g.inedges(u).map(|(e, _)| flow[g.edge_id(e)].1).sum();
+            let outflow: i32 = g.outedges(u).map(|(e, _)| flow[g.edge_id(e)].1).sum();
+            assert_eq!(inflow, outflow);
+        }
+        assert!(mincut.contains(&s) && !mincut.contains(&t));
+    }
+
+    #[test]
+    fn test_maxflow() {
+        let (g, lower, upper) = network(
+            5,
+            &[
+                (0, 1, 0, 10),
+                (0, 2, 0, 10),
+                (1, 3, 2, 4),
+                (2, 3, 0, 8),
+                (3, 4, 5, 9),
+                (2, 1, 1, 3),
+            ],
+        );
+        check_maxflow::<Dinic<_, _>>(&g, &lower, &upper);
+        check_maxflow::<EdmondsKarp<_, _>>(&g, &lower, &upper);
+        check_maxflow::<PushRelabel<_, _>>(&g, &lower, &upper);
+        check_maxflow::<BoykovKolmogorov<_, _>>(&g, &lower, &upper);
+    }
+
+    #[test]
+    fn test_infeasible_flow() {
+        // more flow must enter node 1 than can leave it
+        let (g, lower, upper) = network(4, &[(0, 1, 0, 10), (2, 1, 4, 5), (1, 3, 0, 3), (0, 2, 0, 10)]);
+        let (s, t) = (g.id2node(0), g.id2node(3));
+        match solve_with_lower_bounds::<Dinic<_, _>>(&g, s, t, |e| lower[g.edge_id(e)], |e| upper[g.edge_id(e)]) {
+            Err(Infeasibility::Cut(cut)) => check_cut(&g, &cut, &lower, &upper),
+            _ => panic!("Problem should be infeasible"),
+        }
+    }
+
+    #[test]
+    fn test_invalid_bounds() {
+        let (g, lower, upper) = network(2, &[(0, 1, 3, 2)]);
+        let result = solve_with_lower_bounds::<Dinic<_, _>>(
+            &g,
+            g.id2node(0),
+            g.id2node(1),
+            |e| lower[g.edge_id(e)],
+            |e| upper[g.edge_id(e)],
+        );
+        assert_eq!(result, Err(Infeasibility::Bounds(g.id2edge(0))));
+    }
+
+    #[test]
+    fn test_circulation() {
+        let (g, lower, upper) = network(3, &[(0, 1, 2, 5), (1, 2, 0, 4), (2, 0, 3, 6)]);
+        let flow = feasible_circulation::<Dinic<_, _>>(&g, |e| lower[g.edge_id(e)], |e| upper[g.edge_id(e)])
+            .expect("Problem should be feasible");
+        assert!(flow
+            .iter()
+            .all(|&(e, f)| lower[g.edge_id(e)] <= f && f <= upper[g.edge_id(e)]));
+        assert!(flow.iter().all(|&(_, f)| f == flow[0].1));
+
+        let (g, lower, upper) = network(3, &[(0, 1, 2, 5), (1, 2, 0, 2), (2, 0, 3, 6)]);
+        match feasible_circulation::<Dinic<_, _>>(&g, |e| lower[g.edge_id(e)], |e| upper[g.edge_id(e)]) {
+            Err(Infeasibility::Cut(cut)) => check_cut(&g, &cut, &lower, &upper),
+            _ => panic!("Problem should be infeasible"),
+        }
+    }
+
+    #[test]
+    fn test_unsigned() {
+        // the edge entering the source comes first
+        let edges = [(1, 0, 1u32, 2u32), (0, 1, 1, 5), (1, 2, 0, 4)];
+        let g = Net::new_with(|b| {
+            let nodes = b.add_nodes(3);
+            for &(u, v, _, _) in &edges {
+                b.add_edge(nodes[u], nodes[v]);
+            }
+        });
+        let lower = edges.iter().map(|e| e.2).collect::<Vec<_>>();
+        let upper = edges.iter().map(|e| e.3).collect::<Vec<_>>();
+        let (s, t) = (g.id2node(0), g.id2node(2));
+
+        let (value, flow, _) =
+            solve_with_lower_bounds::<Dinic<_, _>>(&g, s, t, |e| lower[g.edge_id(e)], |e| upper[g.edge_id(e)])
+                .expect("Problem should be feasible");
+        assert_eq!(value, 4);
+        assert!(flow
+            .iter()
+            .all(|&(e, f)| lower[g.edge_id(e)] <= f && f <= upper[g.edge_id(e)]));
+
+        let (value, _, _) =
+            solve_with_lower_bounds::<PushRelabel<_, _>>(&g, s, t, |e| lower[g.edge_id(e)], |e| upper[g.edge_id(e)])
+                .expect("Problem should be feasible");
+        assert_eq!(value, 4);
+
+        let flow = feasible_circulation::<Dinic<_, _>>(&g, |e| lower[g.edge_id(e)], |e| upper[g.edge_id(e)])
+            .expect("Problem should be feasible");
+        assert!(flow
+            .iter()
+            .all(|&(e, f)| lower[g.edge_id(e)] <= f && f <= upper[g.edge_id(e)]));
+        assert_eq!(flow[0].1, flow[1].1);
+        assert_eq!(flow[2].1, 0);
+    }
+}
@@ -15,6 +15,45 @@
 //
 
 //! Maximum Network Flow algorithms.
+//!
+//! All algorithms implement the [`MaxFlow`] trait, so code can be
+//! written generically over the algorithm. The function [`solve_with`]
+//! solves a max-flow problem with a given algorithm.
+//!
+//! # Example
+//!
+//! ```
+//! use rs_graph::{Buildable, Builder, Net};
+//! use rs_graph::maxflow::{self, BoykovKolmogorov, Dinic, EdmondsKarp, PushRelabel};
+//! use rs_graph::traits::*;
+//!
+//! let g = Net::new_with(|b| {
+//!     let nodes = b.add_nodes(4);
+//!     b.add_edge(nodes[0], nodes[1]);
+//!     b.add_edge(nodes[0], nodes[2]);
+//!     b.add_edge(nodes[1], nodes[2]);
+//!     b.add_edge(nodes[1], nodes[3]);
+//!     b.add_edge(nodes[2], nodes[3]);
+//! });
+//! let upper = [4, 2, 3, 1, 5];
+//! let s = g.id2node(0);
+//! let t = g.id2node(3);
+//!
+//! let (value, _, _) = maxflow::solve_with::<Dinic<_, _>>(&g, s, t, |e| upper[g.edge_id(e)]);
+//! assert_eq!(value, 6);
+//! let (value, _, _) = maxflow::solve_with::<EdmondsKarp<_, _>>(&g, s, t, |e| upper[g.edge_id(e)]);
+//! assert_eq!(value, 6);
+//! let (value, _, _) = maxflow::solve_with::<PushRelabel<_, _>>(&g, s, t, |e| upper[g.edge_id(e)]);
+//! assert_eq!(value, 6);
+//! let (value, _, _) = maxflow::solve_with::<BoykovKolmogorov<_, _>>(&g, s, t, |e| upper[g.edge_id(e)]);
+//! assert_eq!(value, 6);
+//! ```
+
+use crate::num::traits::NumAssign;
+use crate::traits::{GraphType, IndexDigraph};
+
+use std::cmp::min;
+use std::collections::VecDeque;
 
 pub mod edmondskarp;
 pub use self::edmondskarp::{edmondskarp, EdmondsKarp};
@@ -23,4 +62,296 @@ pub mod dinic;
 pub use self::dinic::{dinic, Dinic};
 
 pub mod pushrelabel;
-pub use self::pushrelabel::{pushrelabel, PushRelabel};
+pub use self::pushrelabel::{pushrelabel, PushRelabel, Strategy};
+
+pub mod boykovkolmogorov;
+pub use self::boykovkolmogorov::{boykovkolmogorov, BoykovKolmogorov};
+
+pub mod lowerbounds;
+pub use self::lowerbounds::{feasible_circulation, solve_with_lower_bounds};
+
+pub mod parametric;
+pub use self::parametric::parametric_maxflow;
+
+/// A max-flow algorithm.
+pub trait MaxFlow<'a> {
+    /// The type of the underlying graph.
+    type Graph: 'a + IndexDigraph;
+
+    /// The type of flow values.
+    type Flow: 'a + NumAssign + Ord + Copy;
+
+    /// The same algorithm for graphs of type `H`.
+    ///
+    /// This allows generic code to run the algorithm on auxiliary
+    /// networks it constructs internally.
+    type Solver<'b, H>: MaxFlow<'b, Graph = H, Flow = Self::Flow>
+    where
+        H: 'b + IndexDigraph,
+        Self::Flow: 'b;
+
+    /// Create a new max-flow algorithm for the graph `g`.
+    fn new(g: &'a Self::Graph) -> Self;
+
+    /// Return the underlying graph.
+    fn as_graph(&self) -> &'a Self::Graph;
+
+    /// Return the value of the latest computed maximum flow.
+    fn value(&self) -> Self::Flow;
+
+    /// Return the flow value on edge `e`.
+    fn flow(&self, e: <Self::Graph as GraphType>::Edge<'_>) -> Self::Flow;
+
+    /// Return an iterator over all (edge, flow) pairs.
+    fn flow_iter<'b>(&'b self) -> impl Iterator<Item = (<Self::Graph as GraphType>::Edge<'a>, Self::Flow)> + 'b
+    where
+        'a: 'b;
+
+    /// Solve the maxflow problem.
+    ///
+    /// The method solves the max flow problem from the source node
+    /// `src` to the sink node `snk` with the given `upper` bounds on
+    /// the edges.
+    fn solve<Us>(
+        &mut self,
+        src: <Self::Graph as GraphType>::Node<'_>,
+        snk: <Self::Graph as GraphType>::Node<'_>,
+        upper: Us,
+    ) where
+        Us: Fn(<Self::Graph as GraphType>::Edge<'a>) -> Self::Flow;
+
+    /// Return the minimal cut associated with the last maximum flow.
+    fn mincut(&self) -> Vec<<Self::Graph as GraphType>::Node<'a>>;
+}
+
+/// Solve the maxflow problem with the algorithm `M`.
+///
+/// The function solves the max flow problem from the source node
+/// `src` to the sink node `snk` with the given `upper` bounds on
+/// the edges.
+///
+/// The function returns the flow value, the flow on each edge and the
+/// nodes in a minimal cut.
+#[allow(clippy::type_complexity)]
+pub fn solve_with<'a, M>(
+    g: &'a M::Graph,
+    src: <M::Graph as GraphType>::Node<'_>,
+    snk: <M::Graph as GraphType>::Node<'_>,
+    upper: impl Fn(<M::Graph as GraphType>::Edge<'a>) -> M::Flow,
+) -> (
+    M::Flow,
+    Vec<(<M::Graph as GraphType>::Edge<'a>, M::Flow)>,
+    Vec<<M::Graph as GraphType>::Node<'a>>,
+)
+where
+    M: MaxFlow<'a>,
+{
+    let mut maxflow = M::new(g);
+    maxflow.solve(src, snk, upper);
+    (maxflow.value(), maxflow.flow_iter().collect(), maxflow.mincut())
+}
+
+/// Turn the flow of a previous computation into a feasible flow for new capacities.
+///
+/// The arcs `2*i` and `2*i+1` are the forward and backward arcs of the
+/// edge `i`, `neighs[u]` contains the pairs `(arc, v)` of arcs leaving
+/// `u`. As in the algorithms, `flow[2*i]` is the flow on edge `i` and
+/// `flow[2*i+1]` is its residual capacity, so the residual capacity of
+/// an arc `e` is `flow[e^1]`.
+///
+/// The flows are first reduced to the new capacities `upper`. Then the
+/// excesses are routed to deficit nodes, the source or the sink, and
+/// finally the remaining deficits are filled from the source or the
+/// sink. Afterwards `flow` is a feasible `src`-`snk`-flow (of
+/// possibly smaller value) w.r.t. the new capacities.
+fn repair_flow<F, Us>(neighs: &[Vec<(usize, usize)>], flow: &mut [F], src: usize, snk: usize, upper: Us)
+where
+    F: NumAssign + Ord + Copy,
+    Us: Fn(usize) -> F,
+{
+    let n = neighs.len();
+    let mut outflow = vec![F::zero(); n];
+    let mut inflow = vec![F::zero(); n];
+    for (uid, neighs) in neighs.iter().enumerate() {
+        for &(e, vid) in neighs {
+            if e & 1 == 0 {
+                let cap = upper(e >> 1);
+                flow[e] = min(flow[e], cap);
+                flow[e | 1] = cap - flow[e];
+                outflow[uid] += flow[e];
+                inflow[vid] += flow[e];
+            }
+        }
+    }
+
+    // the remaining net inflow (excess) and net outflow (deficit) of each node
+    let mut excess = vec![F::zero(); n];
+    let mut deficit = vec![F::zero(); n];
+    for uid in 0..n {
+        if uid == src || uid == snk {
+            continue;
+        }
+        if inflow[uid] > outflow[uid] {
+            excess[uid] = inflow[uid] - outflow[uid];
+        } else {
+            deficit[uid] = outflow[uid] - inflow[uid];
+        }
+    }
+
+    let mut pred = vec![None; n];
+    let mut queue = VecDeque::with_capacity(n);
+
+    // Route excesses to a deficit node, the source or the sink.
+    #[allow(clippy::needless_range_loop)]
+    for uid in 0..n {
+        while excess[uid] > F::zero() {
+            let tid = find_residual_path(neighs, flow, uid, true, &mut pred, &mut queue, |vid| {
+                vid == src || vid == snk || deficit[vid] > F::zero()
+            })
+            .expect("Excess node must be connected to a deficit node");
+            let mut df = excess[uid];
+            if tid != src && tid != snk {
+                df = min(df, deficit[tid]);
+            }
+            df = augment_path(flow, &pred, tid, uid, df);
+            excess[uid] -= df;
+            if tid != src && tid != snk {
+                deficit[tid] -= df;
+            }
+        }
+    }
+
+    // Fill the remaining deficits from the source or the sink.
+    #[allow(clippy::needless_range_loop)]
+    for uid in 0..n {
+        while deficit[uid] > F::zero() {
+            let tid = find_residual_path(neighs, flow, uid, false, &mut pred, &mut queue, |vid| {
+                vid == src || vid == snk
+            })
+            .expect("Deficit node must be connected to the source or the sink");
+            let df = deficit[uid];
+            deficit[uid] -= augment_path(flow, &pred, tid, uid, df);
+        }
+    }
+}
+
+/// Find a path in the residual network by a BFS.
+///
+/// If `forward` is `true` the search starts at `start` along residual
+/// arcs, otherwise it searches backwards for a path ending in `start`.
+/// The search stops at the first node satisfying `is_target` and
+/// returns that node. For each node `v` on the path, `pred[v]` contains
+/// the arc of the path incident to `v` and the next node towards
+/// `start`.
+fn find_residual_path<F, T>(
+    neighs: &[Vec<(usize, usize)>],
+    flow: &[F],
+    start: usize,
+    forward: bool,
+    pred: &mut [Option<(usize, usize)>],
+    queue: &mut VecDeque<usize>,
+    is_target: T,
+) -> Option<usize>
+where
+    F: NumAssign + Ord + Copy,
+    T: Fn(usize) -> bool,
+{
+    pred.fill(None);
+    pred[start] = Some((usize::MAX, start));
+    queue.clear();
+    queue.push_back(start);
+    while let Some(uid) = queue.pop_front() {
+        for &(e, vid) in &neighs[uid] {
+            // The arc `e` goes from `uid` to `vid`, the arc `e^1` in the other direction.
+            let a = if forward { e } else { e ^ 1 };
+            if pred[vid].is_none() && flow[a ^ 1] > F::zero() {
+                pred[vid] = Some((a, uid));
+                if is_target(vid) {
+                    return Some(vid);
+                }
+                queue.push_back(vid);
+            }
+        }
+    }
+    None
+}
+
+/// Send at most `df` units of flow along the path found by `find_residual_path`.
+///
+/// Returns the amount of flow actually sent.
+fn augment_path<F>(flow: &mut [F], pred: &[Option<(usize, usize)>], end: usize, start: usize, mut df: F) -> F
+where
+    F: NumAssign + Ord + Copy,
+{
+    let mut vid = end;
+    while vid != start {
+        let (a, next) = pred[vid].unwrap();
+        df = min(df, flow[a ^ 1]);
+        vid = next;
+    }
+    let mut vid = end;
+    while vid != start {
+        let (a, next) = pred[vid].unwrap();
+        flow[a] += df;
+        flow[a ^ 1] -= df;
+        vid = next;
+    }
+    df
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Dinic, PushRelabel};
+    use crate::traits::*;
+    use crate::{Buildable, Builder, Net};
+
+    /// Instance with `u32` capacities for testing warm-start re-solves.
+    fn instance() -> (Net, Vec<u32>) {
+        let edges = [(0, 2, 5), (2, 3, 5), (3, 1, 5), (2, 4, 4), (4, 1, 4), (0, 4, 1)];
+        let g = Net::new_with(|b| {
+            let nodes = b.add_nodes(5);
+            for &(u, v, _) in &edges {
+                b.add_edge(nodes[u], nodes[v]);
+            }
+        });
+        (g, edges.iter().map(|&(_, _, c)| c).collect())
+    }
+
+    #[test]
+    fn test_dinic_resolve_unsigned() {
+        let (g, mut upper) = instance();
+        let (s, t) = (g.id2node(0), g.id2node(1));
+        let mut dinic = Dinic::new(&g);
+        dinic.solve(s, t, |e| upper[g.edge_id(e)]);
+        assert_eq!(dinic.value(), 6);
+
+        upper[1] = 3;
+        dinic.resolve(s, t, |e| upper[g.edge_id(e)]);
+        assert_eq!(dinic.value(), 6);
+        assert!(g.edges().all(|e| dinic.flow(e) <= upper[g.edge_id(e)]));
+
+        upper[0] = 2;
+        dinic.resolve(s, t, |e| upper[g.edge_id(e)]);
+        assert_eq!(dinic.value(), 3);
+        assert!(g.edges().all(|e| dinic.flow(e) <= upper[g.edge_id(e)]));
+    }
+
+    #[test]
+    fn test_pushrelabel_resolve_unsigned() {
+        let (g, mut upper) = instance();
+        let (s, t) = (g.id2node(0), g.id2node(1));
+        let mut pr = PushRelabel::new(&g);
+        pr.solve(s, t, |e| upper[g.edge_id(e)]);
+        assert_eq!(pr.value(), 6);
+
+        upper[1] = 3;
+        pr.resolve(s, t, |e| upper[g.edge_id(e)]);
+        assert_eq!(pr.value(), 6);
+        assert!(g.edges().all(|e| pr.flow(e) <= upper[g.edge_id(e)]));
+
+        upper[0] = 2;
+        pr.resolve(s, t, |e| upper[g.edge_id(e)]);
+        assert_eq!(pr.value(), 3);
+        assert!(g.edges().all(|e| pr.flow(e) <= upper[g.edge_id(e)]));
+    }
+}
@@ -0,0 +1,441 @@
+// Copyright (c) 2022 Frank Fischer <frank-fischer@shadow-soft.de>
+//
+// This program is free software: you can redistribute it and/or
+// modify it under the terms of the GNU General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see  <http://www.gnu.org/licenses/>
+//
+
+//! Parametric maximum flows.
+//!
+//! In a parametric max-flow problem the capacities depend on a
+//! parameter `lambda`: the capacities of edges leaving the source are
+//! non-decreasing functions of `lambda`, the capacities of edges
+//! entering the sink are non-increasing functions of `lambda` and all
+//! other capacities are constant. Gallo, Grigoriadis and Tarjan showed
+//! that the minimal cuts of such a problem are nested (the source side
+//! grows with `lambda`) and that the push-relabel algorithm can reuse
+//! its preflow when `lambda` increases (see
+//! [`PushRelabel::resolve_parametric`]).
+//!
+//! The capacities are required to be affine functions of the
+//! parameter. Then the breakpoints, i.e. the parameter values at which
+//! the minimal cut changes, are computed exactly if the flow type
+//! supports exact division (e.g. rational numbers). For integral flow
+//! types the breakpoints are rounded up to the next integer, for
+//! floating point types they are subject to rounding errors.
+//!
+//! # Example
+//!
+//! The densest subgraph of a graph can be found by a parametric
+//! problem: the source is connected to a node for each edge with
+//! capacity `lambda`, each edge node is connected to both its end nodes
+//! and each node is connected to the sink with capacity `k`. Then the
+//! source side of a minimal cut contains a subgraph with density
+//! (edges per node) at least `k / lambda` (if it is non-trivial).
+//!
+//! ```
+//! use rs_graph::{Buildable, Builder, Net};
+//! use rs_graph::maxflow::parametric::parametric_maxflow;
+//! use rs_graph::traits::*;
+//!
+//! // a complete graph on 4 nodes with a pendant node 4
+//! let edges = [(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3), (3, 4)];
+//! let n = 5;
+//! let g = Net::new_with(|b| {
+//!     let s = b.add_node();
+//!     let t = b.add_node();
+//!     let nodes = b.add_nodes(n);
+//!     for &(u, v) in &edges {
+//!         let x = b.add_node();
+//!         b.add_edge(s, x);
+//!         b.add_edge(x, nodes[u]);
+//!         b.add_edge(x, nodes[v]);
+//!     }
+//!     for &u in &nodes {
+//!         b.add_edge(u, t);
+//!     }
+//! });
+//! let (s, t) = (g.id2node(0), g.id2node(1));
+//!
+//! let k = 6;
+//! let breakpoints = parametric_maxflow(&g, s, t, 0, 20, |e, lambda| {
+//!     if g.src(e) == s {
+//!         lambda
+//!     } else if g.snk(e) == t {
+//!         k
+//!     } else {
+//!         100
+//!     }
+//! });
+//!
+//! // the complete graph (density 6/4) appears for lambda = 4, the
+//! // whole graph (density 7/5) for lambda = 6
+//! let lambdas = breakpoints.iter().map(|bp| bp.lambda).collect::<Vec<_>>();
+//! assert_eq!(lambdas, vec![0, 4, 6]);
+//! assert_eq!(breakpoints[0].cut, vec![s]);
+//! assert_eq!(breakpoints[1].cut.len(), 1 + 4 + 6);
+//! assert_eq!(breakpoints[2].cut.len(), 1 + 5 + 7);
+//! ```
+
+use super::PushRelabel;
+use crate::traits::{GraphType, IndexDigraph};
+
+use crate::num::traits::NumAssign;
+
+use std::marker::PhantomData;
+
+/// A breakpoint of a parametric max-flow problem.
+#[derive(Clone, Debug)]
+pub struct Breakpoint<N, F> {
+    /// The parameter value at which the minimal cut changes.
+    pub lambda: F,
+    /// The value of a maximum flow for this parameter value.
+    pub value: F,
+    /// The source side of the minimal cut.
+    ///
+    /// This is the minimal cut for all parameter values from `lambda`
+    /// up to (but excluding) the next breakpoint. It is the maximal
+    /// source side, i.e. the set of nodes from which the sink cannot be
+    /// reached in the residual network.
+    pub cut: Vec<N>,
+}
+
+/// Solve a parametric max-flow problem.
+///
+/// The capacity of edge `e` for parameter `lambda` is `upper(e,
+/// lambda)`. The capacities of edges leaving `src` must be
+/// non-decreasing affine functions of `lambda`, the capacities of
+/// edges entering `snk` must be non-increasing affine functions of
+/// `lambda`, all other capacities must not depend on `lambda`.
+///
+/// The function returns all breakpoints in the range
+/// `lambda_min..=lambda_max` in increasing order, i.e. the parameter
+/// values at which the minimal cut changes. The first breakpoint is
+/// always `lambda_min`. The cuts of the breakpoints are nested. If `F`
+/// is an integral type, each breakpoint is the smallest integral
+/// parameter value with the new minimal cut.
+///
+/// The breakpoints are found by the slope algorithm of Gallo,
+/// Grigoriadis and Tarjan: the next breakpoint is approached from
+/// above by intersecting the capacity of the current minimal cut with
+/// the capacity of a minimal cut for a larger parameter value (i.e.
+/// Newton's method for the minimal cut function). All problems are
+/// solved by [`PushRelabel::resolve_parametric`] starting from the
+/// preflow of a smaller parameter value, only the problem for
+/// `lambda_min` is solved from scratch.
+pub fn parametric_maxflow<'a, G, F, Us>(
+    g: &'a G,
+    src: G::Node<'_>,
+    snk: G::Node<'_>,
+    lambda_min: F,
+    lambda_max: F,
+    upper: Us,
+) -> Vec<Breakpoint<G::Node<'a>, F>>
+where
+    G: IndexDigraph,
+    F: 'a + NumAssign + Ord + Copy,
+    Us: Fn(G::Edge<'a>, F) -> F,
+{
+    Solver::new(g, src, snk, upper).breakpoints(lambda_min, lambda_max)
+}
+
+/// The push-relabel algorithm for a parametric problem.
+struct Solver<'a, G, F, Us>
+where
+    G: IndexDigraph,
+{
+    g: &'a G,
+    src: usize,
+    snk: usize,
+    upper: Us,
+    /// Whether `F` is an integral type.
+    integral: bool,
+    /// The number of problems solved from scratch.
+    cnt_cold: usize,
+    /// The number of problems solved by reusing a preflow.
+    cnt_warm: usize,
+    phantom: PhantomData<F>,
+}
+
+/// The solution of the problem for a fixed parameter value.
+struct Probe<'a, G, F>
+where
+    G: IndexDigraph,
+{
+    /// The solver containing the maximum preflow.
+    pr: PushRelabel<'a, G, F>,
+    /// The parameter value.
+    lambda: F,
+    /// The value of the maximum flow.
+    value: F,
+    /// The maximal source side of the minimal cut.
+    cut: Vec<<G as GraphType>::Node<'a>>,
+    /// Whether a node (by index) is contained in `cut`.
+    incut: Vec<bool>,
+}
+
+impl<'a, G, F> Probe<'a, G, F>
+where
+    G: IndexDigraph,
+    F: 'a + NumAssign + Ord + Copy,
+{
+    fn new(pr: PushRelabel<'a, G, F>, lambda: F) -> Self {
+        let g = pr.as_graph();
+        let cut = pr.mincut();
+        let mut incut = vec![false; g.num_nodes()];
+        for &u in &cut {
+            incut[g.node_id(u)] = true;
+        }
+        Probe {
+            value: pr.value(),
+            pr,
+            lambda,
+            cut,
+            incut,
+        }
+    }
+
+    fn breakpoint(&self) -> Breakpoint<G::Node<'a>, F> {
+        Breakpoint {
+            lambda: self.lambda,
+            value: self.value,
+            cut: self.cut.clone(),
+        }
+    }
+}
+
+impl<'a, G, F, Us> Solver<'a, G, F, Us>
+where
+    G: IndexDigraph,
+    F: 'a + NumAssign + Ord + Copy,
+    Us: Fn(G::Edge<'a>, F) -> F,
+{
+    fn new(g: &'a G, src: G::Node<'_>, snk: G::Node<'_>, upper: Us) -> Self {
+        Solver {
+            g,
+            src: g.node_id(src),
+            snk: g.node_id(snk),
+            upper,
+            integral: F::one() / (F::one() + F::one()) == F::zero(),
+            cnt_cold: 0,
+            cnt_warm: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Compute all breakpoints in the range `lambda_min..=lambda_max`.
+    fn breakpoints(&mut self, lambda_min: F, lambda_max: F) -> Vec<Breakpoint<G::Node<'a>, F>> {
+        assert!(lambda_min <= lambda_max, "The parameter range must not be empty");
+
+        let mut pr = PushRelabel::new(self.g);
+        pr.compute_flow = false;
+        pr.solve(self.g.id2node(self.src), self.g.id2node(self.snk), |e| {
+            (self.upper)(e, lambda_min)
+        });
+        self.cnt_cold += 1;
+        let mut cur = Probe::new(pr, lambda_min);
+
+        let mut breakpoints = vec![cur.breakpoint()];
+        if lambda_min == lambda_max {
+            return breakpoints;
+        }
+
+        let max = self.probe(&cur, lambda_max);
+        while cur.cut.len() < max.cut.len() {
+            match self.next_breakpoint(&cur, &max) {
+                Some(next) => {
+                    breakpoints.push(next.breakpoint());
+                    cur = next;
+                }
+                None => {
+                    breakpoints.push(max.breakpoint());
+                    break;
+                }
+            }
+        }
+
+        breakpoints
+    }
+
+    /// Find the next breakpoint after `cur`.
+    ///
+    /// The solution `max` is for the largest parameter value, its cut
+    /// must be larger than the cut of `cur`. Returns the solution for
+    /// the next breakpoint or `None` if this is `max`.
+    fn next_breakpoint(&mut self, cur: &Probe<'a, G, F>, max: &Probe<'a, G, F>) -> Option<Probe<'a, G, F>> {
+        let mut hi: Option<Probe<'a, G, F>> = None;
+        loop {
+            let h = hi.as_ref().unwrap_or(max);
+            // The capacity of the current cut exceeds the minimal cut
+            // capacity at `h.lambda` by `d`. If `d` is zero, the
+            // current cut is minimal up to `h.lambda`.
+            let d = self.capacity(&cur.incut, h.lambda) - h.value;
+            if d == F::zero() {
+                return hi;
+            }
+
+            // Intersect the (affine) capacity functions of both cuts.
+            // The minimal cut function is concave, so the next
+            // breakpoint is not larger than the intersection point.
+            let d0 = self.capacity(&h.incut, cur.lambda) - cur.value;
+            let num = (h.lambda - cur.lambda) * d0;
+            let den = d0 + d;
+            let mut step = num / den;
+            if self.integral && step * den < num {
+                step += F::one();
+            }
+            let lambda = cur.lambda + step;
+
+            if lambda < h.lambda {
+                let next = self.probe(cur, lambda);
+                if !self.integral && next.cut.len() == h.cut.len() {
+                    return Some(next);
+                }
+                hi = Some(next);
+            } else if self.integral && h.lambda - F::one() > cur.lambda {
+                // Rounding did not make progress, check whether the
+                // cut changes before `h.lambda`.
+                let next = self.probe(cur, h.lambda - F::one());
+                if next.cut.len() == cur.cut.len() {
+                    return hi;
+                }
+                hi = Some(next);
+            } else {
+                return hi;
+            }
+        }
+    }
+
+    /// Solve the problem for `lambda` starting from the preflow of `base`.
+    fn probe(&mut self, base: &Probe<'a, G, F>, lambda: F) -> Probe<'a, G, F> {
+        debug_assert!(base.lambda < lambda);
+        let mut pr = base.pr.clone();
+        pr.resolve_parametric(self.g.id2node(self.src), self.g.id2node(self.snk), |e| {
+            (self.upper)(e, lambda)
+        });
+        self.cnt_warm += 1;
+        Probe::new(pr, lambda)
+    }
+
+    /// Return the capacity of a cut for parameter `lambda`.
+    fn capacity(&self, incut: &[bool], lambda: F) -> F {
+        let g = self.g;
+        g.edges()
+            .filter(|&e| incut[g.node_id(g.src(e))] && !incut[g.node_id(g.snk(e))])
+            .fold(F::zero(), |cap, e| cap + (self.upper)(e, lambda))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parametric_maxflow, Solver};
+    use crate::maxflow::pushrelabel;
+    use crate::traits::*;
+    use crate::{Buildable, Builder, Net};
+
+    use ordered_float::OrderedFloat;
+
+    #[test]
+    fn test_fractional() {
+        // node 2 joins the source side at lambda = 1.5, node 3 at lambda = 2.75
+        let g = Net::new_with(|b| {
+            let nodes = b.add_nodes(4);
+            b.add_edge(nodes[0], nodes[2]);
+            b.add_edge(nodes[2], nodes[1]);
+            b.add_edge(nodes[0], nodes[3]);
+            b.add_edge(nodes[3], nodes[1]);
+        });
+        let (s, t) = (g.id2node(0), g.id2node(1));
+
+        let caps = [(0.0, 1.0), (3.0, -1.0), (0.0, 1.0), (5.5, -1.0)];
+        let breakpoints = parametric_maxflow(&g, s, t, OrderedFloat(0.0), OrderedFloat(3.0), |e, lambda| {
+            let (c, d) = caps[g.edge_id(e)];
+            OrderedFloat(c) + OrderedFloat(d) * lambda
+        });
+        let lambdas = breakpoints.iter().map(|bp| bp.lambda.0).collect::<Vec<_>>();
+        assert_eq!(lambdas, vec![0.0, 1.5, 2.75]);
+        assert_eq!(breakpoints[1].value.0, 3.0);
+        assert_eq!(breakpoints[1].cut, vec![s, g.id2node(2)]);
+        assert_eq!(breakpoints[2].value.0, 3.0);
+
+        // for integral parameters the breakpoints are rounded up
+        let caps = [(0, 1), (3, -1), (0, 1), (5, -1)];
+        let breakpoints = parametric_maxflow(&g, s, t, 0, 3, |e, lambda| {
+            let (c, d) = caps[g.edge_id(e)];
+            c + d * lambda
+        });
+        let lambdas = breakpoints.iter().map(|bp| bp.lambda).collect::<Vec<_>>();
+        assert_eq!(lambdas, vec![0, 2, 3]);
+    }
+
+    #[test]
+    fn test_random() {
+        let mut next = crate::testutil::lcg(23);
+
+        for _ in 0..10 {
+            let n = 12;
+            // each edge has a constant capacity and a slope
+            let mut caps = vec![];
+            let g = Net::new_with(|b| {
+                let nodes = b.add_nodes(n);
+                for u in 2..n {
+                    b.add_edge(nodes[0], nodes[u]);
+                    caps.push((next(10) as i64, next(4) as i64));
+                    b.add_edge(nodes[u], nodes[1]);
+                    caps.push((next(10) as i64 + 60, next(4) as i64));
+                    for v in 2..n {
+                        if u != v && next(4) == 0 {
+                            b.add_edge(nodes[u], nodes[v]);
+                            caps.push((next(10) as i64, 0));
+                        }
+                    }
+                }
+            });
+            let (s, t) = (g.id2node(0), g.id2node(1));
+            let upper = |e, lambda: i64| {
+                let (c, d) = caps[g.edge_id(e)];
+                if g.src(e) == s {
+                    c + d * lambda
+                } else if g.snk(e) == t {
+                    c - d * lambda
+                } else {
+                    c
+                }
+            };
+
+            let mut solver = Solver::new(&g, s, t, upper);
+            let breakpoints = solver.breakpoints(0, 15);
+            // only the first problem is solved from scratch
+            assert_eq!(solver.cnt_cold, 1);
+            assert!(solver.cnt_warm >= breakpoints.len());
+            assert_eq!(breakpoints[0].lambda, 0);
+            for w in breakpoints.windows(2) {
+                assert!(w[0].lambda < w[1].lambda);
+                assert!(w[0].cut.len() < w[1].cut.len());
+                assert!(w[0].cut.iter().all(|u| w[1].cut.contains(u)));
+            }
+
+            // compare with the solutions for each parameter value
+            for lambda in 0..=15 {
+                let (value, _, mut cut) = pushrelabel(&g, s, t, |e| upper(e, lambda));
+                let bp = breakpoints.iter().rev().find(|bp| bp.lambda <= lambda).unwrap();
+                let mut bpcut = bp.cut.clone();
+                cut.sort_by_key(|&u| g.node_id(u));
+                bpcut.sort_by_key(|&u| g.node_id(u));
+                assert_eq!(cut, bpcut);
+                if bp.lambda == lambda {
+                    assert_eq!(bp.value, value);
+                }
+            }
+        }
+    }
+}
@@ -18,7 +18,16 @@
 //! flow problems.
 //!
 //! This implementation uses the gap heuristic and the global
-//! relabelling heuristic.
+//! relabelling heuristic. The active nodes can be selected by
+//! different strategies (see [`Strategy`]), the default is the
+//! highest-label strategy.
+//!
+//! The algorithm works in two phases. Phase I computes a maximum
+//! preflow, which already determines the flow value and a minimal cut.
+//! Phase II turns the preflow into a flow by returning the remaining
+//! excess to the source. If only the value or the minimal cut is
+//! needed, phase II can be skipped by setting
+//! [`PushRelabel::compute_flow`] to `false`.
 //!
 //! # Example
 //!
@@ -115,6 +124,7 @@
 //! }));
 //! ```
 
+use super::{repair_flow, solve_with, MaxFlow};
 use crate::traits::IndexDigraph;
 
 use std::cmp::min;
@@ -122,6 +132,23 @@ use std::collections::VecDeque;
 
 use crate::num::traits::NumAssign;
 
+/// The strategy for selecting the next active node.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Strategy {
+    /// Select an active node with the largest height.
+    #[default]
+    HighestLabel,
+    /// Select the active nodes in first-in-first-out order.
+    Fifo,
+    /// Select an active node with large excess and smallest height.
+    ///
+    /// The algorithm works in phases with a decreasing scaling
+    /// parameter `delta`. In each phase only nodes with excess larger
+    /// than `delta/2` are selected and no push lets the excess of a node
+    /// (except for the source and the sink) exceed `delta`.
+    ExcessScaling,
+}
+
 /// The push-relabel algorithm.
 ///
 /// This struct contains all algorithmic working data.
@@ -143,14 +170,65 @@ where
     buckets: Vec<Bucket>,
     /// The queue of nodes for a BFS.
     queue: VecDeque<usize>,
-    /// The largest height of an active node.
+    /// The queue of active nodes (FIFO strategy).
+    active: VecDeque<usize>,
+    /// The largest height of an active node (highest-label strategy).
     largest_act: usize,
+    /// The smallest height of an active node with large excess (excess scaling).
+    lowest_act: usize,
+    /// The scaling parameter (excess scaling).
+    delta: F,
+    /// Whether the algorithm is in phase II.
+    phase2: bool,
     /// The flow value.
     value: F,
     /// The number of relabel operations performed during the algorithm.
     pub cnt_relabel: usize,
     /// Whether to use the global relabelling heuristic.
     pub use_global_relabelling: bool,
+    /// The frequency of the global relabelling heuristic.
+    ///
+    /// A global relabelling is done after every
+    /// `global_relabelling_freq * n` relabel operations (but at least
+    /// after each relabel operation). The default is `1.0`.
+    pub global_relabelling_freq: f64,
+    /// The strategy for selecting the next active node.
+    pub strategy: Strategy,
+    /// Whether to compute a flow in phase II.
+    ///
+    /// If this is `false`, the algorithm stops after phase I with a
+    /// maximum preflow. The flow value and the minimal cut are correct
+    /// but the flow on the edges does not satisfy flow conservation.
+    /// The default is `true`.
+    pub compute_flow: bool,
+}
+
+impl<'a, G, F> Clone for PushRelabel<'a, G, F>
+where
+    G: 'a + IndexDigraph,
+    F: Clone,
+{
+    fn clone(&self) -> Self {
+        PushRelabel {
+            g: self.g,
+            nodes: self.nodes.clone(),
+            edges: self.edges.clone(),
+            flow: self.flow.clone(),
+            buckets: self.buckets.clone(),
+            queue: self.queue.clone(),
+            active: self.active.clone(),
+            largest_act: self.largest_act,
+            lowest_act: self.lowest_act,
+            delta: self.delta.clone(),
+            phase2: self.phase2,
+            value: self.value.clone(),
+            cnt_relabel: self.cnt_relabel,
+            use_global_relabelling: self.use_global_relabelling,
+            global_relabelling_freq: self.global_relabelling_freq,
+            strategy: self.strategy,
+            compute_flow: self.compute_flow,
+        }
+    }
 }
 
 /// Data associated with a node.
@@ -164,6 +242,8 @@ struct NodeInfo<Flow> {
     next_act: usize,
     /// The next edge to be considered
     iter: usize,
+    /// Whether the node is in the list of active nodes.
+    queued: bool,
 }
 
 impl<Flow> NodeInfo<Flow>
@@ -175,6 +255,7 @@ where
         self.excess = Flow::zero();
         self.next_act = usize::max_value();
         self.iter = 0;
+        self.queued = false;
     }
 }
 
@@ -186,17 +267,14 @@ struct Bucket {
     /// The active nodes are kept in a singly linked list, this is the first
     /// node in that list.
     first_act: usize,
-    /// Number of inactive nodes of this height.
-    num_inact: usize,
+    /// Number of (active and inactive) nodes of this height.
+    count: usize,
 }
 
 impl Bucket {
-    /// Returns `true` if the bucket is empty.
-    ///
-    /// This means that there are neither active nor inactive nodes of the
-    /// buckets height.
-    fn is_empty(&self) -> bool {
-        self.first_act == usize::max_value() && self.num_inact == 0
+    /// Return `true` if the bucket contains an active node.
+    fn has_active(&self) -> bool {
+        self.first_act != usize::MAX
     }
 }
 
@@ -216,6 +294,7 @@ where
                     excess: F::zero(),
                     next_act: usize::max_value(),
                     iter: 0,
+                    queued: false,
                 };
                 n
             ],
@@ -232,16 +311,23 @@ where
             buckets: vec![
                 Bucket {
                     first_act: usize::max_value(),
-                    num_inact: 0,
+                    count: 0,
                 };
                 n * 2
             ],
             queue: VecDeque::with_capacity(n),
+            active: VecDeque::with_capacity(n),
             largest_act: 0,
+            lowest_act: 0,
+            delta: F::zero(),
+            phase2: false,
             value: F::zero(),
 
             cnt_relabel: 0,
             use_global_relabelling: true,
+            global_relabelling_freq: 1.0,
+            strategy: Strategy::HighestLabel,
+            compute_flow: true,
         }
     }
 
@@ -282,33 +368,124 @@ where
         let snk = self.g.node_id(snk);
         assert_ne!(src, snk, "Source and sink node must not be equal");
 
-        let n = self.g.num_nodes();
         self.cnt_relabel = 0;
         self.init_preflow(src, upper);
+        self.run(src, snk);
+    }
+
+    /// Run the push-relabel algorithm starting from the current flow.
+    ///
+    /// This is like [`PushRelabel::solve`], but the flow of the latest
+    /// computation is used as starting point for the (possibly
+    /// changed) `upper` bounds. Flows exceeding their new upper bound
+    /// are reduced and rerouted, then the algorithm continues with the
+    /// resulting preflow. This is usually much faster than a new
+    /// computation if only a few bounds have changed.
+    ///
+    /// The flow value and the minimal cut are the same as for
+    /// [`PushRelabel::solve`], the flow on the edges may differ.
+    pub fn resolve<Us>(&mut self, src: G::Node<'_>, snk: G::Node<'_>, upper: Us)
+    where
+        Us: Fn(G::Edge<'a>) -> F,
+    {
+        let src = self.g.node_id(src);
+        let snk = self.g.node_id(snk);
+        assert_ne!(src, snk, "Source and sink node must not be equal");
 
+        self.cnt_relabel = 0;
+        self.init_warm_preflow(src, snk, upper);
+        self.run(src, snk);
+    }
+
+    /// Run the push-relabel algorithm after a monotone change of the capacities.
+    ///
+    /// This is the update step of the parametric algorithm of Gallo,
+    /// Grigoriadis and Tarjan. Compared to the latest computation the
+    /// capacities of edges leaving `src` must not have decreased, the
+    /// capacities of edges entering `snk` must not have increased and
+    /// all other capacities must be unchanged. Then the current
+    /// preflow remains valid: the flow on edges into the sink is
+    /// reduced to the new capacities and edges leaving the source are
+    /// saturated again. In particular, the flow need not have been
+    /// completed in phase II (see [`PushRelabel::compute_flow`]).
+    ///
+    /// The minimal cut of the new problem contains the minimal cut of
+    /// the previous problem.
+    pub fn resolve_parametric<Us>(&mut self, src: G::Node<'_>, snk: G::Node<'_>, upper: Us)
+    where
+        Us: Fn(G::Edge<'a>) -> F,
+    {
+        let src = self.g.node_id(src);
+        let snk = self.g.node_id(snk);
+        assert_ne!(src, snk, "Source and sink node must not be equal");
+
+        self.cnt_relabel = 0;
+        for i in 0..self.g.num_edges() {
+            let e = self.g.id2edge(i);
+            let cap = upper(e);
+            let flw = self.flow[i << 1];
+            if flw > cap {
+                // the flow is sent back to the tail of an edge into the sink
+                let uid = self.g.node_id(self.g.src(e));
+                let vid = self.g.node_id(self.g.snk(e));
+                assert_eq!(vid, snk, "Only capacities of edges into the sink may decrease");
+                self.nodes[uid].excess += flw - cap;
+                self.nodes[vid].excess -= flw - cap;
+                self.flow[i << 1] = cap;
+            }
+            self.flow[(i << 1) | 1] = cap - self.flow[i << 1];
+        }
+
+        // send maximal flow out of source
+        for &(e, v) in &self.edges[src] {
+            let f = e ^ 1;
+            let df = self.flow[f];
+            self.flow[e] += df;
+            self.flow[f] = F::zero();
+            self.nodes[v].excess += df;
+        }
+
+        self.run(src, snk);
+    }
+
+    /// Run both phases of the algorithm on the current preflow.
+    fn run(&mut self, src: usize, snk: usize) {
+        let n = self.g.num_nodes();
+        self.phase2 = false;
+        self.init_delta();
         self.update_heights(src, snk, false);
 
+        let freq = ((self.global_relabelling_freq * n as f64) as usize).max(1);
         let mut lvl_relabel = if self.use_global_relabelling {
-            n
+            self.cnt_relabel + freq
         } else {
             usize::max_value()
         };
 
-        while self.largest_act != 0 && self.largest_act != n {
-            let l = self.largest_act;
-            let u = self.buckets[l].first_act;
-            self.buckets[l].first_act = self.nodes[u].next_act;
-            self.discharge(u);
-            if self.cnt_relabel >= lvl_relabel && self.largest_act < n {
-                self.update_heights(src, snk, false);
-                lvl_relabel += n;
+        loop {
+            while let Some(u) = self.select() {
+                self.discharge(u);
+                if !self.phase2 && self.cnt_relabel >= lvl_relabel {
+                    self.update_heights(src, snk, false);
+                    lvl_relabel = self.cnt_relabel + freq;
+                }
             }
 
-            if self.largest_act == 0 {
-                // End of phase I. Compute exact labels one last time, this time
-                // from the source.
-                self.update_heights(src, snk, true);
+            if self.phase2 || !self.compute_flow {
+                break;
             }
+
+            // End of phase I. Compute exact labels one last time, this time
+            // from the source.
+            self.phase2 = true;
+            self.init_delta();
+            self.update_heights(src, snk, true);
+        }
+
+        if !self.compute_flow {
+            // Compute exact labels from the sink, the nodes that cannot
+            // reach the sink form the minimal cut.
+            self.update_heights(src, snk, false);
         }
 
         self.value = self.nodes[snk].excess;
@@ -355,10 +532,47 @@ where
         self.nodes[src].height = self.g.num_nodes();
     }
 
+    /// Initialize the preflow algorithm from the current flow.
+    ///
+    /// The current flow is made feasible for the new bounds, then all
+    /// edges leaving the source node are saturated. The excess of the
+    /// sink is set to the value of the flow.
+    fn init_warm_preflow<Us>(&mut self, src: usize, snk: usize, upper: Us)
+    where
+        Us: Fn(G::Edge<'a>) -> F,
+    {
+        for node in &mut self.nodes {
+            node.reset();
+        }
+
+        let g = self.g;
+        repair_flow(&self.edges, &mut self.flow, src, snk, |i| upper(g.id2edge(i)));
+
+        for &(e, _) in &self.edges[snk] {
+            if (e & 1) == 0 {
+                self.nodes[snk].excess -= self.flow[e];
+            } else {
+                self.nodes[snk].excess += self.flow[e ^ 1];
+            }
+        }
+
+        // send maximal flow out of source
+        for &(e, v) in &self.edges[src] {
+            let f = e ^ 1;
+            let df = self.flow[f];
+            self.flow[e] += df;
+            self.flow[f] = F::zero();
+            self.nodes[v].excess += df;
+        }
+
+        self.nodes[src].height = self.g.num_nodes();
+    }
+
     /// Compute exact labels.
     ///
     /// This function does a bfs from the sink (phase I) or source (phase II) to
-    /// compute exact labels.
+    /// compute exact labels. Afterwards all active nodes of the current phase
+    /// are added to the list of active nodes.
     fn update_heights(&mut self, src: usize, snk: usize, from_src: bool) {
         let n = self.g.num_nodes();
 
@@ -367,62 +581,195 @@ where
             node.height = n + n;
             // we need to reset the iterators for correctness
             node.iter = 0;
+            node.queued = false;
         }
 
         // Except for the source and and the sink
         self.nodes[snk].height = 0;
         self.nodes[src].height = n;
 
-        for b in if from_src {
-            &mut self.buckets[n + 1..]
-        } else {
-            &mut self.buckets[..n]
-        } {
-            b.first_act = usize::max_value();
-            b.num_inact = 0;
-        }
-
-        // source and sink count as inactive, just in case
-        self.buckets[0].num_inact = 1;
-        self.buckets[n].num_inact = 1;
-
         // find correct labels by BFS from sink
         self.queue.clear();
         self.queue.push_back(snk);
+        self.bfs();
+
+        // possibly start again from the source to find the perfect
+        // labels for phase II
+        if from_src {
+            self.queue.push_back(src);
+            self.bfs();
+        }
 
+        // fill the buckets
+        for b in &mut self.buckets {
+            b.first_act = usize::max_value();
+            b.count = 0;
+        }
+        self.active.clear();
         self.largest_act = 0;
+        self.lowest_act = n + n;
+        for u in 0..n {
+            let h = self.nodes[u].height;
+            if h < n + n {
+                self.buckets[h].count += 1;
+            }
+            self.activate(u);
+        }
+    }
+
+    /// Lower the heights of all nodes reachable backwards from the nodes in the queue.
+    fn bfs(&mut self) {
+        while let Some(v) = self.queue.pop_front() {
+            let h = self.nodes[v].height + 1;
+            for &(e, u) in &self.edges[v] {
+                let udata = &mut self.nodes[u];
+                if udata.height > h && self.flow[e] > F::zero() {
+                    udata.height = h;
+                    self.queue.push_back(u);
+                }
+            }
+        }
+    }
+
+    /// Initialize the scaling parameter for the excess scaling strategy.
+    ///
+    /// The parameter is set to the smallest power of 2 not smaller
+    /// than the largest excess of a node.
+    fn init_delta(&mut self) {
+        if self.strategy != Strategy::ExcessScaling {
+            return;
+        }
+        let max_excess = self.nodes.iter().map(|node| node.excess).max().unwrap_or_else(F::zero);
+        self.delta = F::one();
+        while self.delta < max_excess {
+            self.delta += self.delta;
+        }
+    }
+
+    /// Return `true` if a node of height `h` may be discharged in the current phase.
+    ///
+    /// In phase I these are the nodes with height in `1..n`, in phase
+    /// II the nodes with height in `n+1..2n`. In particular, the source
+    /// and the sink are never discharged.
+    fn in_phase(&self, h: usize) -> bool {
+        let n = self.g.num_nodes();
+        if self.phase2 {
+            n < h && h < n + n
+        } else {
+            0 < h && h < n
+        }
+    }
+
+    /// Return `true` if node `u` has large excess.
+    ///
+    /// For the excess scaling strategy this means that the excess is
+    /// larger than `delta/2`, otherwise the excess must be positive.
+    fn is_large(&self, u: usize) -> bool {
+        let excess = self.nodes[u].excess;
+        if self.strategy == Strategy::ExcessScaling {
+            excess + excess > self.delta
+        } else {
+            excess > F::zero()
+        }
+    }
+
+    /// Add `u` to the list of active nodes if it may be selected.
+    fn activate(&mut self, u: usize) {
+        if !self.nodes[u].queued && self.in_phase(self.nodes[u].height) && self.is_large(u) {
+            self.insert(u);
+        }
+    }
+
+    /// Insert `u` into the list of active nodes.
+    fn insert(&mut self, u: usize) {
+        debug_assert!(!self.nodes[u].queued);
+        let h = self.nodes[u].height;
+        self.nodes[u].queued = true;
+        match self.strategy {
+            Strategy::Fifo => self.active.push_back(u),
+            Strategy::HighestLabel | Strategy::ExcessScaling => {
+                self.nodes[u].next_act = self.buckets[h].first_act;
+                self.buckets[h].first_act = u;
+                self.largest_act = self.largest_act.max(h);
+                self.lowest_act = self.lowest_act.min(h);
+            }
+        }
+    }
+
+    /// Remove and return the first active node of height `h`.
+    fn pop_bucket(&mut self, h: usize) -> usize {
+        let u = self.buckets[h].first_act;
+        self.buckets[h].first_act = self.nodes[u].next_act;
+        u
+    }
+
+    /// Select the next active node to be discharged.
+    ///
+    /// Returns `None` if there is no active node left in the current
+    /// phase.
+    fn select(&mut self) -> Option<usize> {
+        let n = self.g.num_nodes();
+        let (lo, hi) = if self.phase2 { (n + 1, n + n) } else { (1, n) };
         loop {
-            while let Some(v) = self.queue.pop_front() {
-                let h = self.nodes[v].height + 1;
-                for &(e, u) in &self.edges[v] {
-                    let udata = &mut self.nodes[u];
-                    if udata.height > h && self.flow[e] > F::zero() {
-                        udata.height = h;
-                        self.queue.push_back(u);
-                        // add node to appropriate bucket
-                        if udata.excess > F::zero() {
-                            // u is active
-                            udata.next_act = self.buckets[h].first_act;
-                            self.buckets[h].first_act = u;
+            let u = match self.strategy {
+                Strategy::Fifo => self.active.pop_front(),
+                Strategy::HighestLabel => {
+                    let top = self.largest_act.min(hi - 1);
+                    match (lo..top + 1).rev().find(|&h| self.buckets[h].has_active()) {
+                        Some(h) => {
                             self.largest_act = h;
-                            debug_assert!(from_src || self.largest_act < n);
-                        } else {
-                            // u is inactive
-                            self.buckets[h].num_inact += 1;
+                            Some(self.pop_bucket(h))
+                        }
+                        None => {
+                            self.largest_act = 0;
+                            None
                         }
                     }
                 }
+                Strategy::ExcessScaling => {
+                    match (self.lowest_act.max(lo)..hi).find(|&h| self.buckets[h].has_active()) {
+                        Some(h) => {
+                            self.lowest_act = h;
+                            Some(self.pop_bucket(h))
+                        }
+                        None => {
+                            self.lowest_act = n + n;
+                            if self.delta <= F::one() {
+                                None
+                            } else {
+                                // no node with large excess, start the next scaling phase
+                                self.delta /= F::one() + F::one();
+                                for u in 0..n {
+                                    self.activate(u);
+                                }
+                                continue;
+                            }
+                        }
+                    }
+                }
+            };
+
+            let u = u?;
+            // Skip nodes that have been removed from the list by the gap heuristic.
+            if !self.nodes[u].queued {
+                continue;
             }
-            // possibly start again from the source to find the perfect
-            // labels for phase II
-            if self.largest_act >= n || !from_src {
-                break;
+            self.nodes[u].queued = false;
+            if self.in_phase(self.nodes[u].height) && self.is_large(u) {
+                return Some(u);
             }
-            self.largest_act = n;
-            self.queue.push_back(src);
         }
     }
 
+    /// Push `df` units of flow from `u` to `v` along arc `e`.
+    fn push(&mut self, e: usize, u: usize, v: usize, df: F) {
+        self.flow[e] += df;
+        self.flow[e ^ 1] -= df;
+        self.nodes[u].excess -= df;
+        self.nodes[v].excess += df;
+        self.activate(v);
+    }
+
     /// Discharges node `u`.
     ///
     /// This function does a sequence of push and relabel operations for an
@@ -431,8 +778,15 @@ where
     /// In phase I, the function may also with `u` having nonzero excess if the
     /// height of u is at least `n`. In this case `u` gets disconnected from the
     /// sink and will not be considered again until phase II.
+    ///
+    /// For the excess scaling strategy `discharge_scaling` is used instead.
     #[allow(clippy::many_single_char_names)]
     fn discharge(&mut self, u: usize) {
+        if self.strategy == Strategy::ExcessScaling {
+            self.discharge_scaling(u);
+            return;
+        }
+
         let n = self.g.num_nodes();
 
         loop {
@@ -440,46 +794,23 @@ where
             // If `u` gets relabelled, it gets height `n_neighbor + 1`.
             let mut h_neighbor = 2 * n;
             let h_u = self.nodes[u].height;
-            let edges = &self.edges[u];
 
             // Start at current edge (if any) or restart at beginning.
             let first_iter = self.nodes[u].iter;
-            let mut cur = first_iter;
 
-            loop {
-                let (e, v) = edges[cur];
+            for cur in first_iter..self.edges[u].len() {
+                let (e, v) = self.edges[u][cur];
                 // skip non-admissible edges
                 let f = e ^ 1;
                 if !self.flow[f].is_zero() {
                     if h_u == self.nodes[v].height + 1 {
                         // Push along edge e
                         let df = min(self.nodes[u].excess, self.flow[f]);
-
-                        debug_assert_eq!(h_u, self.nodes[v].height + 1);
                         debug_assert!(df > F::zero());
-
-                        if self.nodes[v].excess.is_zero() {
-                            // sink node becomes active
-                            let h = self.nodes[v].height;
-                            self.nodes[v].next_act = self.buckets[h].first_act;
-                            self.buckets[h].first_act = v;
-                            debug_assert!(self.buckets[h].num_inact > 0, "height:{} n:{}", h, n);
-                            self.buckets[h].num_inact -= 1;
-                        }
-
-                        self.flow[e] += df;
-                        self.flow[f] -= df;
-                        self.nodes[u].excess -= df;
-                        self.nodes[v].excess += df;
+                        self.push(e, u, v, df);
 
                         // check if node is fully discharged
                         if self.nodes[u].excess.is_zero() {
-                            // node is inactive now
-                            self.buckets[h_u].num_inact += 1;
-                            self.largest_act = (0..h_u + 1)
-                                .rev()
-                                .find(|&h| self.buckets[h].first_act != usize::max_value())
-                                .unwrap_or(0);
                             // save current edge
                             self.nodes[u].iter = cur;
                             return;
@@ -488,21 +819,11 @@ where
                         h_neighbor = h_neighbor.min(self.nodes[v].height);
                     }
                 }
-
-                // edge is full, go to next
-                cur += 1;
-                if cur == edges.len() {
-                    break;
-                }
             }
 
             // Update new height for neighbors before `first_edge`.
             self.nodes[u].iter = 0;
-            for &(e, v) in &edges[..first_iter] {
-                if !self.flow[e ^ 1].is_zero() {
-                    h_neighbor = h_neighbor.min(self.nodes[v].height);
-                }
-            }
+            h_neighbor = self.min_neighbor_height(u, first_iter, h_neighbor);
 
             // we ran out of admissible edges but node still has positive excess, relabel node
             if !self.relabel(u, h_neighbor + 1) {
@@ -512,6 +833,66 @@ where
         }
     }
 
+    /// Discharges node `u` for the excess scaling strategy.
+    ///
+    /// The node `u` has the smallest height of all nodes with large
+    /// excess. Flow is pushed from `u` until its excess is not large
+    /// anymore or some neighbor cannot receive more flow without
+    /// exceeding `delta`. In the latter case the neighbor has large
+    /// excess now (and a smaller height), so `u` is put back to the
+    /// list. If there is no admissible edge, `u` is relabelled.
+    fn discharge_scaling(&mut self, u: usize) {
+        let n = self.g.num_nodes();
+        let mut h_neighbor = 2 * n;
+        let h_u = self.nodes[u].height;
+        let first_iter = self.nodes[u].iter;
+
+        for cur in first_iter..self.edges[u].len() {
+            let (e, v) = self.edges[u][cur];
+            let f = e ^ 1;
+            if !self.flow[f].is_zero() {
+                if h_u == self.nodes[v].height + 1 {
+                    let mut df = min(self.nodes[u].excess, self.flow[f]);
+                    if self.in_phase(self.nodes[v].height) {
+                        df = min(df, self.delta - self.nodes[v].excess);
+                    }
+                    self.push(e, u, v, df);
+
+                    if !self.is_large(u) {
+                        // the remaining excess is handled in a later scaling phase
+                        self.nodes[u].iter = cur;
+                        return;
+                    }
+                    if !self.flow[f].is_zero() {
+                        // the neighbor is full, continue with the neighbor
+                        self.nodes[u].iter = cur;
+                        self.insert(u);
+                        return;
+                    }
+                } else {
+                    h_neighbor = h_neighbor.min(self.nodes[v].height);
+                }
+            }
+        }
+
+        self.nodes[u].iter = 0;
+        h_neighbor = self.min_neighbor_height(u, first_iter, h_neighbor);
+        if self.relabel(u, h_neighbor + 1) {
+            self.insert(u);
+        }
+    }
+
+    /// Return the minimal height of the residual neighbors of `u` along the first `k` edges.
+    ///
+    /// The initial value of the minimum is `h`.
+    fn min_neighbor_height(&self, u: usize, k: usize, h: usize) -> usize {
+        self.edges[u][..k]
+            .iter()
+            .filter(|&&(e, _)| !self.flow[e ^ 1].is_zero())
+            .map(|&(_, v)| self.nodes[v].height)
+            .fold(h, usize::min)
+    }
+
     /// The relabel operation.
     ///
     /// Relabel `u` to height `h_new`.
@@ -548,24 +929,21 @@ where
 
         debug_assert!(h_new > h_old);
 
+        self.buckets[h_old].count -= 1;
+        let mut h_new = h_new;
+
         // *** The GAP heuristic ***
 
         // Check if we are still in phase I.
         if h_old < n {
-            let mut h_new = h_new;
-
             // Test whether the node's old bucket is empty now.
             // This would create a gap.
-            if self.buckets[h_old].is_empty() {
+            if self.buckets[h_old].count == 0 {
                 // It is empty now, so u is disconnected from the sink.
                 // We remove all nodes with higher label from all buckets.
-                for h in h_old + 1..n {
-                    if self.buckets[h].is_empty() {
-                        // all higher buckets are empty, too
-                        break;
-                    }
-                    self.buckets[h].first_act = usize::max_value();
-                    self.buckets[h].num_inact = 0;
+                for b in &mut self.buckets[h_old + 1..n] {
+                    b.first_act = usize::max_value();
+                    b.count = 0;
                 }
 
                 // Relabel all nodes to n+1
@@ -578,40 +956,76 @@ where
                 // at most once (when it is relabelled and creates a gap), the
                 // running time is at most O(n^2) anyway.
                 for node in &mut self.nodes {
-                    if h_old < node.height {
+                    if h_old < node.height && node.height < n {
                         node.height = n + 1;
+                        node.queued = false;
+                        self.buckets[n + 1].count += 1;
                     }
                 }
 
                 // The node has now label n + 1.
                 h_new = n + 1;
             }
+        }
 
-            // This node has now a too large label for phase I.
-            // There is no need to discharge the node again. Instead we look for
-            // a new active node.
-            if h_new >= n {
-                debug_assert_eq!(self.largest_act, h_old);
+        self.nodes[u].height = h_new;
+        if h_new < n + n {
+            self.buckets[h_new].count += 1;
+        }
 
-                self.nodes[u].height = h_new;
+        // In phase I a node with a too large label need not be
+        // discharged again until phase II.
+        self.in_phase(h_new)
+    }
+}
 
-                // Find largest remaining bucket with an active node.
-                //
-                // Phase I ends once this reaches 0.
-                self.largest_act = (0..h_old + 1)
-                    .rev()
-                    .find(|&h| self.buckets[h].first_act != usize::max_value())
-                    .unwrap_or(0);
-
-                // The current node has been relabelled to n+1, so no further discharge is necessary.
-                return false;
-            }
-        }
+impl<'a, G, F> MaxFlow<'a> for PushRelabel<'a, G, F>
+where
+    G: IndexDigraph,
+    F: 'a + NumAssign + Ord + Copy,
+{
+    type Graph = G;
 
-        // The node remains active, is must have the largest height now.
-        self.nodes[u].height = h_new;
-        self.largest_act = h_new;
-        true
+    type Flow = F;
+
+    type Solver<'b, H>
+        = PushRelabel<'b, H, F>
+    where
+        H: 'b + IndexDigraph,
+        F: 'b;
+
+    fn new(g: &'a G) -> Self {
+        PushRelabel::new(g)
+    }
+
+    fn as_graph(&self) -> &'a G {
+        PushRelabel::as_graph(self)
+    }
+
+    fn value(&self) -> F {
+        PushRelabel::value(self)
+    }
+
+    fn flow(&self, e: G::Edge<'_>) -> F {
+        PushRelabel::flow(self, e)
+    }
+
+    fn flow_iter<'b>(&'b self) -> impl Iterator<Item = (G::Edge<'a>, F)> + 'b
+    where
+        'a: 'b,
+    {
+        PushRelabel::flow_iter(self)
+    }
+
+    fn solve<Us>(&mut self, src: G::Node<'_>, snk: G::Node<'_>, upper: Us)
+    where
+        Us: Fn(G::Edge<'a>) -> F,
+    {
+        PushRelabel::solve(self, src, snk, upper)
+    }
+
+    fn mincut(&self) -> Vec<G::Node<'a>> {
+        PushRelabel::mincut(self)
     }
 }
 
@@ -684,7 +1098,5 @@ where
     F: 'a + NumAssign + Ord + Copy,
     Us: Fn(G::Edge<'_>) -> F,
 {
-    let mut maxflow = PushRelabel::new(g);
-    maxflow.solve(src, snk, upper);
-    (maxflow.value(), maxflow.flow_iter().collect(), maxflow.mincut())
+    solve_with::<PushRelabel<_, _>>(g, src, snk, upper)
 }
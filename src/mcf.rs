@@ -16,10 +16,49 @@
  */
 
 //! Minimum Cost Flow algorithms.
+//!
+//! All solvers implement the [`MinCostFlow`] trait, so code can be
+//! written generically over the algorithm:
+//!
+//! ```
+//! use rs_graph::{Buildable, Builder, Net};
+//! use rs_graph::mcf::{CostScaling, MinCostFlow, NetworkSimplex, SolutionState};
+//! use rs_graph::traits::*;
+//!
+//! fn solve<'a, S>(g: &'a Net) -> isize
+//! where
+//!     S: MinCostFlow<&'a Net, isize>,
+//! {
+//!     let mut mcf = S::new(g);
+//!     mcf.set_balances(|u| [2, 0, -2][g.node_id(u)]);
+//!     mcf.set_uppers(|_| 1);
+//!     mcf.set_costs(|e| [1, 1, 3][g.edge_id(e)]);
+//!     assert_eq!(mcf.solve(), SolutionState::Optimal);
+//!     mcf.value()
+//! }
+//!
+//! let g = Net::new_with(|b| {
+//!     let nodes = b.add_nodes(3);
+//!     b.add_edge(nodes[0], nodes[1]);
+//!     b.add_edge(nodes[1], nodes[2]);
+//!     b.add_edge(nodes[0], nodes[2]);
+//! });
+//!
+//! assert_eq!(solve::<NetworkSimplex<_, _>>(&g), 5);
+//! assert_eq!(solve::<CostScaling<_, _>>(&g), 5);
+//! ```
+
+use crate::traits::{GraphType, IndexDigraph};
 
 pub mod simplex;
 pub use simplex::{network_simplex, NetworkSimplex};
 
+pub mod costscaling;
+pub use costscaling::{cost_scaling, CostScaling};
+
+pub mod capacityscaling;
+pub use capacityscaling::{capacity_scaling, CapacityScaling};
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum SolutionState {
     /// Unknown state, the problem has not been solved, yet
@@ -27,7 +66,117 @@ pub enum SolutionState {
     /// The problem has been solved to optimality
     Optimal,
     /// The problem is infeasible
+    ///
+    /// A certificate can be obtained from the solver, e.g.
+    /// [`NetworkSimplex::infeasibility`].
     Infeasible,
     /// The problem is unbounded
+    ///
+    /// A certificate can be obtained from the solver, e.g.
+    /// [`NetworkSimplex::unbounded_cycle`].
     Unbounded,
 }
+
+/// A certificate for the infeasibility of a flow problem.
+///
+/// This is used for min-cost-flow problems as well as for the
+/// max-flow problems with lower bounds in
+/// [`maxflow::lowerbounds`](crate::maxflow::lowerbounds).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Infeasibility<N, E> {
+    /// An edge whose lower bound is larger than its upper bound.
+    Bounds(E),
+    /// A node set whose net supply cannot leave the set.
+    ///
+    /// The set $S \subseteq V$ satisfies
+    /// $b(S) > u(\delta^+(S)) - l(\delta^-(S))$, i.e. the net supply
+    /// of $S$ exceeds the capacity of the edges leaving $S$ minus the
+    /// lower bounds of the edges entering $S$ (Hoffman's condition).
+    /// For problems without supplies this means
+    /// $l(\delta^-(S)) > u(\delta^+(S))$, i.e. the flow that must
+    /// enter $S$ exceeds the capacity of the edges leaving $S$.
+    Cut(Vec<N>),
+}
+
+/// A min-cost-flow solver.
+///
+/// The solver owns (a reference to) the graph and stores the balances,
+/// bounds and costs. The values are initially zero. Any change of the
+/// problem data resets the solution state to
+/// [`SolutionState::Unknown`].
+pub trait MinCostFlow<G, F>
+where
+    G: IndexDigraph,
+{
+    /// Create a new solver for the graph `g`.
+    fn new(g: G) -> Self
+    where
+        Self: Sized;
+
+    /// Return the underlying graph.
+    fn as_graph(&self) -> &G;
+
+    /// Return the balance of a node.
+    fn balance(&self, u: <G as GraphType>::Node<'_>) -> F;
+
+    /// Set the balance of a node.
+    fn set_balance(&mut self, u: <G as GraphType>::Node<'_>, balance: F);
+
+    /// Set the balances of all nodes.
+    fn set_balances<'a, Bs>(&'a mut self, balance: Bs)
+    where
+        Bs: Fn(<G as GraphType>::Node<'a>) -> F;
+
+    /// Return the lower bound of an edge.
+    fn lower(&self, e: <G as GraphType>::Edge<'_>) -> F;
+
+    /// Set the lower bound of an edge.
+    fn set_lower(&mut self, e: <G as GraphType>::Edge<'_>, lb: F);
+
+    /// Set the lower bounds of all edges.
+    fn set_lowers<'a, Ls>(&'a mut self, lower: Ls)
+    where
+        Ls: Fn(<G as GraphType>::Edge<'a>) -> F;
+
+    /// Return the upper bound of an edge.
+    fn upper(&self, e: <G as GraphType>::Edge<'_>) -> F;
+
+    /// Set the upper bound of an edge.
+    fn set_upper(&mut self, e: <G as GraphType>::Edge<'_>, ub: F);
+
+    /// Set the upper bounds of all edges.
+    fn set_uppers<'a, Us>(&'a mut self, upper: Us)
+    where
+        Us: Fn(<G as GraphType>::Edge<'a>) -> F;
+
+    /// Return the cost of an edge.
+    fn cost(&self, e: <G as GraphType>::Edge<'_>) -> F;
+
+    /// Set the cost of an edge.
+    fn set_cost(&mut self, e: <G as GraphType>::Edge<'_>, cost: F);
+
+    /// Set the costs of all edges.
+    fn set_costs<'a, Cs>(&'a mut self, cost: Cs)
+    where
+        Cs: Fn(<G as GraphType>::Edge<'a>) -> F;
+
+    /// Solve the min-cost-flow problem.
+    fn solve(&mut self) -> SolutionState;
+
+    /// Return the solution state of the latest computation.
+    fn solution_state(&self) -> SolutionState;
+
+    /// Return the flow of an edge.
+    fn flow(&self, e: <G as GraphType>::Edge<'_>) -> F;
+
+    /// Return the cost of the latest computed flow.
+    fn value(&self) -> F;
+
+    /// Return the potential of a node.
+    ///
+    /// If the latest computation was successful, these are optimal
+    /// dual values, i.e. each edge with positive reduced cost
+    /// $c(uv) + \pi(u) - \pi(v)$ is at its lower bound and each edge
+    /// with negative reduced cost is at its upper bound.
+    fn potential(&self, u: <G as GraphType>::Node<'_>) -> F;
+}
@@ -0,0 +1,758 @@
+/*
+ * Copyright (c) 2023 Frank Fischer <frank-fischer@shadow-soft.de>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but
+ * WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see  <http://www.gnu.org/licenses/>
+ */
+
+//! A successive shortest path algorithm with capacity scaling.
+//!
+//! The algorithm maintains a pseudoflow satisfying all bounds and node
+//! potentials such that all residual arcs have non-negative reduced
+//! costs. In each iteration flow is sent from a node with excess to a
+//! node with deficit along a shortest path w.r.t. the reduced costs,
+//! which is computed by [`dijkstra`](crate::shortestpath::dijkstra) on
+//! the residual [`Network`]. Capacity scaling restricts the
+//! augmentations to paths of residual capacity at least $\Delta$, where
+//! $\Delta$ is decreased geometrically.
+//!
+//! The cost of the pseudoflow is monotonically increasing in the
+//! augmentations of each phase. The computation can be interrupted
+//! after each augmentation using
+//! [`CapacityScaling::solve_with_callback`].
+//!
+//! The algorithm requires integral bounds and balances.
+//!
+//! # Example
+//!
+//! ```
+//! use rs_graph::{Buildable, Builder, Net};
+//! use rs_graph::mcf::{CapacityScaling, SolutionState};
+//! use rs_graph::traits::*;
+//!
+//! let mut costs = vec![];
+//! let g = Net::new_with(|b| {
+//!     let nodes = b.add_nodes(4);
+//!     for &(u, v, c) in &[(0, 1, 1), (0, 2, 4), (1, 2, 1), (1, 3, 5), (2, 3, 1)] {
+//!         b.add_edge(nodes[u], nodes[v]);
+//!         costs.push(c);
+//!     }
+//! });
+//!
+//! let mut cs = CapacityScaling::new(&g);
+//! cs.set_balances(|u| [4, 0, 0, -4][g.node_id(u)]);
+//! cs.set_uppers(|_| 3);
+//! cs.set_costs(|e| costs[g.edge_id(e)]);
+//!
+//! assert_eq!(cs.solve(), SolutionState::Optimal);
+//! assert_eq!(cs.value(), 17);
+//! ```
+
+use super::{MinCostFlow, SolutionState};
+use crate::adapters::Network;
+use crate::adjacencies::{Adjacencies, OutEdges};
+use crate::collections::{BinHeap, NodeVecMap};
+use crate::shortestpath::dijkstra;
+use crate::traits::{FiniteGraph, GraphType, IndexDigraph, IndexGraph};
+use num_traits::{Bounded, FromPrimitive, NumAssign, NumCast, Signed};
+
+use std::cmp::{max, min};
+
+/// A successive shortest path algorithm with capacity scaling for
+/// min-cost-flow problems.
+pub struct CapacityScaling<G, F> {
+    graph: G,
+
+    balances: Vec<F>,
+    lower: Vec<F>,
+    upper: Vec<F>,
+    costs: Vec<F>,
+
+    /// The source and sink node ids of each edge.
+    ends: Vec<(usize, usize)>,
+    /// The capacity of each edge (upper bound minus lower bound).
+    caps: Vec<F>,
+    /// The flow on each edge (minus the lower bound).
+    flows: Vec<F>,
+    /// The excess of each node.
+    excess: Vec<F>,
+    /// The node potentials.
+    potentials: Vec<F>,
+
+    solution_state: SolutionState,
+    naugment: usize,
+
+    /// The factor by which $\Delta$ is divided after each phase.
+    ///
+    /// The default is `4`.
+    pub factor: usize,
+    /// The infinite flow value.
+    ///
+    /// Capacities greater than or equal to this are considered
+    /// unbounded. The default is `F::max_value()`.
+    pub infinite: F,
+}
+
+impl<G, F> CapacityScaling<G, F>
+where
+    G: IndexDigraph,
+    F: Bounded + NumCast + NumAssign + Ord + Copy + FromPrimitive + Signed,
+{
+    pub fn new(g: G) -> Self {
+        let n = FiniteGraph::num_nodes(&g);
+        let m = FiniteGraph::num_edges(&g);
+        let ends = g.edges().map(|e| (g.node_id(g.src(e)), g.node_id(g.snk(e)))).collect();
+        CapacityScaling {
+            graph: g,
+
+            balances: vec![F::zero(); n],
+            lower: vec![F::zero(); m],
+            upper: vec![F::zero(); m],
+            costs: vec![F::zero(); m],
+
+            ends,
+            caps: vec![F::zero(); m],
+            flows: vec![F::zero(); m],
+            excess: vec![F::zero(); n],
+            potentials: vec![F::zero(); n],
+
+            solution_state: SolutionState::Unknown,
+            naugment: 0,
+
+            factor: 4,
+            infinite: F::max_value(),
+        }
+    }
+
+    pub fn as_graph(&self) -> &G {
+        &self.graph
+    }
+
+    pub fn balance(&self, u: <G as GraphType>::Node<'_>) -> F {
+        self.balances[self.graph.node_id(u)]
+    }
+
+    pub fn set_balance(&mut self, u: <G as GraphType>::Node<'_>, balance: F) {
+        self.solution_state = SolutionState::Unknown;
+        self.balances[self.graph.node_id(u)] = balance;
+    }
+
+    pub fn set_balances<'a, Bs>(&'a mut self, balance: Bs)
+    where
+        Bs: Fn(<G as GraphType>::Node<'a>) -> F,
+    {
+        self.solution_state = SolutionState::Unknown;
+        for u in self.graph.nodes() {
+            self.balances[self.graph.node_id(u)] = (balance)(u);
+        }
+    }
+
+    pub fn lower(&self, e: <G as GraphType>::Edge<'_>) -> F {
+        self.lower[self.graph.edge_id(e)]
+    }
+
+    pub fn set_lower(&mut self, e: <G as GraphType>::Edge<'_>, lb: F) {
+        self.solution_state = SolutionState::Unknown;
+        self.lower[self.graph.edge_id(e)] = lb;
+    }
+
+    pub fn set_lowers<'a, Ls>(&'a mut self, lower: Ls)
+    where
+        Ls: Fn(<G as GraphType>::Edge<'a>) -> F,
+    {
+        self.solution_state = SolutionState::Unknown;
+        for e in self.graph.edges() {
+            self.lower[self.graph.edge_id(e)] = (lower)(e);
+        }
+    }
+
+    pub fn upper(&self, e: <G as GraphType>::Edge<'_>) -> F {
+        self.upper[self.graph.edge_id(e)]
+    }
+
+    pub fn set_upper(&mut self, e: <G as GraphType>::Edge<'_>, ub: F) {
+        self.solution_state = SolutionState::Unknown;
+        self.upper[self.graph.edge_id(e)] = ub;
+    }
+
+    pub fn set_uppers<'a, Us>(&'a mut self, upper: Us)
+    where
+        Us: Fn(<G as GraphType>::Edge<'a>) -> F,
+    {
+        self.solution_state = SolutionState::Unknown;
+        for e in self.graph.edges() {
+            self.upper[self.graph.edge_id(e)] = (upper)(e);
+        }
+    }
+
+    pub fn cost(&self, e: <G as GraphType>::Edge<'_>) -> F {
+        self.costs[self.graph.edge_id(e)]
+    }
+
+    pub fn set_cost(&mut self, e: <G as GraphType>::Edge<'_>, cost: F) {
+        self.solution_state = SolutionState::Unknown;
+        self.costs[self.graph.edge_id(e)] = cost;
+    }
+
+    pub fn set_costs<'a, Cs>(&'a mut self, cost: Cs)
+    where
+        Cs: Fn(<G as GraphType>::Edge<'a>) -> F,
+    {
+        self.solution_state = SolutionState::Unknown;
+        for e in self.graph.edges() {
+            self.costs[self.graph.edge_id(e)] = (cost)(e);
+        }
+    }
+
+    /// Return the value of the latest computed flow value.
+    pub fn value(&self) -> F {
+        let mut v = F::zero();
+        for e in FiniteGraph::edges(&self.graph) {
+            v += self.flow(e) * self.costs[self.graph.edge_id(e)];
+        }
+        v
+    }
+
+    /// The flow of an Edge.
+    ///
+    /// If the computation has been interrupted, this is the flow of
+    /// the current pseudoflow.
+    pub fn flow(&self, a: <G as GraphType>::Edge<'_>) -> F {
+        let eid = self.graph.edge_id(a);
+        self.flows[eid] + self.lower[eid]
+    }
+
+    /// Return the potential of a node.
+    ///
+    /// If the latest computation was successful, these are optimal
+    /// dual values, i.e. each edge with positive reduced cost
+    /// $c(uv) + \pi(u) - \pi(v)$ is at its lower bound and each edge
+    /// with negative reduced cost is at its upper bound.
+    pub fn potential(&self, u: <G as GraphType>::Node<'_>) -> F {
+        self.potentials[self.graph.node_id(u)]
+    }
+
+    /// Return the solution state of the latest computation.
+    pub fn solution_state(&self) -> SolutionState {
+        self.solution_state
+    }
+
+    /// Return the number of augmentations of the latest computation.
+    pub fn num_iterations(&self) -> usize {
+        self.naugment
+    }
+
+    /// Solve the min-cost-flow problem.
+    pub fn solve(&mut self) -> SolutionState {
+        self.solve_with_callback(|_| true)
+    }
+
+    /// Solve the min-cost-flow problem with an interruption callback.
+    ///
+    /// The callback is called after each augmentation. If it returns
+    /// `false` the computation is interrupted, the solution state is
+    /// `SolutionState::Unknown` and the current pseudoflow can be
+    /// retrieved using [`CapacityScaling::flow`].
+    pub fn solve_with_callback<C>(&mut self, mut callback: C) -> SolutionState
+    where
+        C: FnMut(&Self) -> bool,
+    {
+        self.naugment = 0;
+        self.solution_state = self.run(&mut callback);
+        self.solution_state
+    }
+
+    fn run<C>(&mut self, callback: &mut C) -> SolutionState
+    where
+        C: FnMut(&Self) -> bool,
+    {
+        let m = self.graph.num_edges();
+
+        // The initial flow is zero, all lower bounds are moved to the balances.
+        self.excess.copy_from_slice(&self.balances);
+        self.potentials.fill(F::zero());
+        for eid in 0..m {
+            self.caps[eid] = self.upper[eid] - self.lower[eid];
+            self.flows[eid] = F::zero();
+            if self.caps[eid] < F::zero() {
+                return SolutionState::Infeasible;
+            }
+            let (uid, vid) = self.ends[eid];
+            self.excess[uid] -= self.lower[eid];
+            self.excess[vid] += self.lower[eid];
+        }
+
+        let mut total_supply = F::zero();
+        let mut total_balance = F::zero();
+        let mut maxcap = F::zero();
+        for &b in &self.excess {
+            total_balance += b;
+            if b > F::zero() {
+                total_supply += b;
+                maxcap = max(maxcap, b);
+            }
+        }
+        if !total_balance.is_zero() {
+            return SolutionState::Infeasible;
+        }
+
+        // Replace infinite capacities by a sufficiently large finite
+        // value. If the problem is bounded, there is an optimal
+        // solution with flow smaller than this value on all
+        // uncapacitated edges, so their reduced costs are non-negative.
+        let mut bound = total_supply + F::one();
+        for eid in 0..m {
+            if self.upper[eid] < self.infinite {
+                bound += self.caps[eid];
+                maxcap = max(maxcap, self.caps[eid]);
+            }
+        }
+        for eid in 0..m {
+            if self.upper[eid] >= self.infinite {
+                self.caps[eid] = bound;
+            }
+        }
+
+        let factor = F::from(self.factor.max(2)).unwrap();
+        let mut delta = F::one();
+        while delta * factor <= maxcap {
+            delta *= factor;
+        }
+
+        loop {
+            self.saturate(delta);
+            if !self.augment(delta, callback) {
+                return SolutionState::Unknown;
+            }
+            if delta == F::one() {
+                break;
+            }
+            delta /= factor;
+        }
+
+        if self.excess.iter().any(|&b| b > F::zero()) {
+            return SolutionState::Infeasible;
+        }
+
+        if self.has_negative_cycle() {
+            return SolutionState::Unbounded;
+        }
+
+        SolutionState::Optimal
+    }
+
+    /// Saturate all arcs with residual capacity at least `delta` and
+    /// negative reduced cost.
+    fn saturate(&mut self, delta: F) {
+        for eid in 0..self.graph.num_edges() {
+            let (uid, vid) = self.ends[eid];
+            let rc = self.costs[eid] + self.potentials[uid] - self.potentials[vid];
+            let df = if rc < F::zero() && self.caps[eid] - self.flows[eid] >= delta {
+                self.caps[eid] - self.flows[eid]
+            } else if rc > F::zero() && self.flows[eid] >= delta {
+                -self.flows[eid]
+            } else {
+                continue;
+            };
+            self.flows[eid] += df;
+            self.excess[uid] -= df;
+            self.excess[vid] += df;
+        }
+    }
+
+    /// Run all augmentations of a phase.
+    ///
+    /// Returns `false` if the computation has been interrupted.
+    fn augment<C>(&mut self, delta: F, callback: &mut C) -> bool
+    where
+        C: FnMut(&Self) -> bool,
+    {
+        let n = self.graph.num_nodes();
+        let mut dist = vec![F::zero(); n];
+        let mut pred = vec![None; n];
+
+        for sid in 0..n {
+            while self.excess[sid] >= delta {
+                let net = Network::new(&self.graph);
+
+                // Compute a shortest path to a node with deficit
+                // w.r.t. the reduced costs on the residual network.
+                let adj = OutEdges(&net).filter(|&(e, _)| self.residual(net.edge_id(e)) >= delta);
+                let weights = |e| self.reduced_cost(net.edge_id(e));
+
+                let mut settled = vec![sid];
+                let mut target = None;
+                dist[sid] = F::zero();
+                pred[sid] = None;
+                for (v, e, d) in dijkstra::start_with_data(
+                    adj,
+                    net.id2node(sid),
+                    weights,
+                    (NodeVecMap::new(&net), BinHeap::<_, _, usize>::default()),
+                ) {
+                    let vid = net.node_id(v);
+                    dist[vid] = d;
+                    pred[vid] = Some(net.edge_id(e));
+                    settled.push(vid);
+                    if self.excess[vid] <= -delta {
+                        target = Some(vid);
+                        break;
+                    }
+                }
+
+                let tid = if let Some(tid) = target {
+                    tid
+                } else {
+                    // there is no augmenting path with capacity `delta` from this node
+                    break;
+                };
+
+                // update the potentials
+                let dt = dist[tid];
+                for &vid in &settled {
+                    self.potentials[vid] -= dt - dist[vid];
+                }
+
+                // find the maximal amount of flow (a multiple of delta)
+                let mut df = min(self.excess[sid], -self.excess[tid]);
+                let mut vid = tid;
+                while let Some(a) = pred[vid] {
+                    df = min(df, self.residual(a));
+                    vid = self.arc_src(a);
+                }
+                df = df / delta * delta;
+
+                // augment
+                let mut vid = tid;
+                while let Some(a) = pred[vid] {
+                    if a & 1 == 0 {
+                        self.flows[a >> 1] += df;
+                    } else {
+                        self.flows[a >> 1] -= df;
+                    }
+                    vid = self.arc_src(a);
+                }
+                self.excess[sid] -= df;
+                self.excess[tid] += df;
+
+                self.naugment += 1;
+                if !callback(self) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// The residual capacity of an arc.
+    fn residual(&self, a: usize) -> F {
+        if a & 1 == 0 {
+            self.caps[a >> 1] - self.flows[a >> 1]
+        } else {
+            self.flows[a >> 1]
+        }
+    }
+
+    /// The reduced cost of an arc.
+    fn reduced_cost(&self, a: usize) -> F {
+        let (uid, vid) = self.ends[a >> 1];
+        let rc = self.costs[a >> 1] + self.potentials[uid] - self.potentials[vid];
+        if a & 1 == 0 {
+            rc
+        } else {
+            -rc
+        }
+    }
+
+    /// The source node id of an arc.
+    fn arc_src(&self, a: usize) -> usize {
+        let (uid, vid) = self.ends[a >> 1];
+        if a & 1 == 0 {
+            uid
+        } else {
+            vid
+        }
+    }
+
+    /// Check whether there is a negative cycle of uncapacitated edges.
+    fn has_negative_cycle(&self) -> bool {
+        let n = self.graph.num_nodes();
+        let edges = (0..self.graph.num_edges())
+            .filter(|&eid| self.upper[eid] >= self.infinite)
+            .map(|eid| (self.ends[eid].0, self.ends[eid].1, self.costs[eid]))
+            .collect::<Vec<_>>();
+
+        // Moore-Bellman-Ford with an artificial source connected to all nodes.
+        let mut dist = vec![F::zero(); n];
+        for _ in 0..n {
+            let mut changed = false;
+            for &(uid, vid, c) in &edges {
+                if dist[uid] + c < dist[vid] {
+                    dist[vid] = dist[uid] + c;
+                    changed = true;
+                }
+            }
+            if !changed {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl<G, F> MinCostFlow<G, F> for CapacityScaling<G, F>
+where
+    G: IndexDigraph,
+    F: Bounded + NumCast + NumAssign + Ord + Copy + FromPrimitive + Signed,
+{
+    fn new(g: G) -> Self {
+        CapacityScaling::new(g)
+    }
+
+    fn as_graph(&self) -> &G {
+        CapacityScaling::as_graph(self)
+    }
+
+    fn balance(&self, u: <G as GraphType>::Node<'_>) -> F {
+        CapacityScaling::balance(self, u)
+    }
+
+    fn set_balance(&mut self, u: <G as GraphType>::Node<'_>, balance: F) {
+        CapacityScaling::set_balance(self, u, balance)
+    }
+
+    fn set_balances<'a, Bs>(&'a mut self, balance: Bs)
+    where
+        Bs: Fn(<G as GraphType>::Node<'a>) -> F,
+    {
+        CapacityScaling::set_balances(self, balance)
+    }
+
+    fn lower(&self, e: <G as GraphType>::Edge<'_>) -> F {
+        CapacityScaling::lower(self, e)
+    }
+
+    fn set_lower(&mut self, e: <G as GraphType>::Edge<'_>, lb: F) {
+        CapacityScaling::set_lower(self, e, lb)
+    }
+
+    fn set_lowers<'a, Ls>(&'a mut self, lower: Ls)
+    where
+        Ls: Fn(<G as GraphType>::Edge<'a>) -> F,
+    {
+        CapacityScaling::set_lowers(self, lower)
+    }
+
+    fn upper(&self, e: <G as GraphType>::Edge<'_>) -> F {
+        CapacityScaling::upper(self, e)
+    }
+
+    fn set_upper(&mut self, e: <G as GraphType>::Edge<'_>, ub: F) {
+        CapacityScaling::set_upper(self, e, ub)
+    }
+
+    fn set_uppers<'a, Us>(&'a mut self, upper: Us)
+    where
+        Us: Fn(<G as GraphType>::Edge<'a>) -> F,
+    {
+        CapacityScaling::set_uppers(self, upper)
+    }
+
+    fn cost(&self, e: <G as GraphType>::Edge<'_>) -> F {
+        CapacityScaling::cost(self, e)
+    }
+
+    fn set_cost(&mut self, e: <G as GraphType>::Edge<'_>, cost: F) {
+        CapacityScaling::set_cost(self, e, cost)
+    }
+
+    fn set_costs<'a, Cs>(&'a mut self, cost: Cs)
+    where
+        Cs: Fn(<G as GraphType>::Edge<'a>) -> F,
+    {
+        CapacityScaling::set_costs(self, cost)
+    }
+
+    fn solve(&mut self) -> SolutionState {
+        CapacityScaling::solve(self)
+    }
+
+    fn solution_state(&self) -> SolutionState {
+        CapacityScaling::solution_state(self)
+    }
+
+    fn flow(&self, e: <G as GraphType>::Edge<'_>) -> F {
+        CapacityScaling::flow(self, e)
+    }
+
+    fn value(&self) -> F {
+        CapacityScaling::value(self)
+    }
+
+    fn potential(&self, u: <G as GraphType>::Node<'_>) -> F {
+        CapacityScaling::potential(self, u)
+    }
+}
+
+/// Solve a min-cost-flow problem with the capacity scaling algorithm.
+///
+/// The function returns the objective value and the optimal flow.
+#[allow(clippy::type_complexity)]
+pub fn capacity_scaling<G, F, Bs, Ls, Us, Cs>(
+    g: &G,
+    balances: Bs,
+    lower: Ls,
+    upper: Us,
+    costs: Cs,
+) -> Option<(F, Vec<(G::Edge<'_>, F)>)>
+where
+    G: IndexDigraph,
+    F: NumAssign + NumCast + FromPrimitive + Ord + Signed + Bounded + Copy,
+    Bs: Fn(G::Node<'_>) -> F,
+    Ls: Fn(G::Edge<'_>) -> F,
+    Us: Fn(G::Edge<'_>) -> F,
+    Cs: Fn(G::Edge<'_>) -> F,
+{
+    let mut cs = CapacityScaling::new(g);
+    cs.set_balances(balances);
+    cs.set_lowers(lower);
+    cs.set_uppers(upper);
+    cs.set_costs(costs);
+    if cs.solve() == SolutionState::Optimal {
+        Some((cs.value(), g.edges().map(|e| (e, cs.flow(e))).collect()))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CapacityScaling;
+    use crate::mcf::SolutionState;
+    use crate::traits::*;
+    use crate::{Buildable, Builder, Net};
+
+    fn network(n: usize, edges: &[(usize, usize, isize, isize, isize)]) -> Net {
+        Net::new_with(|b| {
+            let nodes = b.add_nodes(n);
+            for &(u, v, _, _, _) in edges {
+                b.add_edge(nodes[u], nodes[v]);
+            }
+        })
+    }
+
+    fn solve(n: usize, balances: &[isize], edges: &[(usize, usize, isize, isize, isize)]) -> (SolutionState, isize) {
+        let g = network(n, edges);
+        let mut cs = CapacityScaling::new(&g);
+        cs.set_balances(|u| balances[g.node_id(u)]);
+        cs.set_lowers(|e| edges[g.edge_id(e)].2);
+        cs.set_uppers(|e| edges[g.edge_id(e)].3);
+        cs.set_costs(|e| edges[g.edge_id(e)].4);
+        let state = cs.solve();
+        if state != SolutionState::Optimal {
+            return (state, 0);
+        }
+
+        let mut excess = balances.to_vec();
+        for e in g.edges() {
+            let (_, _, l, u, c) = edges[g.edge_id(e)];
+            let f = cs.flow(e);
+            assert!(l <= f && f <= u);
+            excess[g.node_id(g.src(e))] -= f;
+            excess[g.node_id(g.snk(e))] += f;
+            // complementary slackness
+            let rc = c + cs.potential(g.src(e)) - cs.potential(g.snk(e));
+            assert!(rc <= 0 || f == l);
+            assert!(rc >= 0 || f == u);
+        }
+        assert!(excess.iter().all(|&x| x == 0));
+        (state, cs.value())
+    }
+
+    #[test]
+    fn test_capacity_scaling() {
+        let edges = [
+            (0, 1, 0, 4, 2),
+            (0, 2, 0, 2, 2),
+            (1, 2, 0, 2, 1),
+            (1, 3, 0, 3, 3),
+            (2, 3, 0, 5, 1),
+            (2, 4, 1, 4, 2),
+            (3, 4, 0, 6, 1),
+        ];
+        assert_eq!(solve(5, &[6, 0, 0, 0, -6], &edges), (SolutionState::Optimal, 30));
+    }
+
+    #[test]
+    fn test_capacity_scaling_negative_costs() {
+        let inf = isize::MAX;
+        let edges = [
+            (0, 1, 0, inf, -3),
+            (1, 2, 0, 4, 2),
+            (2, 0, 0, inf, -1),
+            (1, 0, 0, inf, 5),
+        ];
+        assert_eq!(solve(3, &[0, 0, 0], &edges), (SolutionState::Optimal, -8));
+    }
+
+    #[test]
+    fn test_capacity_scaling_infeasible() {
+        let edges = [(0, 1, 0, 3, 1), (1, 2, 0, 10, 1), (0, 3, 0, 1, 1), (3, 2, 0, 10, 1)];
+        assert_eq!(solve(4, &[5, 0, -5, 0], &edges).0, SolutionState::Infeasible);
+        assert_eq!(solve(2, &[0, 0], &[(0, 1, 4, 2, 1)]).0, SolutionState::Infeasible);
+        assert_eq!(solve(2, &[1, 0], &[(0, 1, 0, 2, 1)]).0, SolutionState::Infeasible);
+    }
+
+    #[test]
+    fn test_capacity_scaling_unbounded() {
+        let inf = isize::MAX;
+        let edges = [
+            (0, 1, 0, inf, 1),
+            (1, 2, 0, inf, -3),
+            (2, 0, 0, inf, 1),
+            (0, 2, 0, 5, 1),
+        ];
+        assert_eq!(solve(3, &[0, 0, 0], &edges).0, SolutionState::Unbounded);
+    }
+
+    #[test]
+    fn test_capacity_scaling_interrupt() {
+        let edges = [
+            (0, 1, 0, 1, 1),
+            (0, 2, 0, 1, 2),
+            (0, 3, 0, 1, 3),
+            (1, 4, 0, 1, 0),
+            (2, 4, 0, 1, 0),
+            (3, 4, 0, 1, 0),
+        ];
+        let g = network(5, &edges);
+        let mut cs = CapacityScaling::new(&g);
+        cs.set_balances(|u| [3, 0, 0, 0, -3][g.node_id(u)]);
+        cs.set_uppers(|e| edges[g.edge_id(e)].3);
+        cs.set_costs(|e| edges[g.edge_id(e)].4);
+
+        // the cost of the pseudoflows increases with each augmentation
+        let mut values = vec![];
+        let state = cs.solve_with_callback(|cs| {
+            values.push(cs.value());
+            values.len() < 2
+        });
+        assert_eq!(state, SolutionState::Unknown);
+        assert_eq!(values, vec![1, 3]);
+        assert_eq!(cs.value(), 3);
+
+        assert_eq!(cs.solve(), SolutionState::Optimal);
+        assert_eq!(cs.value(), 6);
+    }
+}
@@ -0,0 +1,762 @@
+/*
+ * Copyright (c) 2023 Frank Fischer <frank-fischer@shadow-soft.de>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful, but
+ * WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see  <http://www.gnu.org/licenses/>
+ */
+
+//! A cost scaling push-relabel implementation.
+//!
+//! This is the cost scaling algorithm of Goldberg and Tarjan. The costs
+//! are multiplied by $n+1$ and the algorithm computes a sequence of
+//! $\varepsilon$-optimal flows for geometrically decreasing values of
+//! $\varepsilon$. Each refinement step is a push-relabel algorithm
+//! processing the active nodes in FIFO order. Once $\varepsilon = 1$ the
+//! flow is optimal for the original (integral) costs.
+//!
+//! The algorithm requires integral costs and bounds.
+//!
+//! # Example
+//!
+//! ```
+//! use rs_graph::{Buildable, Builder, Net};
+//! use rs_graph::mcf::{CostScaling, SolutionState};
+//! use rs_graph::traits::*;
+//!
+//! let mut costs = vec![];
+//! let g = Net::new_with(|b| {
+//!     let nodes = b.add_nodes(4);
+//!     for &(u, v, c) in &[(0, 1, 1), (0, 2, 4), (1, 2, 1), (1, 3, 5), (2, 3, 1)] {
+//!         b.add_edge(nodes[u], nodes[v]);
+//!         costs.push(c);
+//!     }
+//! });
+//!
+//! let mut cs = CostScaling::new(&g);
+//! cs.set_balances(|u| [4, 0, 0, -4][g.node_id(u)]);
+//! cs.set_uppers(|_| 3);
+//! cs.set_costs(|e| costs[g.edge_id(e)]);
+//!
+//! assert_eq!(cs.solve(), SolutionState::Optimal);
+//! assert_eq!(cs.value(), 17);
+//! ```
+
+use super::{MinCostFlow, SolutionState};
+use crate::maxflow::Dinic;
+use crate::traits::{FiniteGraph, GraphType, IndexDigraph, IndexGraph};
+use crate::{Buildable, Builder, Net};
+use num_traits::{Bounded, FromPrimitive, NumAssign, NumCast, Signed};
+
+use std::cmp::{max, min};
+use std::collections::VecDeque;
+
+/// A cost scaling push-relabel algorithm for min-cost-flow problems.
+pub struct CostScaling<G, F> {
+    graph: G,
+
+    balances: Vec<F>,
+    lower: Vec<F>,
+    upper: Vec<F>,
+    costs: Vec<F>,
+
+    /// The adjacent arcs of each node.
+    ///
+    /// The arc `2*i` is the forward arc of edge `i`, the arc `2*i+1`
+    /// is its backward arc.
+    neighs: Vec<Vec<(usize, usize)>>,
+    /// The residual capacity of each arc.
+    residuals: Vec<F>,
+    /// The scaled costs of each edge.
+    scaled_costs: Vec<F>,
+    /// The node potentials.
+    ///
+    /// During the computation these are scaled like the costs.
+    potentials: Vec<F>,
+    /// The excess of each node.
+    excess: Vec<F>,
+    /// The current arc of each node.
+    current: Vec<usize>,
+    /// The queue of active nodes.
+    queue: VecDeque<usize>,
+
+    solution_state: SolutionState,
+    nrefines: usize,
+    nrelabels: usize,
+
+    /// The factor by which $\varepsilon$ is divided in each refinement.
+    ///
+    /// The default is `16`.
+    pub alpha: usize,
+    /// The infinite flow value.
+    ///
+    /// Capacities greater than or equal to this are considered
+    /// unbounded. The default is `F::max_value()`.
+    pub infinite: F,
+}
+
+impl<G, F> CostScaling<G, F>
+where
+    G: IndexDigraph,
+    F: Bounded + NumCast + NumAssign + Ord + Copy + FromPrimitive + Signed,
+{
+    pub fn new(g: G) -> Self {
+        let n = FiniteGraph::num_nodes(&g);
+        let m = FiniteGraph::num_edges(&g);
+        let neighs = g
+            .nodes()
+            .map(|u| {
+                g.outedges(u)
+                    .map(|(e, v)| (g.edge_id(e) << 1, g.node_id(v)))
+                    .chain(g.inedges(u).map(|(e, v)| ((g.edge_id(e) << 1) | 1, g.node_id(v))))
+                    .collect()
+            })
+            .collect();
+        CostScaling {
+            graph: g,
+
+            balances: vec![F::zero(); n],
+            lower: vec![F::zero(); m],
+            upper: vec![F::zero(); m],
+            costs: vec![F::zero(); m],
+
+            neighs,
+            residuals: vec![F::zero(); 2 * m],
+            scaled_costs: vec![F::zero(); m],
+            potentials: vec![F::zero(); n],
+            excess: vec![F::zero(); n],
+            current: vec![0; n],
+            queue: VecDeque::with_capacity(n),
+
+            solution_state: SolutionState::Unknown,
+            nrefines: 0,
+            nrelabels: 0,
+
+            alpha: 16,
+            infinite: F::max_value(),
+        }
+    }
+
+    pub fn as_graph(&self) -> &G {
+        &self.graph
+    }
+
+    pub fn balance(&self, u: <G as GraphType>::Node<'_>) -> F {
+        self.balances[self.graph.node_id(u)]
+    }
+
+    pub fn set_balance(&mut self, u: <G as GraphType>::Node<'_>, balance: F) {
+        self.solution_state = SolutionState::Unknown;
+        self.balances[self.graph.node_id(u)] = balance;
+    }
+
+    pub fn set_balances<'a, Bs>(&'a mut self, balance: Bs)
+    where
+        Bs: Fn(<G as GraphType>::Node<'a>) -> F,
+    {
+        self.solution_state = SolutionState::Unknown;
+        for u in self.graph.nodes() {
+            self.balances[self.graph.node_id(u)] = (balance)(u);
+        }
+    }
+
+    pub fn lower(&self, e: <G as GraphType>::Edge<'_>) -> F {
+        self.lower[self.graph.edge_id(e)]
+    }
+
+    pub fn set_lower(&mut self, e: <G as GraphType>::Edge<'_>, lb: F) {
+        self.solution_state = SolutionState::Unknown;
+        self.lower[self.graph.edge_id(e)] = lb;
+    }
+
+    pub fn set_lowers<'a, Ls>(&'a mut self, lower: Ls)
+    where
+        Ls: Fn(<G as GraphType>::Edge<'a>) -> F,
+    {
+        self.solution_state = SolutionState::Unknown;
+        for e in self.graph.edges() {
+            self.lower[self.graph.edge_id(e)] = (lower)(e);
+        }
+    }
+
+    pub fn upper(&self, e: <G as GraphType>::Edge<'_>) -> F {
+        self.upper[self.graph.edge_id(e)]
+    }
+
+    pub fn set_upper(&mut self, e: <G as GraphType>::Edge<'_>, ub: F) {
+        self.solution_state = SolutionState::Unknown;
+        self.upper[self.graph.edge_id(e)] = ub;
+    }
+
+    pub fn set_uppers<'a, Us>(&'a mut self, upper: Us)
+    where
+        Us: Fn(<G as GraphType>::Edge<'a>) -> F,
+    {
+        self.solution_state = SolutionState::Unknown;
+        for e in self.graph.edges() {
+            self.upper[self.graph.edge_id(e)] = (upper)(e);
+        }
+    }
+
+    pub fn cost(&self, e: <G as GraphType>::Edge<'_>) -> F {
+        self.costs[self.graph.edge_id(e)]
+    }
+
+    pub fn set_cost(&mut self, e: <G as GraphType>::Edge<'_>, cost: F) {
+        self.solution_state = SolutionState::Unknown;
+        self.costs[self.graph.edge_id(e)] = cost;
+    }
+
+    pub fn set_costs<'a, Cs>(&'a mut self, cost: Cs)
+    where
+        Cs: Fn(<G as GraphType>::Edge<'a>) -> F,
+    {
+        self.solution_state = SolutionState::Unknown;
+        for e in self.graph.edges() {
+            self.costs[self.graph.edge_id(e)] = (cost)(e);
+        }
+    }
+
+    /// Return the value of the latest computed flow value.
+    pub fn value(&self) -> F {
+        let mut v = F::zero();
+        for e in FiniteGraph::edges(&self.graph) {
+            v += self.flow(e) * self.costs[self.graph.edge_id(e)];
+        }
+        v
+    }
+
+    /// The flow of an Edge.
+    pub fn flow(&self, a: <G as GraphType>::Edge<'_>) -> F {
+        let eid = self.graph.edge_id(a);
+        self.residuals[(eid << 1) | 1] + self.lower[eid]
+    }
+
+    /// Return the solution state of the latest computation.
+    pub fn solution_state(&self) -> SolutionState {
+        self.solution_state
+    }
+
+    /// Return the potential of a node.
+    ///
+    /// If the latest computation was successful, these are optimal
+    /// dual values, i.e. each edge with positive reduced cost
+    /// $c(uv) + \pi(u) - \pi(v)$ is at its lower bound and each edge
+    /// with negative reduced cost is at its upper bound.
+    pub fn potential(&self, u: <G as GraphType>::Node<'_>) -> F {
+        self.potentials[self.graph.node_id(u)]
+    }
+
+    /// Return the number of refinement steps of the latest computation.
+    pub fn num_iterations(&self) -> usize {
+        self.nrefines
+    }
+
+    /// Return the number of relabel operations of the latest computation.
+    pub fn num_relabels(&self) -> usize {
+        self.nrelabels
+    }
+
+    /// Solve the min-cost-flow problem.
+    pub fn solve(&mut self) -> SolutionState {
+        self.nrefines = 0;
+        self.nrelabels = 0;
+        self.solution_state = self.run();
+        self.solution_state
+    }
+
+    fn run(&mut self) -> SolutionState {
+        let n = self.graph.num_nodes();
+        let m = self.graph.num_edges();
+
+        // The initial flow is zero, all lower bounds are moved to the balances.
+        let mut supplies = self.balances.clone();
+        for eid in 0..m {
+            let cap = self.upper[eid] - self.lower[eid];
+            if cap < F::zero() {
+                return SolutionState::Infeasible;
+            }
+            self.residuals[eid << 1] = cap;
+            self.residuals[(eid << 1) | 1] = F::zero();
+            let e = self.graph.id2edge(eid);
+            supplies[self.graph.node_id(self.graph.src(e))] -= self.lower[eid];
+            supplies[self.graph.node_id(self.graph.snk(e))] += self.lower[eid];
+        }
+
+        let mut total_supply = F::zero();
+        let mut total_balance = F::zero();
+        for &b in &supplies {
+            total_balance += b;
+            if b > F::zero() {
+                total_supply += b;
+            }
+        }
+        if !total_balance.is_zero() {
+            return SolutionState::Infeasible;
+        }
+
+        // Replace infinite capacities by a sufficiently large finite
+        // value. If the problem is bounded, there is an optimal
+        // solution with flow smaller than this value on all
+        // uncapacitated edges, so their reduced costs are non-negative.
+        let mut bound = total_supply + F::one();
+        for eid in 0..m {
+            if self.upper[eid] < self.infinite {
+                bound += self.residuals[eid << 1];
+            }
+        }
+        for eid in 0..m {
+            if self.upper[eid] >= self.infinite {
+                self.residuals[eid << 1] = bound;
+            }
+        }
+
+        if !self.is_feasible(&supplies, total_supply) {
+            return SolutionState::Infeasible;
+        }
+
+        if self.has_negative_cycle() {
+            return SolutionState::Unbounded;
+        }
+
+        // Scale the costs.
+        let scale = F::from(n + 1).unwrap();
+        let mut eps = F::one();
+        for eid in 0..m {
+            self.scaled_costs[eid] = self.costs[eid] * scale;
+            eps = max(eps, self.scaled_costs[eid].abs());
+        }
+
+        self.potentials.fill(F::zero());
+        let alpha = F::from(self.alpha.max(2)).unwrap();
+        loop {
+            eps = max(eps / alpha, F::one());
+            self.refine(&supplies, eps);
+            if eps == F::one() {
+                break;
+            }
+        }
+
+        self.compute_potentials(scale);
+
+        SolutionState::Optimal
+    }
+
+    /// Compute optimal node potentials for the original costs.
+    ///
+    /// The scaled potentials of the final refinement are rounded and
+    /// then corrected by a label-correcting shortest path algorithm
+    /// on the residual network, which does not contain negative
+    /// cycles because the flow is optimal.
+    fn compute_potentials(&mut self, scale: F) {
+        let n = self.graph.num_nodes();
+        for p in &mut self.potentials {
+            let q = *p / scale;
+            *p = if q * scale > *p { q - F::one() } else { q };
+        }
+
+        let mut inqueue = vec![true; n];
+        self.queue.clear();
+        self.queue.extend(0..n);
+        while let Some(uid) = self.queue.pop_front() {
+            inqueue[uid] = false;
+            for &(a, vid) in &self.neighs[uid] {
+                if self.residuals[a] > F::zero() {
+                    let c = if (a & 1) == 0 {
+                        self.costs[a >> 1]
+                    } else {
+                        -self.costs[a >> 1]
+                    };
+                    if self.potentials[uid] + c < self.potentials[vid] {
+                        self.potentials[vid] = self.potentials[uid] + c;
+                        if !inqueue[vid] {
+                            inqueue[vid] = true;
+                            self.queue.push_back(vid);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Check whether the supplies can be satisfied.
+    ///
+    /// This solves a max-flow problem from an artificial source
+    /// connected to all supply nodes to an artificial sink connected to
+    /// all demand nodes.
+    fn is_feasible(&self, supplies: &[F], total_supply: F) -> bool {
+        if total_supply.is_zero() {
+            return true;
+        }
+
+        let n = self.graph.num_nodes();
+        let m = self.graph.num_edges();
+        let mut caps = Vec::with_capacity(m + n);
+        let net = Net::new_with(|b| {
+            let nodes = b.add_nodes(n + 2);
+            for eid in 0..m {
+                let e = self.graph.id2edge(eid);
+                b.add_edge(
+                    nodes[self.graph.node_id(self.graph.src(e))],
+                    nodes[self.graph.node_id(self.graph.snk(e))],
+                );
+                caps.push(self.residuals[eid << 1]);
+            }
+            for (uid, &s) in supplies.iter().enumerate() {
+                if s > F::zero() {
+                    b.add_edge(nodes[n], nodes[uid]);
+                    caps.push(s);
+                } else if s < F::zero() {
+                    b.add_edge(nodes[uid], nodes[n + 1]);
+                    caps.push(-s);
+                }
+            }
+        });
+
+        let mut maxflow = Dinic::new(&net);
+        maxflow.solve(net.id2node(n), net.id2node(n + 1), |e| caps[net.edge_id(e)]);
+        maxflow.value() == total_supply
+    }
+
+    /// Check whether there is a negative cycle of uncapacitated edges.
+    fn has_negative_cycle(&self) -> bool {
+        let n = self.graph.num_nodes();
+        let edges = (0..self.graph.num_edges())
+            .filter(|&eid| self.upper[eid] >= self.infinite)
+            .map(|eid| {
+                let e = self.graph.id2edge(eid);
+                (
+                    self.graph.node_id(self.graph.src(e)),
+                    self.graph.node_id(self.graph.snk(e)),
+                    self.costs[eid],
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // Moore-Bellman-Ford with an artificial source connected to all nodes.
+        let mut dist = vec![F::zero(); n];
+        for _ in 0..n {
+            let mut changed = false;
+            for &(uid, vid, c) in &edges {
+                if dist[uid] + c < dist[vid] {
+                    dist[vid] = dist[uid] + c;
+                    changed = true;
+                }
+            }
+            if !changed {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// The reduced cost of an arc.
+    fn reduced_cost(&self, a: usize, uid: usize, vid: usize) -> F {
+        let c = self.scaled_costs[a >> 1];
+        let c = if (a & 1) == 0 { c } else { -c };
+        c + self.potentials[uid] - self.potentials[vid]
+    }
+
+    /// Transform an `eps`-optimal flow into an `eps/alpha`-optimal flow.
+    fn refine(&mut self, supplies: &[F], eps: F) {
+        self.nrefines += 1;
+
+        // Saturate all arcs with negative reduced cost. This yields a
+        // 0-optimal pseudoflow.
+        for uid in 0..self.graph.num_nodes() {
+            for i in 0..self.neighs[uid].len() {
+                let (a, vid) = self.neighs[uid][i];
+                let r = self.residuals[a];
+                if r > F::zero() && self.reduced_cost(a, uid, vid) < F::zero() {
+                    self.residuals[a ^ 1] += r;
+                    self.residuals[a] = F::zero();
+                }
+            }
+        }
+
+        // Compute the excesses.
+        self.excess.copy_from_slice(supplies);
+        for eid in 0..self.graph.num_edges() {
+            let e = self.graph.id2edge(eid);
+            let flw = self.residuals[(eid << 1) | 1];
+            self.excess[self.graph.node_id(self.graph.src(e))] -= flw;
+            self.excess[self.graph.node_id(self.graph.snk(e))] += flw;
+        }
+
+        self.queue.clear();
+        for uid in 0..self.graph.num_nodes() {
+            self.current[uid] = 0;
+            if self.excess[uid] > F::zero() {
+                self.queue.push_back(uid);
+            }
+        }
+
+        while let Some(uid) = self.queue.pop_front() {
+            self.discharge(uid, eps);
+        }
+    }
+
+    /// Push the excess of an active node to its neighbors.
+    fn discharge(&mut self, uid: usize, eps: F) {
+        while self.excess[uid] > F::zero() {
+            if self.current[uid] == self.neighs[uid].len() {
+                self.relabel(uid, eps);
+                self.current[uid] = 0;
+                continue;
+            }
+
+            let (a, vid) = self.neighs[uid][self.current[uid]];
+            if self.residuals[a] > F::zero() && self.reduced_cost(a, uid, vid) < F::zero() {
+                let df = min(self.excess[uid], self.residuals[a]);
+                let was_active = self.excess[vid] > F::zero();
+                self.residuals[a] -= df;
+                self.residuals[a ^ 1] += df;
+                self.excess[uid] -= df;
+                self.excess[vid] += df;
+                if !was_active && self.excess[vid] > F::zero() {
+                    self.queue.push_back(vid);
+                }
+                if !self.residuals[a].is_zero() {
+                    // the node has been discharged completely
+                    continue;
+                }
+            }
+            self.current[uid] += 1;
+        }
+    }
+
+    /// Decrease the potential of a node as much as possible.
+    ///
+    /// Afterwards the node has an admissible arc.
+    fn relabel(&mut self, uid: usize, eps: F) {
+        self.nrelabels += 1;
+        let mut newpot = None;
+        for &(a, vid) in &self.neighs[uid] {
+            if self.residuals[a] > F::zero() {
+                let c = self.scaled_costs[a >> 1];
+                let c = if (a & 1) == 0 { c } else { -c };
+                let p = self.potentials[vid] - c;
+                newpot = Some(newpot.map(|q| max(p, q)).unwrap_or(p));
+            }
+        }
+        debug_assert!(newpot.is_some(), "Active node without residual arc");
+        self.potentials[uid] = newpot.unwrap_or(self.potentials[uid]) - eps;
+    }
+}
+
+impl<G, F> MinCostFlow<G, F> for CostScaling<G, F>
+where
+    G: IndexDigraph,
+    F: Bounded + NumCast + NumAssign + Ord + Copy + FromPrimitive + Signed,
+{
+    fn new(g: G) -> Self {
+        CostScaling::new(g)
+    }
+
+    fn as_graph(&self) -> &G {
+        CostScaling::as_graph(self)
+    }
+
+    fn balance(&self, u: <G as GraphType>::Node<'_>) -> F {
+        CostScaling::balance(self, u)
+    }
+
+    fn set_balance(&mut self, u: <G as GraphType>::Node<'_>, balance: F) {
+        CostScaling::set_balance(self, u, balance)
+    }
+
+    fn set_balances<'a, Bs>(&'a mut self, balance: Bs)
+    where
+        Bs: Fn(<G as GraphType>::Node<'a>) -> F,
+    {
+        CostScaling::set_balances(self, balance)
+    }
+
+    fn lower(&self, e: <G as GraphType>::Edge<'_>) -> F {
+        CostScaling::lower(self, e)
+    }
+
+    fn set_lower(&mut self, e: <G as GraphType>::Edge<'_>, lb: F) {
+        CostScaling::set_lower(self, e, lb)
+    }
+
+    fn set_lowers<'a, Ls>(&'a mut self, lower: Ls)
+    where
+        Ls: Fn(<G as GraphType>::Edge<'a>) -> F,
+    {
+        CostScaling::set_lowers(self, lower)
+    }
+
+    fn upper(&self, e: <G as GraphType>::Edge<'_>) -> F {
+        CostScaling::upper(self, e)
+    }
+
+    fn set_upper(&mut self, e: <G as GraphType>::Edge<'_>, ub: F) {
+        CostScaling::set_upper(self, e, ub)
+    }
+
+    fn set_uppers<'a, Us>(&'a mut self, upper: Us)
+    where
+        Us: Fn(<G as GraphType>::Edge<'a>) -> F,
+    {
+        CostScaling::set_uppers(self, upper)
+    }
+
+    fn cost(&self, e: <G as GraphType>::Edge<'_>) -> F {
+        CostScaling::cost(self, e)
+    }
+
+    fn set_cost(&mut self, e: <G as GraphType>::Edge<'_>, cost: F) {
+        CostScaling::set_cost(self, e, cost)
+    }
+
+    fn set_costs<'a, Cs>(&'a mut self, cost: Cs)
+    where
+        Cs: Fn(<G as GraphType>::Edge<'a>) -> F,
+    {
+        CostScaling::set_costs(self, cost)
+    }
+
+    fn solve(&mut self) -> SolutionState {
+        CostScaling::solve(self)
+    }
+
+    fn solution_state(&self) -> SolutionState {
+        CostScaling::solution_state(self)
+    }
+
+    fn flow(&self, e: <G as GraphType>::Edge<'_>) -> F {
+        CostScaling::flow(self, e)
+    }
+
+    fn value(&self) -> F {
+        CostScaling::value(self)
+    }
+
+    fn potential(&self, u: <G as GraphType>::Node<'_>) -> F {
+        CostScaling::potential(self, u)
+    }
+}
+
+/// Solve a min-cost-flow problem with the cost scaling algorithm.
+///
+/// The function returns the objective value and the optimal flow.
+#[allow(clippy::type_complexity)]
+pub fn cost_scaling<G, F, Bs, Ls, Us, Cs>(
+    g: &G,
+    balances: Bs,
+    lower: Ls,
+    upper: Us,
+    costs: Cs,
+) -> Option<(F, Vec<(G::Edge<'_>, F)>)>
+where
+    G: IndexDigraph,
+    F: NumAssign + NumCast + FromPrimitive + Ord + Signed + Bounded + Copy,
+    Bs: Fn(G::Node<'_>) -> F,
+    Ls: Fn(G::Edge<'_>) -> F,
+    Us: Fn(G::Edge<'_>) -> F,
+    Cs: Fn(G::Edge<'_>) -> F,
+{
+    let mut cs = CostScaling::new(g);
+    cs.set_balances(balances);
+    cs.set_lowers(lower);
+    cs.set_uppers(upper);
+    cs.set_costs(costs);
+    if cs.solve() == SolutionState::Optimal {
+        Some((cs.value(), g.edges().map(|e| (e, cs.flow(e))).collect()))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CostScaling;
+    use crate::mcf::SolutionState;
+    use crate::traits::*;
+    use crate::{Buildable, Builder, Net};
+
+    fn solve(n: usize, balances: &[isize], edges: &[(usize, usize, isize, isize, isize)]) -> (SolutionState, isize) {
+        let g = Net::new_with(|b| {
+            let nodes = b.add_nodes(n);
+            for &(u, v, _, _, _) in edges {
+                b.add_edge(nodes[u], nodes[v]);
+            }
+        });
+        let mut cs = CostScaling::new(&g);
+        cs.set_balances(|u| balances[g.node_id(u)]);
+        cs.set_lowers(|e| edges[g.edge_id(e)].2);
+        cs.set_uppers(|e| edges[g.edge_id(e)].3);
+        cs.set_costs(|e| edges[g.edge_id(e)].4);
+        let state = cs.solve();
+        if state != SolutionState::Optimal {
+            return (state, 0);
+        }
+
+        let mut excess = balances.to_vec();
+        for e in g.edges() {
+            let (_, _, l, u, _) = edges[g.edge_id(e)];
+            let f = cs.flow(e);
+            assert!(l <= f && f <= u);
+            excess[g.node_id(g.src(e))] -= f;
+            excess[g.node_id(g.snk(e))] += f;
+        }
+        assert!(excess.iter().all(|&x| x == 0));
+        (state, cs.value())
+    }
+
+    #[test]
+    fn test_cost_scaling() {
+        let edges = [
+            (0, 1, 0, 4, 2),
+            (0, 2, 0, 2, 2),
+            (1, 2, 0, 2, 1),
+            (1, 3, 0, 3, 3),
+            (2, 3, 0, 5, 1),
+            (2, 4, 1, 4, 2),
+            (3, 4, 0, 6, 1),
+        ];
+        assert_eq!(solve(5, &[6, 0, 0, 0, -6], &edges), (SolutionState::Optimal, 30));
+    }
+
+    #[test]
+    fn test_cost_scaling_negative_costs() {
+        let inf = isize::MAX;
+        let edges = [
+            (0, 1, 0, inf, -3),
+            (1, 2, 0, 4, 2),
+            (2, 0, 0, inf, -1),
+            (1, 0, 0, inf, 5),
+        ];
+        assert_eq!(solve(3, &[0, 0, 0], &edges), (SolutionState::Optimal, -8));
+    }
+
+    #[test]
+    fn test_cost_scaling_infeasible() {
+        let edges = [(0, 1, 0, 3, 1), (1, 2, 0, 10, 1), (0, 3, 0, 1, 1), (3, 2, 0, 10, 1)];
+        assert_eq!(solve(4, &[5, 0, -5, 0], &edges).0, SolutionState::Infeasible);
+        assert_eq!(solve(2, &[0, 0], &[(0, 1, 4, 2, 1)]).0, SolutionState::Infeasible);
+        assert_eq!(solve(2, &[1, 0], &[(0, 1, 0, 2, 1)]).0, SolutionState::Infeasible);
+    }
+
+    #[test]
+    fn test_cost_scaling_unbounded() {
+        let inf = isize::MAX;
+        let edges = [
+            (0, 1, 0, inf, 1),
+            (1, 2, 0, inf, -3),
+            (2, 0, 0, inf, 1),
+            (0, 2, 0, 5, 1),
+        ];
+        assert_eq!(solve(3, &[0, 0, 0], &edges).0, SolutionState::Unbounded);
+    }
+}
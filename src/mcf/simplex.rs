@@ -17,7 +17,7 @@
 
 //! A primal network simplex implementation.
 
-use super::SolutionState;
+use super::{Infeasibility, MinCostFlow, SolutionState};
 use crate::adjacencies::Adjacencies;
 use crate::collections::NodeVecMap;
 use crate::search::dfs;
@@ -26,10 +26,22 @@ use num_traits::{Bounded, FromPrimitive, NumAssign, NumCast, Signed, ToPrimitive
 
 type ID = u32;
 
+/// Pricing strategy for choosing the entering edge.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Pricing {
+    /// Choose the first eligible edge (round robin).
     RoundRobin,
+    /// Choose the edge with the most negative reduced cost (Dantzig's rule).
     Complete,
+    /// Choose the best edge of the first block containing an eligible edge.
     Block,
+    /// Keep a list of candidate edges and choose the best candidate.
+    ///
+    /// In a major iteration the edges are scanned blockwise until at
+    /// least `candidate_list_size` eligible edges have been found. The
+    /// following minor iterations only reconsider the edges in this
+    /// candidate list until it is exhausted or a limit of minor
+    /// iterations has been reached.
     MultiplePartial,
 }
 
@@ -59,10 +71,27 @@ pub struct NetworkSimplex<G, F> {
     pub pricing: Pricing,
     current_edge: ID,
     block_size: usize,
+    /// The size of the candidate list for `Pricing::MultiplePartial`.
+    ///
+    /// If `None` (the default) the size is set to
+    /// `max(sqrt(m) / 4, 5)`.
+    pub candidate_list_size: Option<usize>,
+    /// The block size for `Pricing::MultiplePartial`.
+    ///
+    /// This is the number of edges scanned in a major iteration
+    /// before checking whether the candidate list is full. If `None`
+    /// (the default) the size is set to `max(sqrt(m) / 2, 10)`.
+    pub candidate_block_size: Option<usize>,
+    candidates: Vec<ID>,
+    list_size: usize,
+    minor_limit: usize,
+    minor_count: usize,
     /// The (flow) value to be considered zero. Defaults to `F::zero()`.
     pub zero: F,
 
     niter: usize,
+    /// The entering edge of the pivot that detected unboundedness.
+    unbounded_edge: Option<ID>,
     solution_state: SolutionState,
     need_new_basis: bool,
 
@@ -113,9 +142,16 @@ where
             pricing: Pricing::Block,
             current_edge: 0,
             block_size: 0,
+            candidate_list_size: None,
+            candidate_block_size: None,
+            candidates: vec![],
+            list_size: 0,
+            minor_limit: 0,
+            minor_count: 0,
             zero: F::zero(),
 
             niter: 0,
+            unbounded_edge: None,
             solution_state: SolutionState::Unknown,
             need_new_basis: true,
 
@@ -135,7 +171,7 @@ where
     }
 
     pub fn set_balance(&mut self, u: <G as GraphType>::Node<'_>, balance: F) {
-        self.need_new_basis = true;
+        self.solution_state = SolutionState::Unknown;
         self.balances[self.graph.node_id(u)] = balance;
     }
 
@@ -143,6 +179,7 @@ where
     where
         Bs: Fn(<G as GraphType>::Node<'a>) -> F,
     {
+        self.solution_state = SolutionState::Unknown;
         for u in self.graph.nodes() {
             self.balances[self.graph.node_id(u)] = (balance)(u);
         }
@@ -153,7 +190,7 @@ where
     }
 
     pub fn set_lower(&mut self, e: <G as GraphType>::Edge<'_>, lb: F) {
-        self.need_new_basis = true;
+        self.solution_state = SolutionState::Unknown;
         self.lower[self.graph.edge_id(e)] = lb;
     }
 
@@ -161,6 +198,7 @@ where
     where
         Ls: Fn(<G as GraphType>::Edge<'a>) -> F,
     {
+        self.solution_state = SolutionState::Unknown;
         for e in self.graph.edges() {
             self.lower[self.graph.edge_id(e)] = (lower)(e);
         }
@@ -171,7 +209,7 @@ where
     }
 
     pub fn set_upper(&mut self, e: <G as GraphType>::Edge<'_>, ub: F) {
-        self.need_new_basis = true;
+        self.solution_state = SolutionState::Unknown;
         self.upper[self.graph.edge_id(e)] = ub;
     }
 
@@ -179,6 +217,7 @@ where
     where
         Us: Fn(<G as GraphType>::Edge<'a>) -> F,
     {
+        self.solution_state = SolutionState::Unknown;
         for e in self.graph.edges() {
             self.upper[self.graph.edge_id(e)] = (upper)(e);
         }
@@ -189,6 +228,7 @@ where
     }
 
     pub fn set_cost(&mut self, e: <G as GraphType>::Edge<'_>, cost: F) {
+        self.solution_state = SolutionState::Unknown;
         self.costs[self.graph.edge_id(e)] = cost;
     }
 
@@ -196,6 +236,7 @@ where
     where
         Cs: Fn(<G as GraphType>::Edge<'a>) -> F,
     {
+        self.solution_state = SolutionState::Unknown;
         for e in self.graph.edges() {
             self.costs[self.graph.edge_id(e)] = (cost)(e);
         }
@@ -216,9 +257,81 @@ where
         self.flows[eid] + self.lower[eid]
     }
 
+    /// The potential of a node.
+    ///
+    /// The potentials are the dual values of the flow conservation
+    /// constraints of the latest computed solution. They are chosen
+    /// such that the reduced cost `cost(e) + potential(u) -
+    /// potential(v)` of each edge `e = (u,v)` is zero if the flow is
+    /// strictly between its bounds, non-negative if the flow is at its
+    /// lower bound and non-positive if the flow is at its upper bound.
+    pub fn potential(&self, u: <G as GraphType>::Node<'_>) -> F {
+        self.potentials[self.graph.node_id(u)]
+    }
+
+    /// The reduced cost of an edge.
+    ///
+    /// The reduced cost of the edge `e = (u,v)` is `cost(e) +
+    /// potential(u) - potential(v)` with respect to the latest
+    /// computed solution. It is zero for all basic edges.
+    pub fn reduced_cost(&self, e: <G as GraphType>::Edge<'_>) -> F {
+        let eid = self.graph.edge_id(e);
+        self.costs[eid] - self.potentials[self.sinks[eid] as usize] + self.potentials[self.sources[eid] as usize]
+    }
+
+    /// Return whether an edge is contained in the basis of the latest
+    /// computed solution.
+    pub fn is_basic(&self, e: <G as GraphType>::Edge<'_>) -> bool {
+        self.state[self.graph.edge_id(e)] == 0
+    }
+
+    /// The range of costs of a non-basic edge for which the latest
+    /// computed basis remains optimal.
+    ///
+    /// If the cost of the edge is changed to any value within the
+    /// returned closed interval (and nothing else is changed), the
+    /// current solution remains optimal. If the edge is at its lower
+    /// bound, its cost may be decreased by its reduced cost and
+    /// increased arbitrarily. If the edge is at its upper bound, its
+    /// cost may be decreased arbitrarily and increased by the absolute
+    /// value of its reduced cost. Unbounded sides of the interval are
+    /// represented by `-infinite` and `infinite`, respectively.
+    ///
+    /// Returns `None` if the edge is a basic edge.
+    pub fn cost_range(&self, e: <G as GraphType>::Edge<'_>) -> Option<(F, F)> {
+        let eid = self.graph.edge_id(e);
+        let c = self.costs[eid];
+        let rc = self.reduced_cost(e);
+        match self.state[eid] {
+            1 => Some((c - rc, self.infinite)),
+            -1 => Some((-self.infinite, c - rc)),
+            _ => None,
+        }
+    }
+
+    /// Discard the current basis.
+    ///
+    /// By default, [`solve`](Self::solve) reuses the basis of the
+    /// previous solution (warm start). After a call to this method the
+    /// next call to `solve` starts again from the artificial initial
+    /// basis (cold start).
+    pub fn reset_basis(&mut self) {
+        self.need_new_basis = true;
+    }
+
     /// Solve the min-cost-flow problem.
+    ///
+    /// If the problem has been solved before, the basis of the previous
+    /// solution is reused. This is efficient if only a few costs, bounds
+    /// or balances have been changed since the last call. If costs
+    /// have been changed, the previous basis remains primal feasible.
+    /// If bounds or balances have been changed, the basis is repaired
+    /// by replacing infeasible basis edges by artificial edges.
+    ///
+    /// Use [`reset_basis`](Self::reset_basis) to force a cold start.
     pub fn solve(&mut self) -> SolutionState {
         self.niter = 0;
+        self.unbounded_edge = None;
         self.solution_state = SolutionState::Unknown;
 
         // check trivial cases
@@ -239,15 +352,21 @@ where
 
         self.initialize_pricing();
 
-        if self.need_new_basis && !self.prepare_initial_basis() {
+        let warm_start = !self.need_new_basis;
+        if warm_start {
+            if !self.update_basis() {
+                self.solution_state = SolutionState::Infeasible;
+                return self.solution_state;
+            }
+        } else if !self.prepare_initial_basis() {
             self.solution_state = SolutionState::Infeasible;
             return self.solution_state;
         }
 
         self.initialize_node_potentials();
 
-        // heuristic initial pivot
-        if !self.compute_initial_pivots() {
+        // heuristic initial pivot (only for the artificial basis)
+        if !warm_start && !self.compute_initial_pivots() {
             self.solution_state = SolutionState::Unbounded;
             return self.solution_state;
         }
@@ -275,11 +394,137 @@ where
 
     /// Return the solution state of the latest computation.
     pub fn solution_state(&self) -> SolutionState {
-        if self.need_new_basis {
-            SolutionState::Unknown
+        self.solution_state
+    }
+
+    /// Return a certificate for the infeasibility of the problem.
+    ///
+    /// If the latest computation returned
+    /// [`SolutionState::Infeasible`], this is either an edge whose
+    /// lower bound exceeds its upper bound or a node set whose net
+    /// supply exceeds the capacity of the cut (see [`Infeasibility`]).
+    /// The node set consists of all nodes reachable in the residual
+    /// network of the final flow from some node with unsatisfied
+    /// supply.
+    ///
+    /// Returns `None` if the latest computation did not detect
+    /// infeasibility. It may also return `None` if the artificial cost
+    /// has been chosen too small, in which case the infeasibility
+    /// detected by the algorithm may be wrong.
+    pub fn infeasibility(&self) -> Option<Infeasibility<G::Node<'_>, G::Edge<'_>>> {
+        if self.solution_state != SolutionState::Infeasible {
+            return None;
+        }
+
+        if let Some(e) = self.graph.edges().find(|&e| self.upper(e) < self.lower(e)) {
+            return Some(Infeasibility::Bounds(e));
+        }
+
+        // compute the unsatisfied supply of each node
+        let n = self.graph.num_nodes();
+        let mut excess = self.balances.clone();
+        for e in self.graph.edges() {
+            let flw = self.flow(e);
+            excess[self.graph.node_id(self.graph.src(e))] -= flw;
+            excess[self.graph.node_id(self.graph.snk(e))] += flw;
+        }
+
+        // search the residual network starting at all nodes with positive excess
+        let mut seen = vec![false; n];
+        let mut stack = Vec::with_capacity(n);
+        for (uid, &ex) in excess.iter().enumerate() {
+            if ex > self.zero {
+                seen[uid] = true;
+                stack.push(uid);
+            }
+        }
+        while let Some(uid) = stack.pop() {
+            let u = self.graph.id2node(uid);
+            let outs = self.graph.outedges(u).filter(|&(e, _)| self.flow(e) < self.upper(e));
+            let ins = self.graph.inedges(u).filter(|&(e, _)| self.flow(e) > self.lower(e));
+            for (_, v) in outs.chain(ins) {
+                let vid = self.graph.node_id(v);
+                if !seen[vid] {
+                    seen[vid] = true;
+                    stack.push(vid);
+                }
+            }
+        }
+
+        // verify the cut condition
+        let mut supply = F::zero();
+        for (uid, &b) in self.balances.iter().enumerate() {
+            if seen[uid] {
+                supply += b;
+            }
+        }
+        for e in self.graph.edges() {
+            let eid = self.graph.edge_id(e);
+            match (
+                seen[self.graph.node_id(self.graph.src(e))],
+                seen[self.graph.node_id(self.graph.snk(e))],
+            ) {
+                (true, false) => supply -= self.upper[eid],
+                (false, true) => supply += self.lower[eid],
+                _ => (),
+            }
+        }
+
+        if supply > self.zero {
+            Some(Infeasibility::Cut(
+                self.graph.nodes().filter(|&u| seen[self.graph.node_id(u)]).collect(),
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Return a negative cycle of uncapacitated edges.
+    ///
+    /// If the latest computation returned [`SolutionState::Unbounded`],
+    /// this is a directed cycle of edges with infinite capacity and
+    /// negative total cost. The edges are returned in the order of the
+    /// cycle.
+    ///
+    /// Returns `None` if the latest computation did not detect
+    /// unboundedness. It may also return `None` if the artificial cost
+    /// has been chosen too small, in which case the unboundedness
+    /// detected by the algorithm may be wrong.
+    pub fn unbounded_cycle(&self) -> Option<Vec<G::Edge<'_>>> {
+        if self.solution_state != SolutionState::Unbounded {
+            return None;
+        }
+        let e_in = self.unbounded_edge? as usize;
+        let (u_in, v_in) = if (e_in & 1) == 0 {
+            (self.sources[e_in / 2] as usize, self.sinks[e_in / 2] as usize)
         } else {
-            self.solution_state
+            (self.sinks[e_in / 2] as usize, self.sources[e_in / 2] as usize)
+        };
+
+        // The cycle consists of the entering edge, the path from v_in
+        // up to the common ancestor and the path from the ancestor
+        // down to u_in.
+        let mut up = vec![e_in / 2];
+        let mut down = vec![];
+        let mut uid = u_in;
+        let mut vid = v_in;
+        while uid != vid {
+            if self.subtrees[uid] < self.subtrees[vid] {
+                down.push(self.parent_edges[uid] as usize / 2);
+                uid = self.parent_nodes[uid] as usize;
+            } else {
+                up.push(self.parent_edges[vid] as usize / 2);
+                vid = self.parent_nodes[vid] as usize;
+            }
+        }
+
+        let m = self.graph.num_edges();
+        let cycle = up.into_iter().chain(down.into_iter().rev());
+        if cycle.clone().any(|eid| eid >= m) {
+            // the cycle contains artificial edges
+            return None;
         }
+        Some(cycle.map(|eid| self.graph.id2edge(eid)).collect())
     }
 
     fn init(&mut self) {
@@ -312,7 +557,24 @@ where
                     .unwrap()
                     .max(10);
             }
-            Pricing::MultiplePartial => todo!(),
+            Pricing::MultiplePartial => {
+                let sqrt_m = (self.graph.num_edges() as f64).sqrt();
+                self.current_edge = 0;
+                self.block_size = self
+                    .candidate_block_size
+                    .unwrap_or_else(|| (sqrt_m * 0.5).round().to_usize().unwrap().max(10))
+                    .max(1);
+                self.list_size = self
+                    .candidate_list_size
+                    .unwrap_or_else(|| (sqrt_m * 0.25).round().to_usize().unwrap().max(5))
+                    .max(1);
+                // The number of minor iterations per major iteration (as in LEMON).
+                self.minor_limit = (self.list_size / 10).max(3);
+                // Force a major iteration in the first step.
+                self.minor_count = self.minor_limit;
+                self.candidates.clear();
+                self.candidates.reserve(self.list_size + self.block_size);
+            }
         }
     }
 
@@ -326,16 +588,7 @@ where
         let mut balances = self.balances.clone();
         balances.push(F::zero());
 
-        // compute the cost value for the artificial edges
-        let artificial_cost = self.artificial_cost.unwrap_or_else(|| {
-            let mut value = F::zero();
-            for &c in &self.costs[0..self.graph.num_edges()] {
-                if c > value {
-                    value = c;
-                }
-            }
-            F::from(n).unwrap() * (F::one() + value)
-        });
+        let artificial_cost = self.compute_artificial_cost();
 
         self.subtrees[uid] = n as ID + 1;
         self.parent_edges[uid] = ID::max_value();
@@ -356,8 +609,9 @@ where
                 return false;
             }
 
+            // Uncapacitated edges cannot be at their upper bound.
             let flw: F;
-            if self.costs[eid] >= F::zero() {
+            if self.costs[eid] >= F::zero() || cap >= self.infinite {
                 self.state[eid] = 1;
                 flw = F::zero();
             } else {
@@ -414,6 +668,180 @@ where
         true
     }
 
+    /// Return the cost value for the artificial edges.
+    fn compute_artificial_cost(&self) -> F {
+        self.artificial_cost.unwrap_or_else(|| {
+            let mut value = F::zero();
+            for &c in &self.costs[0..self.graph.num_edges()] {
+                if c > value {
+                    value = c;
+                }
+            }
+            F::from(self.graph.num_nodes()).unwrap() * (F::one() + value)
+        })
+    }
+
+    /// Update the basis of the previous solution.
+    ///
+    /// The flows on the non-basic edges are set to the (possibly
+    /// changed) bounds and the flows on the basis edges are recomputed
+    /// from the balances. Each subtree whose parent edge would get an
+    /// infeasible flow is detached and connected to the root by its
+    /// artificial edge, while the former parent edge becomes non-basic
+    /// at its nearest bound.
+    ///
+    /// Returns `false` if some edge has an empty feasible interval.
+    fn update_basis(&mut self) -> bool {
+        let n = self.graph.num_nodes();
+        let m = self.graph.num_edges();
+        let rootid = n;
+
+        let artificial_cost = self.compute_artificial_cost();
+
+        // The net supply of each node with respect to the non-basic
+        // edges and the lower bounds of the basic edges.
+        let mut supplies = self.balances.clone();
+        supplies.push(F::zero());
+        for eid in 0..m {
+            let cap = self.upper[eid] - self.lower[eid];
+            if cap < self.zero {
+                return false;
+            }
+            self.caps[eid] = cap;
+
+            let flw = match self.state[eid] {
+                0 => {
+                    supplies[self.sources[eid] as usize] -= self.lower[eid];
+                    supplies[self.sinks[eid] as usize] += self.lower[eid];
+                    continue;
+                }
+                -1 if cap < self.infinite => cap,
+                _ => {
+                    self.state[eid] = 1;
+                    F::zero()
+                }
+            };
+            self.flows[eid] = flw;
+            let flw = flw + self.lower[eid];
+            supplies[self.sources[eid] as usize] -= flw;
+            supplies[self.sinks[eid] as usize] += flw;
+        }
+
+        // Compute the flows on the basis edges bottom-up, i.e. in
+        // reverse preorder. After a node has been handled, `supplies`
+        // contains the net supply of its (remaining) subtree.
+        let mut detached = Vec::new();
+        let mut uid = self.last_preorder[rootid] as usize;
+        while uid != rootid {
+            let fid = self.parent_edges[uid] as usize;
+            let vid = self.parent_nodes[uid] as usize;
+            let supply = supplies[uid];
+            if vid == rootid {
+                self.set_artificial_parent(uid, supply, artificial_cost);
+            } else {
+                // the flow on the parent edge in its own direction
+                let flw = -oriented_flow(fid, supply);
+                if flw < F::zero() || flw > self.caps[fid / 2] {
+                    // Make the parent edge non-basic at its nearest bound ...
+                    let flw = if flw < F::zero() {
+                        self.state[fid / 2] = 1;
+                        F::zero()
+                    } else {
+                        self.state[fid / 2] = -1;
+                        self.caps[fid / 2]
+                    };
+                    self.flows[fid / 2] = flw;
+                    // ... and send the remaining supply to the root.
+                    let outflow = -oriented_flow(fid, flw);
+                    supplies[vid] += outflow;
+                    detached.push((uid, supply - outflow));
+                } else {
+                    self.flows[fid / 2] = flw;
+                    supplies[vid] += supply;
+                }
+            }
+            uid = self.prev_preorder[uid] as usize;
+        }
+
+        // Move the detached subtrees below the root. The subtrees are
+        // ordered bottom-up, so each subtree is moved before any of its
+        // ancestors.
+        for (uid, supply) in detached {
+            self.detach_subtree(uid);
+            self.set_artificial_parent(uid, supply, artificial_cost);
+        }
+
+        true
+    }
+
+    /// Make the artificial edge of node `uid` its parent edge.
+    ///
+    /// The edge is oriented such that it carries the net `supply` of
+    /// the subtree of `uid` with a non-negative flow. The node must
+    /// already be a child of the root.
+    fn set_artificial_parent(&mut self, uid: usize, supply: F, artificial_cost: F) {
+        let n = self.graph.num_nodes();
+        let eid = 2 * (self.graph.num_edges() + uid);
+        let fid = if supply >= F::zero() {
+            self.costs[eid / 2] = F::zero();
+            self.sources[eid / 2] = uid as ID;
+            self.sinks[eid / 2] = n as ID;
+            self.flows[eid / 2] = supply;
+            eid ^ 1
+        } else {
+            self.costs[eid / 2] = artificial_cost;
+            self.sources[eid / 2] = n as ID;
+            self.sinks[eid / 2] = uid as ID;
+            self.flows[eid / 2] = -supply;
+            eid
+        };
+        self.caps[eid / 2] = self.infinite;
+        self.state[eid / 2] = 0;
+        self.parent_nodes[uid] = n as ID;
+        self.parent_edges[uid] = fid as ID;
+    }
+
+    /// Move the subtree of `uid` directly below the root.
+    ///
+    /// The subtree is moved to the front of the preorder list. The
+    /// parent edge of `uid` is not changed.
+    fn detach_subtree(&mut self, uid: usize) {
+        let rootid = self.graph.num_nodes();
+        let size = self.subtrees[uid];
+        let u_last = self.last_preorder[uid];
+        let u_prev = self.prev_preorder[uid];
+
+        // Remove the subtree from the preorder list ...
+        let after = self.next_preorder[u_last as usize];
+        self.next_preorder[u_prev as usize] = after;
+        if after != ID::max_value() {
+            self.prev_preorder[after as usize] = u_prev;
+        }
+
+        // ... update the ancestors ...
+        let mut vid = self.parent_nodes[uid] as usize;
+        loop {
+            if self.last_preorder[vid] == u_last {
+                self.last_preorder[vid] = u_prev;
+            }
+            if vid == rootid {
+                break;
+            }
+            self.subtrees[vid] -= size;
+            vid = self.parent_nodes[vid] as usize;
+        }
+
+        // ... and insert it right after the root.
+        let first = self.next_preorder[rootid];
+        self.next_preorder[rootid] = uid as ID;
+        self.prev_preorder[uid] = rootid as ID;
+        self.next_preorder[u_last as usize] = first;
+        if first != ID::max_value() {
+            self.prev_preorder[first as usize] = u_last;
+        }
+        self.parent_nodes[uid] = rootid as ID;
+    }
+
     /// Heuristic initial pivots
     ///
     /// Returns `false` if unboundedness has been detected and `true` otherwise.
@@ -468,7 +896,7 @@ where
 
         // Perform heuristic initial pivots
         for eid in edges {
-            if self.reduced_cost(eid) >= F::zero() {
+            if self.pricing_cost(eid) >= F::zero() {
                 continue;
             }
             let eid = self.oriented_edge(eid);
@@ -553,6 +981,7 @@ where
         }
 
         if d >= self.infinite {
+            self.unbounded_edge = Some(e_in as ID);
             return false;
         }
 
@@ -815,7 +1244,7 @@ where
     fn round_robin_pricing(&mut self) -> Option<ID> {
         let mut eid = self.current_edge as usize;
         loop {
-            if self.reduced_cost(eid) < F::zero() {
+            if self.pricing_cost(eid) < F::zero() {
                 self.current_edge = eid as ID;
                 return Some(self.oriented_edge(eid));
             }
@@ -830,7 +1259,7 @@ where
         let mut min_cost = F::zero();
         let mut min_edge = None;
         for eid in 0..self.graph.num_edges() {
-            let c = self.reduced_cost(eid);
+            let c = self.pricing_cost(eid);
             if c < min_cost {
                 min_cost = c;
                 min_edge = Some(eid);
@@ -850,7 +1279,7 @@ where
 
         loop {
             while eid < m {
-                let c = self.reduced_cost(eid);
+                let c = self.pricing_cost(eid);
                 if c < min_cost {
                     min_cost = c;
                     min_edge = Some(eid);
@@ -884,10 +1313,74 @@ where
     }
 
     fn multiple_partial_pricing(&mut self) -> Option<ID> {
-        todo!()
+        // Minor iteration: choose the best edge from the candidate list.
+        if self.minor_count < self.minor_limit {
+            let mut min_cost = F::zero();
+            let mut min_edge = None;
+            let mut i = 0;
+            while i < self.candidates.len() {
+                let eid = self.candidates[i] as usize;
+                let c = self.pricing_cost(eid);
+                if c < F::zero() {
+                    if c < min_cost {
+                        min_cost = c;
+                        min_edge = Some(eid);
+                    }
+                    i += 1;
+                } else {
+                    // the edge is not eligible anymore, remove it from the list
+                    self.candidates.swap_remove(i);
+                }
+            }
+
+            if let Some(eid) = min_edge {
+                self.minor_count += 1;
+                return Some(self.oriented_edge(eid));
+            }
+        }
+
+        // Major iteration: rebuild the candidate list by scanning the edges blockwise.
+        self.candidates.clear();
+        let m = self.graph.num_edges();
+        let start = self.current_edge as usize % m;
+        let mut eid = start;
+        let mut min_cost = F::zero();
+        let mut min_edge = None;
+        let mut cnt = self.block_size;
+        loop {
+            let c = self.pricing_cost(eid);
+            if c < F::zero() {
+                self.candidates.push(eid as ID);
+                if c < min_cost {
+                    min_cost = c;
+                    min_edge = Some(eid);
+                }
+            }
+
+            eid = (eid + 1) % m;
+            cnt -= 1;
+            if cnt == 0 {
+                // end of block, stop if the candidate list is full
+                if self.candidates.len() >= self.list_size {
+                    break;
+                }
+                cnt = self.block_size;
+            }
+            if eid == start {
+                break;
+            }
+        }
+
+        self.current_edge = eid as ID;
+        self.minor_count = 1;
+        min_edge.map(|eid| self.oriented_edge(eid))
     }
 
-    fn reduced_cost(&self, eid: usize) -> F {
+    /// Return the reduced cost of an edge with respect to its bound.
+    ///
+    /// The value is negative if and only if the edge is eligible to
+    /// enter the basis.
+    fn pricing_cost(&self, eid: usize) -> F {
         unsafe {
             F::from(*self.state.get_unchecked(eid)).unwrap()
                 * (*self.costs.get_unchecked(eid)
@@ -909,6 +1402,100 @@ where
     (F::one() - F::from((eid & 1) * 2).unwrap()) * d
 }
 
+impl<G, F> MinCostFlow<G, F> for NetworkSimplex<G, F>
+where
+    G: IndexDigraph,
+    F: Bounded + NumCast + NumAssign + PartialOrd + Copy + FromPrimitive + Signed,
+{
+    fn new(g: G) -> Self {
+        NetworkSimplex::new(g)
+    }
+
+    fn as_graph(&self) -> &G {
+        NetworkSimplex::as_graph(self)
+    }
+
+    fn balance(&self, u: <G as GraphType>::Node<'_>) -> F {
+        NetworkSimplex::balance(self, u)
+    }
+
+    fn set_balance(&mut self, u: <G as GraphType>::Node<'_>, balance: F) {
+        NetworkSimplex::set_balance(self, u, balance)
+    }
+
+    fn set_balances<'a, Bs>(&'a mut self, balance: Bs)
+    where
+        Bs: Fn(<G as GraphType>::Node<'a>) -> F,
+    {
+        NetworkSimplex::set_balances(self, balance)
+    }
+
+    fn lower(&self, e: <G as GraphType>::Edge<'_>) -> F {
+        NetworkSimplex::lower(self, e)
+    }
+
+    fn set_lower(&mut self, e: <G as GraphType>::Edge<'_>, lb: F) {
+        NetworkSimplex::set_lower(self, e, lb)
+    }
+
+    fn set_lowers<'a, Ls>(&'a mut self, lower: Ls)
+    where
+        Ls: Fn(<G as GraphType>::Edge<'a>) -> F,
+    {
+        NetworkSimplex::set_lowers(self, lower)
+    }
+
+    fn upper(&self, e: <G as GraphType>::Edge<'_>) -> F {
+        NetworkSimplex::upper(self, e)
+    }
+
+    fn set_upper(&mut self, e: <G as GraphType>::Edge<'_>, ub: F) {
+        NetworkSimplex::set_upper(self, e, ub)
+    }
+
+    fn set_uppers<'a, Us>(&'a mut self, upper: Us)
+    where
+        Us: Fn(<G as GraphType>::Edge<'a>) -> F,
+    {
+        NetworkSimplex::set_uppers(self, upper)
+    }
+
+    fn cost(&self, e: <G as GraphType>::Edge<'_>) -> F {
+        NetworkSimplex::cost(self, e)
+    }
+
+    fn set_cost(&mut self, e: <G as GraphType>::Edge<'_>, cost: F) {
+        NetworkSimplex::set_cost(self, e, cost)
+    }
+
+    fn set_costs<'a, Cs>(&'a mut self, cost: Cs)
+    where
+        Cs: Fn(<G as GraphType>::Edge<'a>) -> F,
+    {
+        NetworkSimplex::set_costs(self, cost)
+    }
+
+    fn solve(&mut self) -> SolutionState {
+        NetworkSimplex::solve(self)
+    }
+
+    fn solution_state(&self) -> SolutionState {
+        NetworkSimplex::solution_state(self)
+    }
+
+    fn flow(&self, e: <G as GraphType>::Edge<'_>) -> F {
+        NetworkSimplex::flow(self, e)
+    }
+
+    fn value(&self) -> F {
+        NetworkSimplex::value(self)
+    }
+
+    fn potential(&self, u: <G as GraphType>::Node<'_>) -> F {
+        NetworkSimplex::potential(self, u)
+    }
+}
+
 /// Solve a min-cost-flow problem with a network simplex algorithm.
 ///
 /// The function returns the objective value and the optimal flow.
@@ -938,3 +1525,122 @@ where
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::NetworkSimplex;
+    use crate::mcf::{Infeasibility, SolutionState};
+    use crate::traits::*;
+    use crate::{Buildable, Builder, Net};
+
+    /// Build a network from a list of edges `(u, v, lower, upper, cost)`.
+    fn network(n: usize, edges: &[(usize, usize, isize, isize, isize)]) -> (Net, Vec<(isize, isize, isize)>) {
+        let g = Net::new_with(|b| {
+            let nodes = b.add_nodes(n);
+            for &(u, v, _, _, _) in edges {
+                b.add_edge(nodes[u], nodes[v]);
+            }
+        });
+        (g, edges.iter().map(|&(_, _, l, u, c)| (l, u, c)).collect())
+    }
+
+    fn solve<'a>(g: &'a Net, balances: &[isize], data: &[(isize, isize, isize)]) -> NetworkSimplex<&'a Net, isize> {
+        let mut spx = NetworkSimplex::new(g);
+        spx.set_balances(|u| balances[g.node_id(u)]);
+        spx.set_lowers(|e| data[g.edge_id(e)].0);
+        spx.set_uppers(|e| data[g.edge_id(e)].1);
+        spx.set_costs(|e| data[g.edge_id(e)].2);
+        spx.solve();
+        spx
+    }
+
+    /// Check Hoffman's condition for a node set.
+    fn check_cut(g: &Net, balances: &[isize], data: &[(isize, isize, isize)], cut: &[<Net as GraphType>::Node<'_>]) {
+        let supply = cut.iter().map(|&u| balances[g.node_id(u)]).sum::<isize>();
+        let cap = g
+            .edges()
+            .map(|e| match (cut.contains(&g.src(e)), cut.contains(&g.snk(e))) {
+                (true, false) => data[g.edge_id(e)].1,
+                (false, true) => -data[g.edge_id(e)].0,
+                _ => 0,
+            })
+            .sum::<isize>();
+        assert!(supply > cap, "supply: {} capacity: {}", supply, cap);
+    }
+
+    #[test]
+    fn test_infeasible_cut() {
+        let (g, data) = network(
+            4,
+            &[(0, 1, 0, 3, 1), (1, 2, 0, 10, 1), (0, 3, 0, 1, 1), (3, 2, 0, 10, 1)],
+        );
+        let balances = [5, 0, -5, 0];
+        let spx = solve(&g, &balances, &data);
+        assert_eq!(spx.solution_state(), SolutionState::Infeasible);
+        assert_eq!(spx.unbounded_cycle(), None);
+        match spx.infeasibility() {
+            Some(Infeasibility::Cut(cut)) => {
+                assert_eq!(cut, vec![g.id2node(0)]);
+                check_cut(&g, &balances, &data, &cut);
+            }
+            cert => panic!("Unexpected certificate: {:?}", cert.is_some()),
+        }
+    }
+
+    #[test]
+    fn test_infeasible_lower_bounds() {
+        let (g, data) = network(3, &[(0, 1, 5, 10, 1), (1, 2, 0, 2, 1), (2, 0, 0, 10, 1)]);
+        let balances = [0, 0, 0];
+        let spx = solve(&g, &balances, &data);
+        assert_eq!(spx.solution_state(), SolutionState::Infeasible);
+        match spx.infeasibility() {
+            Some(Infeasibility::Cut(cut)) => check_cut(&g, &balances, &data, &cut),
+            cert => panic!("Unexpected certificate: {:?}", cert.is_some()),
+        }
+    }
+
+    #[test]
+    fn test_infeasible_bounds() {
+        let (g, data) = network(2, &[(0, 1, 0, 3, 1), (0, 1, 4, 2, 1)]);
+        let spx = solve(&g, &[0, 0], &data);
+        assert_eq!(spx.solution_state(), SolutionState::Infeasible);
+        assert_eq!(spx.infeasibility(), Some(Infeasibility::Bounds(g.id2edge(1))));
+    }
+
+    #[test]
+    fn test_unbounded_cycle() {
+        let inf = isize::MAX;
+        let (g, data) = network(
+            5,
+            &[
+                (0, 1, 0, 5, 1),
+                (1, 2, 0, inf, 2),
+                (2, 3, 0, inf, -4),
+                (3, 1, 0, inf, 1),
+                (3, 4, 0, 5, 1),
+                (2, 1, 0, 7, -5),
+            ],
+        );
+        let spx = solve(&g, &[5, 0, 0, 0, -5], &data);
+        assert_eq!(spx.solution_state(), SolutionState::Unbounded);
+        assert_eq!(spx.infeasibility(), None);
+
+        let cycle = spx.unbounded_cycle().unwrap();
+        assert_eq!(cycle.len(), 3);
+        assert!(cycle.iter().map(|&e| data[g.edge_id(e)].2).sum::<isize>() < 0);
+        for (i, &e) in cycle.iter().enumerate() {
+            assert_eq!(data[g.edge_id(e)].1, inf);
+            assert_eq!(g.snk(e), g.src(cycle[(i + 1) % cycle.len()]));
+        }
+    }
+
+    #[test]
+    fn test_feasible_no_certificate() {
+        let (g, data) = network(3, &[(0, 1, 0, 5, 1), (1, 2, 0, 5, 1)]);
+        let spx = solve(&g, &[3, 0, -3], &data);
+        assert_eq!(spx.solution_state(), SolutionState::Optimal);
+        assert_eq!(spx.value(), 6);
+        assert_eq!(spx.infeasibility(), None);
+        assert_eq!(spx.unbounded_cycle(), None);
+    }
+}
@@ -0,0 +1,331 @@
+// Copyright (c) 2022 Frank Fischer <frank-fischer@shadow-soft.de>
+//
+// This program is free software: you can redistribute it and/or
+// modify it under the terms of the GNU General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see  <http://www.gnu.org/licenses/>
+//
+
+//! Gomory-Hu trees for all-pairs minimum cuts.
+//!
+//! A Gomory-Hu tree of an undirected network is a tree on the nodes of
+//! the network such that for each pair of nodes `u` and `v` the value
+//! of a minimal `u`-`v`-cut equals the smallest value of an edge on
+//! the path from `u` to `v` in the tree. Furthermore, removing this
+//! edge from the tree splits the nodes into the two sides of a minimal
+//! cut.
+//!
+//! The tree is computed with Gusfield's algorithm, which requires
+//! `n-1` maximum flow computations on the original network (and no
+//! contractions).
+//!
+//! # Example
+//!
+//! ```
+//! use rs_graph::{Buildable, Builder, Net};
+//! use rs_graph::mincut::gomory_hu;
+//! use rs_graph::traits::*;
+//!
+//! let g = Net::new_with(|b| {
+//!     let nodes = b.add_nodes(6);
+//!     for &(u, v) in &[(0, 1), (0, 2), (1, 2), (1, 3), (1, 4), (2, 4), (3, 4), (3, 5), (4, 5)] {
+//!         b.add_edge(nodes[u], nodes[v]);
+//!     }
+//! });
+//! let upper = [1, 7, 1, 3, 2, 4, 1, 6, 2];
+//!
+//! let tree = gomory_hu(&g, |e| upper[g.edge_id(e)]);
+//!
+//! // the tree has n-1 edges
+//! assert_eq!(tree.edges().count(), 5);
+//!
+//! let (u, v) = (g.id2node(0), g.id2node(5));
+//! assert_eq!(tree.min_cut(u, v), 6);
+//!
+//! // the value of the returned partition equals the min cut
+//! let cut = tree.min_cut_nodes(u, v);
+//! assert!(cut.contains(&u) && !cut.contains(&v));
+//! let value: i32 = g
+//!     .edges()
+//!     .filter(|&e| cut.contains(&g.src(e)) != cut.contains(&g.snk(e)))
+//!     .map(|e| upper[g.edge_id(e)])
+//!     .sum();
+//! assert_eq!(value, 6);
+//! ```
+
+use crate::builder::{Buildable, Builder};
+use crate::maxflow::Dinic;
+use crate::traits::IndexGraph;
+use crate::Net;
+
+use crate::num::traits::NumAssign;
+
+/// A Gomory-Hu tree.
+///
+/// The tree is rooted at the node with index 0. Each other node
+/// stores its parent in the tree and the value of the tree edge to its
+/// parent.
+pub struct GomoryHuTree<'a, G, F>
+where
+    G: IndexGraph,
+{
+    g: &'a G,
+    /// The parent and the value of the edge to the parent of each node.
+    parent: Vec<Option<(usize, F)>>,
+    /// The depth of each node in the tree.
+    depth: Vec<usize>,
+}
+
+impl<'a, G, F> GomoryHuTree<'a, G, F>
+where
+    G: IndexGraph,
+    F: NumAssign + Ord + Copy,
+{
+    /// Return the underlying graph.
+    pub fn as_graph(&self) -> &'a G {
+        self.g
+    }
+
+    /// Return the parent of `u` in the tree and the value of the tree edge.
+    ///
+    /// Returns `None` if `u` is the root of the tree.
+    pub fn parent(&self, u: G::Node<'_>) -> Option<(G::Node<'a>, F)> {
+        self.parent[self.g.node_id(u)].map(|(p, value)| (self.g.id2node(p), value))
+    }
+
+    /// Return an iterator over all tree edges.
+    ///
+    /// Each tree edge is returned as triple (node, parent, value).
+    pub fn edges<'b>(&'b self) -> impl Iterator<Item = (G::Node<'a>, G::Node<'a>, F)> + 'b {
+        self.parent
+            .iter()
+            .enumerate()
+            .filter_map(move |(u, p)| p.map(|(p, value)| (self.g.id2node(u), self.g.id2node(p), value)))
+    }
+
+    /// Return the value of a minimal cut separating `u` and `v`.
+    pub fn min_cut(&self, u: G::Node<'_>, v: G::Node<'_>) -> F {
+        let w = self.min_edge(self.g.node_id(u), self.g.node_id(v));
+        self.parent[w].unwrap().1
+    }
+
+    /// Return the nodes on the side of `u` of a minimal cut separating `u` and `v`.
+    pub fn min_cut_nodes(&self, u: G::Node<'_>, v: G::Node<'_>) -> Vec<G::Node<'a>> {
+        let uid = self.g.node_id(u);
+        let w = self.min_edge(uid, self.g.node_id(v));
+
+        // compute the subtree rooted at w, it is the side of the cut containing w
+        let n = self.g.num_nodes();
+        let mut insub = vec![None; n];
+        insub[w] = Some(true);
+        for x in 0..n {
+            let mut y = x;
+            while insub[y].is_none() {
+                match self.parent[y] {
+                    Some((p, _)) => y = p,
+                    None => insub[y] = Some(false),
+                }
+            }
+            let side = insub[y];
+            let mut y = x;
+            while insub[y].is_none() {
+                insub[y] = side;
+                y = self.parent[y].unwrap().0;
+            }
+        }
+
+        let uside = insub[uid];
+        (0..n)
+            .filter(|&x| insub[x] == uside)
+            .map(|x| self.g.id2node(x))
+            .collect()
+    }
+
+    /// Return the node whose tree edge to its parent has the smallest
+    /// value on the tree path between `u` and `v`.
+    fn min_edge(&self, mut u: usize, mut v: usize) -> usize {
+        assert_ne!(u, v, "Nodes must not be equal");
+        let mut best: Option<(usize, F)> = None;
+        while u != v {
+            // move up the deeper node
+            if self.depth[u] < self.depth[v] {
+                std::mem::swap(&mut u, &mut v);
+            }
+            let (p, value) = self.parent[u].unwrap();
+            if best.map(|(_, bestvalue)| value < bestvalue).unwrap_or(true) {
+                best = Some((u, value));
+            }
+            u = p;
+        }
+        best.unwrap().0
+    }
+}
+
+/// Compute a Gomory-Hu tree of an undirected network.
+///
+/// The capacity of each (undirected) edge is given by `upper`. The
+/// tree is computed by Gusfield's algorithm using `n-1` computations
+/// of maximum flows with [`Dinic`].
+pub fn gomory_hu<'a, G, F, Us>(g: &'a G, upper: Us) -> GomoryHuTree<'a, G, F>
+where
+    G: IndexGraph,
+    F: NumAssign + Ord + Copy,
+    Us: Fn(G::Edge<'a>) -> F,
+{
+    let n = g.num_nodes();
+
+    // the network with two opposite arcs for each undirected edge
+    let mut caps = Vec::with_capacity(2 * g.num_edges());
+    let net = Net::new_with(|b| {
+        let nodes = b.add_nodes(n);
+        for e in g.edges() {
+            let (u, v) = g.enodes(e);
+            let (u, v) = (nodes[g.node_id(u)], nodes[g.node_id(v)]);
+            let cap = upper(e);
+            b.add_edge(u, v);
+            b.add_edge(v, u);
+            caps.push(cap);
+            caps.push(cap);
+        }
+    });
+
+    let mut parent = vec![0; n];
+    let mut values = vec![F::zero(); n];
+    let mut incut = vec![false; n];
+    let mut maxflow = Dinic::new(&net);
+    for s in 1..n {
+        let t = parent[s];
+        maxflow.solve(net.id2node(s), net.id2node(t), |e| caps[net.edge_id(e)]);
+        for x in incut.iter_mut() {
+            *x = false;
+        }
+        for u in maxflow.mincut() {
+            incut[net.node_id(u)] = true;
+        }
+
+        values[s] = maxflow.value();
+        for i in 0..n {
+            if i != s && incut[i] && parent[i] == t {
+                parent[i] = s;
+            }
+        }
+        if incut[parent[t]] {
+            parent[s] = parent[t];
+            parent[t] = s;
+            values.swap(s, t);
+        }
+    }
+
+    // the root of the tree is the unique node being its own parent
+    let parent: Vec<_> = (0..n)
+        .map(|u| {
+            if parent[u] == u {
+                None
+            } else {
+                Some((parent[u], values[u]))
+            }
+        })
+        .collect();
+
+    let mut depth = vec![None; n];
+    for u in 0..n {
+        let mut v = u;
+        let mut d = 0;
+        while depth[v].is_none() {
+            match parent[v] {
+                Some((p, _)) => {
+                    v = p;
+                    d += 1;
+                }
+                None => {
+                    depth[v] = Some(0);
+                    break;
+                }
+            }
+        }
+        let mut d = depth[v].unwrap() + d;
+        let mut v = u;
+        while depth[v].is_none() {
+            depth[v] = Some(d);
+            d -= 1;
+            v = parent[v].unwrap().0;
+        }
+    }
+
+    GomoryHuTree {
+        g,
+        parent,
+        depth: depth.into_iter().map(Option::unwrap).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::gomory_hu;
+    use crate::maxflow::dinic;
+    use crate::traits::*;
+    use crate::{Buildable, Builder, Net};
+
+    #[test]
+    fn test_all_pairs() {
+        let n = 12;
+        let mut next = crate::testutil::lcg(42);
+        let mut upper = vec![];
+        let g = Net::new_with(|b| {
+            let nodes = b.add_nodes(n);
+            for u in 0..n {
+                for v in u + 1..n {
+                    if next(3) == 0 {
+                        b.add_edge(nodes[u], nodes[v]);
+                        upper.push(next(10) as i32 + 1);
+                    }
+                }
+            }
+        });
+
+        // the directed network with two opposite arcs per edge
+        let net = Net::new_with(|b| {
+            let nodes = b.add_nodes(n);
+            for e in g.edges() {
+                let (u, v) = (nodes[g.node_id(g.src(e))], nodes[g.node_id(g.snk(e))]);
+                b.add_edge(u, v);
+                b.add_edge(v, u);
+            }
+        });
+
+        let tree = gomory_hu(&g, |e| upper[g.edge_id(e)]);
+        assert_eq!(tree.edges().count(), n - 1);
+
+        for u in g.nodes() {
+            for v in g.nodes() {
+                if u == v {
+                    continue;
+                }
+                let (value, _, _) = dinic(&net, u, v, |e| upper[net.edge_id(e) / 2]);
+                assert_eq!(tree.min_cut(u, v), value);
+
+                let cut = tree.min_cut_nodes(u, v);
+                let mut incut = vec![false; n];
+                for &w in &cut {
+                    incut[g.node_id(w)] = true;
+                }
+                assert!(incut[g.node_id(u)]);
+                assert!(!incut[g.node_id(v)]);
+                let cutvalue: i32 = g
+                    .edges()
+                    .filter(|&e| incut[g.node_id(g.src(e))] != incut[g.node_id(g.snk(e))])
+                    .map(|e| upper[g.edge_id(e)])
+                    .sum();
+                assert_eq!(cutvalue, value);
+            }
+        }
+    }
+}
@@ -0,0 +1,217 @@
+// Copyright (c) 2022 Frank Fischer <frank-fischer@shadow-soft.de>
+//
+// This program is free software: you can redistribute it and/or
+// modify it under the terms of the GNU General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see  <http://www.gnu.org/licenses/>
+//
+
+//! Implementation of the Stoer-Wagner algorithm
+
+use crate::traits::IndexGraph;
+
+use crate::num::traits::NumAssign;
+
+use std::collections::BinaryHeap;
+
+/// Run the Stoer-Wagner algorithm to solve the *Global Minimum Cut*
+/// problem on a graph.
+///
+/// * `g` is the undirected graph `weights` the edge weights
+///
+/// The weights must be non-negative. The function returns the value
+/// of a minimal cut and the nodes on one side of this cut. The graph
+/// must have at least two nodes. If the graph is not connected, the
+/// returned cut has value zero.
+///
+/// # Example
+///
+/// ```
+/// use rs_graph::{Net, traits::*};
+/// use rs_graph::mincut::stoer_wagner;
+/// use rs_graph::string::{Data, from_ascii};
+///
+/// let Data { graph, weights, nodes } = from_ascii::<Net>(r"
+///     a-2-b-3-c-4-d
+///     |  /|   |  /|
+///     3 2 2   2 2 2
+///     |/  |   |/  |
+///     e-3-f-1-g-3-h
+///     ").unwrap();
+///
+/// // run the algorithm
+/// let (value, cut) = stoer_wagner(&graph, |e| weights[graph.edge_id(e)]);
+///
+/// // check the results
+/// assert_eq!(value, 4);
+///
+/// let mut cut = cut.into_iter().map(|u| graph.node_id(u)).collect::<Vec<_>>();
+/// cut.sort();
+/// let side = |s: &str| {
+///     let mut side = s.chars().map(|c| nodes[&c]).collect::<Vec<_>>();
+///     side.sort();
+///     side
+/// };
+/// assert!(cut == side("abef") || cut == side("cdgh"));
+/// ```
+pub fn stoer_wagner<'a, G, W, F>(g: &'a G, weights: F) -> (W, Vec<G::Node<'a>>)
+where
+    G: IndexGraph,
+    W: NumAssign + Ord + Copy,
+    F: Fn(G::Edge<'a>) -> W,
+{
+    let n = g.num_nodes();
+    assert!(n >= 2, "The graph must have at least two nodes");
+
+    // adjacency lists of the (merged) nodes
+    let mut adj = vec![vec![]; n];
+    for e in g.edges() {
+        let (u, v) = g.enodes(e);
+        let (u, v) = (g.node_id(u), g.node_id(v));
+        if u != v {
+            let w = weights(e);
+            adj[u].push((v, w));
+            adj[v].push((u, w));
+        }
+    }
+
+    // the original nodes contained in each merged node
+    let mut members: Vec<Vec<usize>> = (0..n).map(|u| vec![u]).collect();
+    let mut active: Vec<usize> = (0..n).collect();
+
+    let mut best: Option<(W, Vec<usize>)> = None;
+    let mut conn = vec![W::zero(); n];
+    let mut added = vec![false; n];
+    let mut heap = BinaryHeap::with_capacity(n);
+
+    while active.len() > 1 {
+        // find the most tightly connected order of the nodes
+        for &u in &active {
+            conn[u] = W::zero();
+            added[u] = false;
+            heap.push((W::zero(), u));
+        }
+        let mut s = active[0];
+        let mut t = active[0];
+        while let Some((w, u)) = heap.pop() {
+            if added[u] || w != conn[u] {
+                continue;
+            }
+            added[u] = true;
+            s = t;
+            t = u;
+            for &(v, c) in &adj[u] {
+                if !added[v] {
+                    conn[v] += c;
+                    heap.push((conn[v], v));
+                }
+            }
+        }
+
+        // the cut of the phase separates t from all other nodes
+        if best.as_ref().map(|(value, _)| conn[t] < *value).unwrap_or(true) {
+            best = Some((conn[t], members[t].clone()));
+        }
+
+        // merge t into s
+        let tmembers = std::mem::take(&mut members[t]);
+        members[s].extend(tmembers);
+        active.retain(|&u| u != t);
+        let tadj = std::mem::take(&mut adj[t]);
+        for (v, c) in tadj {
+            if v != s {
+                adj[s].push((v, c));
+            }
+        }
+        adj[s].retain(|&(v, _)| v != t);
+
+        // update the adjacency lists of the neighbors of t
+        for &u in &active {
+            if u == s {
+                continue;
+            }
+            let mut ws = W::zero();
+            let mut found = false;
+            adj[u].retain(|&(v, c)| {
+                if v == t || v == s {
+                    ws += c;
+                    found = true;
+                    false
+                } else {
+                    true
+                }
+            });
+            if found {
+                adj[u].push((s, ws));
+            }
+        }
+
+        // combine parallel edges of s
+        adj[s].sort_by_key(|&(v, _)| v);
+        let mut combined: Vec<(usize, W)> = Vec::with_capacity(adj[s].len());
+        for &(v, c) in &adj[s] {
+            match combined.last_mut() {
+                Some((last, lastc)) if *last == v => *lastc += c,
+                _ => combined.push((v, c)),
+            }
+        }
+        adj[s] = combined;
+    }
+
+    let (value, side) = best.unwrap();
+    (value, side.into_iter().map(|u| g.id2node(u)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::stoer_wagner;
+    use crate::mincut::gomory_hu;
+    use crate::traits::*;
+    use crate::{Buildable, Builder, Net};
+
+    #[test]
+    fn test_random() {
+        let mut next = crate::testutil::lcg(17);
+
+        for n in 2..20 {
+            let mut weights = vec![];
+            let g = Net::new_with(|b| {
+                let nodes = b.add_nodes(n);
+                for u in 0..n {
+                    for v in u + 1..n {
+                        if next(3) == 0 {
+                            b.add_edge(nodes[u], nodes[v]);
+                            weights.push(next(10) as i32);
+                        }
+                    }
+                }
+            });
+
+            let (value, cut) = stoer_wagner(&g, |e| weights[g.edge_id(e)]);
+            assert!(!cut.is_empty() && cut.len() < n);
+
+            let mut incut = vec![false; n];
+            for &u in &cut {
+                incut[g.node_id(u)] = true;
+            }
+            let cutvalue: i32 = g
+                .edges()
+                .filter(|&e| incut[g.node_id(g.src(e))] != incut[g.node_id(g.snk(e))])
+                .map(|e| weights[g.edge_id(e)])
+                .sum();
+            assert_eq!(cutvalue, value);
+
+            // the global minimal cut is the smallest edge of a Gomory-Hu tree
+            let tree = gomory_hu(&g, |e| weights[g.edge_id(e)]);
+            assert_eq!(tree.edges().map(|(_, _, w)| w).min().unwrap(), value);
+        }
+    }
+}
@@ -0,0 +1,29 @@
+// Copyright (c) 2022 Frank Fischer <frank-fischer@shadow-soft.de>
+//
+// This program is free software: you can redistribute it and/or
+// modify it under the terms of the GNU General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see  <http://www.gnu.org/licenses/>
+//
+
+//! Helpers for the unit tests.
+
+/// Return a deterministic pseudo-random number generator.
+///
+/// The returned closure `next` is a linear congruential generator,
+/// `next(max)` returns a number in `0..max`.
+pub fn lcg(seed: u64) -> impl FnMut(u64) -> u64 {
+    let mut rnd = seed;
+    move |max| {
+        rnd = rnd.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (rnd >> 33) % max
+    }
+}
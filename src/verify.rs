@@ -0,0 +1,476 @@
+// Copyright (c) 2022 Frank Fischer <frank-fischer@shadow-soft.de>
+//
+// This program is free software: you can redistribute it and/or
+// modify it under the terms of the GNU General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see  <http://www.gnu.org/licenses/>
+//
+
+//! Independent checks of the results of optimization algorithms.
+//!
+//! The functions in this module verify the optimality of a solution
+//! by checking feasibility and an optimality certificate:
+//!
+//! - [`check_maxflow`]: a maximum flow is certified by a cut whose
+//!   capacity equals the flow value,
+//! - [`check_mcf`]: a minimum cost flow is certified by node potentials
+//!   satisfying complementary slackness,
+//! - [`check_mst`]: a minimum spanning tree (or forest) is certified by
+//!   the cycle optimality condition.
+//!
+//! Each function returns the first violated condition as a
+//! [`Violation`] naming the offending node or edge.
+//!
+//! # Example
+//!
+//! ```
+//! use rs_graph::{Buildable, Builder, Net};
+//! use rs_graph::maxflow::{Dinic, MaxFlow};
+//! use rs_graph::verify::{check_maxflow, Violation};
+//! use rs_graph::traits::*;
+//!
+//! let g = Net::new_with(|b| {
+//!     let nodes = b.add_nodes(4);
+//!     b.add_edge(nodes[0], nodes[1]);
+//!     b.add_edge(nodes[0], nodes[2]);
+//!     b.add_edge(nodes[1], nodes[2]);
+//!     b.add_edge(nodes[1], nodes[3]);
+//!     b.add_edge(nodes[2], nodes[3]);
+//! });
+//! let upper = [4, 2, 3, 1, 5];
+//! let (s, t) = (g.id2node(0), g.id2node(3));
+//!
+//! let mut maxflow = Dinic::new(&g);
+//! maxflow.solve(s, t, |e| upper[g.edge_id(e)]);
+//! assert_eq!(check_maxflow(&maxflow, s, t, |e| upper[g.edge_id(e)]), Ok(()));
+//!
+//! // the flow is not feasible for smaller capacities
+//! let upper = [4, 2, 3, 1, 4];
+//! assert_eq!(
+//!     check_maxflow(&maxflow, s, t, |e| upper[g.edge_id(e)]),
+//!     Err(Violation::UpperBound(g.id2edge(4)))
+//! );
+//! ```
+
+use crate::maxflow::MaxFlow;
+use crate::mcf::MinCostFlow;
+use crate::traits::{FiniteDigraph, FiniteGraph, GraphType, IndexDigraph, IndexGraph};
+
+use crate::num::traits::{NumAssign, Zero};
+
+use std::collections::VecDeque;
+use std::fmt;
+
+/// A violated feasibility or optimality condition.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Violation<N, E> {
+    /// The flow on an edge is smaller than its lower bound (or negative).
+    LowerBound(E),
+    /// The flow on an edge exceeds its upper bound.
+    UpperBound(E),
+    /// The net outflow of a node differs from its balance.
+    ///
+    /// For a maximum flow this means that flow conservation is violated
+    /// at a node other than the source and the sink.
+    Balance(N),
+    /// The reported objective value differs from the value of the solution.
+    Value,
+    /// The source is not contained in the cut or the sink is contained
+    /// in the cut.
+    CutSide(N),
+    /// An edge leaving the cut is not saturated or an edge entering
+    /// the cut carries flow.
+    ///
+    /// In this case the capacity of the cut is larger than the flow
+    /// value.
+    CutEdge(E),
+    /// The flow on an edge violates complementary slackness.
+    ///
+    /// The reduced cost of the edge is positive but the flow is larger
+    /// than the lower bound or the reduced cost is negative but the
+    /// flow is smaller than the upper bound.
+    ComplementarySlackness(E),
+    /// A tree edge closes a cycle with other tree edges.
+    TreeCycle(E),
+    /// A non-tree edge connects two components of the spanning forest.
+    NotSpanning(E),
+    /// A non-tree edge (first) is cheaper than a tree edge (second) on
+    /// the cycle it closes with the tree.
+    CycleOptimality(E, E),
+}
+
+impl<N, E> fmt::Display for Violation<N, E>
+where
+    N: fmt::Debug,
+    E: fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        use self::Violation::*;
+        match self {
+            LowerBound(e) => write!(fmt, "Flow on edge {:?} below lower bound", e),
+            UpperBound(e) => write!(fmt, "Flow on edge {:?} above upper bound", e),
+            Balance(u) => write!(fmt, "Balance of node {:?} violated", u),
+            Value => write!(fmt, "Wrong objective value"),
+            CutSide(u) => write!(fmt, "Node {:?} on wrong side of the cut", u),
+            CutEdge(e) => write!(fmt, "Flow on cut edge {:?} not at its bound", e),
+            ComplementarySlackness(e) => write!(fmt, "Complementary slackness violated on edge {:?}", e),
+            TreeCycle(e) => write!(fmt, "Tree edge {:?} closes a cycle", e),
+            NotSpanning(e) => write!(fmt, "Edge {:?} connects two tree components", e),
+            CycleOptimality(e, f) => write!(fmt, "Edge {:?} is cheaper than tree edge {:?}", e, f),
+        }
+    }
+}
+
+impl<N, E> std::error::Error for Violation<N, E>
+where
+    N: fmt::Debug,
+    E: fmt::Debug,
+{
+}
+
+/// A violation for the graph of the max-flow algorithm `M`.
+type MaxFlowViolation<'a, M> =
+    Violation<<<M as MaxFlow<'a>>::Graph as GraphType>::Node<'a>, <<M as MaxFlow<'a>>::Graph as GraphType>::Edge<'a>>;
+
+/// Check the result of a maximum flow algorithm.
+///
+/// The flow of `maxflow` must satisfy the capacities `upper` and flow
+/// conservation at all nodes except `src` and `snk`, the flow value
+/// must be the net inflow of `snk`, and the flow value must equal the
+/// capacity of the returned minimal cut (i.e. all edges leaving the
+/// cut are saturated and all edges entering the cut carry no flow).
+pub fn check_maxflow<'a, M, Us>(
+    maxflow: &M,
+    src: <M::Graph as GraphType>::Node<'_>,
+    snk: <M::Graph as GraphType>::Node<'_>,
+    upper: Us,
+) -> Result<(), MaxFlowViolation<'a, M>>
+where
+    M: MaxFlow<'a>,
+    Us: Fn(<M::Graph as GraphType>::Edge<'a>) -> M::Flow,
+{
+    let g = maxflow.as_graph();
+    let src = g.node_id(src);
+    let snk = g.node_id(snk);
+    let zero = M::Flow::zero();
+
+    let mut outflow = vec![zero; g.num_nodes()];
+    let mut inflow = vec![zero; g.num_nodes()];
+    for e in g.edges() {
+        let flw = maxflow.flow(e);
+        if flw < zero {
+            return Err(Violation::LowerBound(e));
+        }
+        if flw > upper(e) {
+            return Err(Violation::UpperBound(e));
+        }
+        outflow[g.node_id(g.src(e))] += flw;
+        inflow[g.node_id(g.snk(e))] += flw;
+    }
+
+    for u in g.nodes() {
+        let uid = g.node_id(u);
+        if uid != src && uid != snk && outflow[uid] != inflow[uid] {
+            return Err(Violation::Balance(u));
+        }
+    }
+    if inflow[snk] < outflow[snk] || inflow[snk] - outflow[snk] != maxflow.value() {
+        return Err(Violation::Value);
+    }
+
+    let mut incut = vec![false; g.num_nodes()];
+    for u in maxflow.mincut() {
+        incut[g.node_id(u)] = true;
+    }
+    if !incut[src] {
+        return Err(Violation::CutSide(g.id2node(src)));
+    }
+    if incut[snk] {
+        return Err(Violation::CutSide(g.id2node(snk)));
+    }
+    for e in g.edges() {
+        let (u, v) = (g.node_id(g.src(e)), g.node_id(g.snk(e)));
+        let flw = maxflow.flow(e);
+        if (incut[u] && !incut[v] && flw != upper(e)) || (!incut[u] && incut[v] && flw != zero) {
+            return Err(Violation::CutEdge(e));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check the result of a min-cost-flow algorithm.
+///
+/// The flow of `mcf` must satisfy the bounds and the balances (the net
+/// outflow of each node equals its balance), the reported value must be
+/// the cost of the flow and the potentials of `mcf` must satisfy
+/// complementary slackness: an edge `e = (u,v)` with positive reduced
+/// cost `cost(e) + potential(u) - potential(v)` must be at its lower
+/// bound, an edge with negative reduced cost at its upper bound.
+pub fn check_mcf<'a, G, F, S>(mcf: &'a S) -> Result<(), Violation<G::Node<'a>, G::Edge<'a>>>
+where
+    G: 'a + IndexDigraph,
+    F: NumAssign + Ord + Copy,
+    S: MinCostFlow<G, F>,
+{
+    let g = mcf.as_graph();
+    let zero = F::zero();
+
+    let mut excess = vec![zero; g.num_nodes()];
+    let mut deficit = vec![zero; g.num_nodes()];
+    let mut value = zero;
+    for e in g.edges() {
+        let flw = mcf.flow(e);
+        if flw < mcf.lower(e) {
+            return Err(Violation::LowerBound(e));
+        }
+        if flw > mcf.upper(e) {
+            return Err(Violation::UpperBound(e));
+        }
+        // split the flow so that unsigned types work, too
+        deficit[g.node_id(g.src(e))] += flw;
+        excess[g.node_id(g.snk(e))] += flw;
+        value += flw * mcf.cost(e);
+    }
+
+    for u in g.nodes() {
+        let uid = g.node_id(u);
+        if excess[uid] + mcf.balance(u) != deficit[uid] {
+            return Err(Violation::Balance(u));
+        }
+    }
+    if value != mcf.value() {
+        return Err(Violation::Value);
+    }
+
+    for e in g.edges() {
+        let (u, v) = g.enodes(e);
+        let (pu, pv) = (mcf.potential(u), mcf.potential(v));
+        let flw = mcf.flow(e);
+        // compare cost(e) + pu with pv to avoid negative numbers
+        let c = mcf.cost(e) + pu;
+        if (c > pv && flw != mcf.lower(e)) || (c < pv && flw != mcf.upper(e)) {
+            return Err(Violation::ComplementarySlackness(e));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check a minimum spanning tree.
+///
+/// The edges `tree` must form a spanning forest of `g`, i.e. they do
+/// not contain a cycle and each edge of `g` connects two nodes of the
+/// same tree. Furthermore, the forest must satisfy the cycle optimality
+/// condition: each non-tree edge is at least as expensive as each tree
+/// edge on the cycle it closes with the tree.
+///
+/// Note that [`crate::mst::prim`] spans only the component of the first
+/// node. Hence its result only passes the check on connected graphs.
+pub fn check_mst<'a, G, W, Ws>(
+    g: &'a G,
+    weights: Ws,
+    tree: &[G::Edge<'a>],
+) -> Result<(), Violation<G::Node<'a>, G::Edge<'a>>>
+where
+    G: IndexGraph,
+    W: Ord + Copy,
+    Ws: Fn(G::Edge<'a>) -> W,
+{
+    let n = g.num_nodes();
+
+    // check that the tree edges form a forest with a union-find structure
+    let mut comps: Vec<usize> = (0..n).collect();
+    fn find(comps: &mut [usize], mut u: usize) -> usize {
+        while comps[u] != u {
+            comps[u] = comps[comps[u]];
+            u = comps[u];
+        }
+        u
+    }
+    let mut intree = vec![false; g.num_edges()];
+    let mut adj = vec![vec![]; n];
+    for &e in tree {
+        let (u, v) = g.enodes(e);
+        let (u, v) = (g.node_id(u), g.node_id(v));
+        let (ru, rv) = (find(&mut comps, u), find(&mut comps, v));
+        if ru == rv {
+            return Err(Violation::TreeCycle(e));
+        }
+        comps[ru] = rv;
+        intree[g.edge_id(e)] = true;
+        adj[u].push((e, v));
+        adj[v].push((e, u));
+    }
+
+    // root each tree and compute the parent edges and depths
+    let mut parent = vec![None; n];
+    let mut depth = vec![usize::MAX; n];
+    let mut queue = VecDeque::new();
+    for r in 0..n {
+        if depth[r] != usize::MAX {
+            continue;
+        }
+        depth[r] = 0;
+        queue.push_back(r);
+        while let Some(u) = queue.pop_front() {
+            for &(e, v) in &adj[u] {
+                if depth[v] == usize::MAX {
+                    depth[v] = depth[u] + 1;
+                    parent[v] = Some((e, u));
+                    queue.push_back(v);
+                }
+            }
+        }
+    }
+
+    for e in g.edges() {
+        if intree[g.edge_id(e)] {
+            continue;
+        }
+        let (u, v) = g.enodes(e);
+        let (mut u, mut v) = (g.node_id(u), g.node_id(v));
+        if find(&mut comps, u) != find(&mut comps, v) {
+            return Err(Violation::NotSpanning(e));
+        }
+
+        // find the most expensive tree edge on the path from u to v
+        let w = weights(e);
+        while u != v {
+            if depth[u] < depth[v] {
+                std::mem::swap(&mut u, &mut v);
+            }
+            let (f, p) = parent[u].unwrap();
+            if weights(f) > w {
+                return Err(Violation::CycleOptimality(e, f));
+            }
+            u = p;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_maxflow, check_mcf, check_mst, Violation};
+    use crate::maxflow::{Dinic, PushRelabel};
+    use crate::mcf::{NetworkSimplex, SolutionState};
+    use crate::mst::{kruskal, prim, worstout};
+    use crate::traits::*;
+    use crate::{classes, Buildable, Builder, Net};
+
+    #[test]
+    fn test_maxflow() {
+        let g = Net::new_with(|b| {
+            let nodes = b.add_nodes(4);
+            for &(u, v) in &[(0, 1), (0, 2), (1, 2), (1, 3), (2, 3)] {
+                b.add_edge(nodes[u], nodes[v]);
+            }
+        });
+        let upper = [4, 2, 3, 1, 5];
+        let (s, t) = (g.id2node(0), g.id2node(3));
+
+        let mut maxflow = PushRelabel::new(&g);
+        maxflow.solve(s, t, |e| upper[g.edge_id(e)]);
+        assert_eq!(check_maxflow(&maxflow, s, t, |e| upper[g.edge_id(e)]), Ok(()));
+
+        // the cut is not minimal for larger capacities
+        let larger = [4, 2, 3, 2, 5];
+        assert_eq!(
+            check_maxflow(&maxflow, s, t, |e| larger[g.edge_id(e)]),
+            Err(Violation::CutEdge(g.id2edge(3)))
+        );
+
+        // source and sink swapped
+        assert_eq!(
+            check_maxflow(&maxflow, t, s, |e| upper[g.edge_id(e)]),
+            Err(Violation::Value)
+        );
+
+        // flow conservation at a node that is not the sink
+        let mut maxflow = Dinic::new(&g);
+        maxflow.solve(s, t, |e| upper[g.edge_id(e)]);
+        assert_eq!(
+            check_maxflow(&maxflow, s, g.id2node(2), |e| upper[g.edge_id(e)]),
+            Err(Violation::Balance(t))
+        );
+    }
+
+    #[test]
+    fn test_mcf() {
+        let g = Net::new_with(|b| {
+            let nodes = b.add_nodes(3);
+            b.add_edge(nodes[0], nodes[1]);
+            b.add_edge(nodes[1], nodes[2]);
+            b.add_edge(nodes[0], nodes[2]);
+        });
+        let mut mcf = NetworkSimplex::new(&g);
+        mcf.set_balances(|u| [2, 0, -2][g.node_id(u)]);
+        mcf.set_uppers(|_| 1);
+        mcf.set_costs(|e| [1, 1, 3][g.edge_id(e)]);
+        assert_eq!(mcf.solve(), SolutionState::Optimal);
+        assert_eq!(check_mcf(&mcf), Ok(()));
+
+        mcf.set_balance(g.id2node(1), 1);
+        assert_eq!(check_mcf(&mcf), Err(Violation::Balance(g.id2node(1))));
+        mcf.set_balance(g.id2node(1), 0);
+
+        mcf.set_upper(g.id2edge(2), 0);
+        assert_eq!(check_mcf(&mcf), Err(Violation::UpperBound(g.id2edge(2))));
+        mcf.set_upper(g.id2edge(2), 1);
+
+        // edge 1 carries flow but is too expensive now, so the
+        // potentials do not certify optimality anymore
+        mcf.set_cost(g.id2edge(1), 10);
+        assert_eq!(check_mcf(&mcf), Err(Violation::ComplementarySlackness(g.id2edge(1))));
+    }
+
+    #[test]
+    fn test_mst() {
+        let g: Net = classes::grid(4, 5);
+        let weights: Vec<usize> = (0..g.num_edges()).map(|i| (i * 7) % 11).collect();
+
+        for tree in &[
+            kruskal(&g, |e| weights[g.edge_id(e)]),
+            prim(&g, |e| weights[g.edge_id(e)]),
+            worstout(&g, |e| weights[g.edge_id(e)]),
+        ] {
+            assert_eq!(check_mst(&g, |e| weights[g.edge_id(e)], tree), Ok(()));
+        }
+
+        // remove an edge
+        let mut tree = kruskal(&g, |e| weights[g.edge_id(e)]);
+        tree.pop();
+        assert!(matches!(
+            check_mst(&g, |e| weights[g.edge_id(e)], &tree),
+            Err(Violation::NotSpanning(_))
+        ));
+
+        // add an additional edge
+        let mut tree = kruskal(&g, |e| weights[g.edge_id(e)]);
+        let other = g.edges().find(|e| !tree.contains(e)).unwrap();
+        tree.push(other);
+        assert_eq!(
+            check_mst(&g, |e| weights[g.edge_id(e)], &tree),
+            Err(Violation::TreeCycle(other))
+        );
+
+        // a triangle with the most expensive edge in the tree
+        let g: Net = classes::cycle(3);
+        let weights = [1, 2, 3];
+        let tree = vec![g.id2edge(0), g.id2edge(2)];
+        assert_eq!(
+            check_mst(&g, |e| weights[g.edge_id(e)], &tree),
+            Err(Violation::CycleOptimality(g.id2edge(1), g.id2edge(2)))
+        );
+    }
+}
@@ -16,9 +16,11 @@
  */
 
 use rs_graph::dimacs;
-use rs_graph::maxflow::{dinic, edmondskarp, PushRelabel};
-use rs_graph::traits::IndexGraph;
+use rs_graph::flowdecomposition::decompose_flow;
+use rs_graph::maxflow::{self, BoykovKolmogorov, Dinic, EdmondsKarp, MaxFlow, PushRelabel, Strategy};
+use rs_graph::traits::*;
 use rs_graph::LinkedListGraph;
+use rs_graph::{classes, Buildable, Builder};
 
 use num_traits::ToPrimitive;
 use std::error::Error;
@@ -30,63 +32,362 @@ const TESTS: &'static [(&'static str, i32, bool)] = &[
     ("tests/maxflow_test4.dat", 2344, true),
 ];
 
-#[test]
-fn test_edmondskarp() -> Result<(), Box<dyn Error>> {
-    for &(file, expected, hard) in TESTS {
-        if hard {
-            continue;
+/// A max-flow instance: the graph, the source, the sink and the capacities.
+type Instance = (LinkedListGraph, usize, usize, Vec<i32>);
+
+/// Read a max-flow instance.
+fn read_instance(file: &str) -> Result<Instance, Box<dyn Error>> {
+    let instance = dimacs::max::read_from_file(file)?;
+    let upper = instance.upper.iter().map(|u| u.to_i32().unwrap()).collect();
+    Ok((instance.graph, instance.src, instance.snk, upper))
+}
+
+/// Check the maximum flow computed by the algorithm `M`.
+///
+/// The flow must satisfy the capacities and flow conservation, and
+/// its value must equal the expected value and the capacity of the
+/// minimal cut.
+fn check_maxflow<'a, M>(maxflow: &M, src: usize, upper: &[i32], expected: i32, file: &str)
+where
+    M: MaxFlow<'a, Graph = LinkedListGraph, Flow = i32>,
+{
+    let g = maxflow.as_graph();
+    assert_eq!(maxflow.value(), expected, "Instance: {}", file);
+
+    let mut excess = vec![0; g.num_nodes()];
+    for (e, flw) in maxflow.flow_iter() {
+        assert!(0 <= flw && flw <= upper[g.edge_id(e)], "Instance: {}", file);
+        assert_eq!(flw, maxflow.flow(e));
+        excess[g.node_id(g.src(e))] -= flw;
+        excess[g.node_id(g.snk(e))] += flw;
+    }
+
+    let mincut = maxflow.mincut();
+    let mut incut = vec![false; g.num_nodes()];
+    for &u in &mincut {
+        incut[g.node_id(u)] = true;
+    }
+    assert!(incut[src], "Instance: {}", file);
+
+    for u in g.nodes() {
+        let uid = g.node_id(u);
+        if uid == src {
+            assert_eq!(excess[uid], -expected, "Instance: {}", file);
+        } else if excess[uid] > 0 {
+            // this must be the sink
+            assert!(!incut[uid]);
+            assert_eq!(excess[uid], expected, "Instance: {}", file);
+        } else {
+            assert_eq!(excess[uid], 0, "Instance: {}", file);
         }
-        let instance = dimacs::max::read_from_file(file)?;
-        let g: LinkedListGraph = instance.graph;
-        let s = g.id2node(instance.src);
-        let t = g.id2node(instance.snk);
-        let upper = instance.upper;
-
-        let (value, _, _) = edmondskarp(&g, s, t, |e| upper[g.edge_id(e)].to_i32().unwrap());
-        assert_eq!(value, expected);
     }
 
+    let cutvalue = g
+        .edges()
+        .filter(|&e| incut[g.node_id(g.src(e))] && !incut[g.node_id(g.snk(e))])
+        .map(|e| upper[g.edge_id(e)])
+        .sum::<i32>();
+    assert_eq!(cutvalue, expected, "Instance: {}", file);
+}
+
+/// Call `f` for all test instances.
+///
+/// The hard instances are skipped unless `hard` is `true`.
+fn for_each_instance<Fun>(hard: bool, mut f: Fun) -> Result<(), Box<dyn Error>>
+where
+    Fun: FnMut(&LinkedListGraph, usize, usize, &[i32], i32, &str),
+{
+    for &(file, expected, is_hard) in TESTS {
+        if is_hard && !hard {
+            continue;
+        }
+        let (g, src, snk, upper) = read_instance(file)?;
+        f(&g, src, snk, &upper, expected, file);
+    }
     Ok(())
 }
 
+/// Solve an instance with the algorithm `M` and check the result.
+fn run_maxflow<'a, M>(g: &'a LinkedListGraph, src: usize, snk: usize, upper: &[i32], expected: i32, file: &str)
+where
+    M: MaxFlow<'a, Graph = LinkedListGraph, Flow = i32>,
+{
+    let s = g.id2node(src);
+    let t = g.id2node(snk);
+
+    let mut maxflow = M::new(g);
+    maxflow.solve(s, t, |e| upper[g.edge_id(e)]);
+    check_maxflow(&maxflow, src, upper, expected, file);
+
+    let (value, flow, mincut) = maxflow::solve_with::<M>(g, s, t, |e| upper[g.edge_id(e)]);
+    assert_eq!(value, expected, "Instance: {}", file);
+    assert_eq!(flow, maxflow.flow_iter().collect::<Vec<_>>(), "Instance: {}", file);
+    assert_eq!(mincut, maxflow.mincut(), "Instance: {}", file);
+}
+
+#[test]
+fn test_edmondskarp() -> Result<(), Box<dyn Error>> {
+    for_each_instance(false, |g, src, snk, upper, expected, file| {
+        run_maxflow::<EdmondsKarp<_, _>>(g, src, snk, upper, expected, file)
+    })
+}
+
 #[test]
 fn test_dinic() -> Result<(), Box<dyn Error>> {
-    for &(file, expected, _) in TESTS {
-        let instance = dimacs::max::read_from_file(file)?;
-        let g: LinkedListGraph = instance.graph;
-        let s = g.id2node(instance.src);
-        let t = g.id2node(instance.snk);
-        let upper = instance.upper;
-
-        let (value, _, _) = dinic(&g, s, t, |e| upper[g.edge_id(e)].to_i32().unwrap());
-        assert_eq!(value, expected);
+    for_each_instance(true, |g, src, snk, upper, expected, file| {
+        run_maxflow::<Dinic<_, _>>(g, src, snk, upper, expected, file)
+    })
+}
+
+#[test]
+fn test_pushrelabel() -> Result<(), Box<dyn Error>> {
+    for_each_instance(true, |g, src, snk, upper, expected, file| {
+        run_maxflow::<PushRelabel<_, _>>(g, src, snk, upper, expected, file)
+    })
+}
+
+#[test]
+fn test_boykovkolmogorov() -> Result<(), Box<dyn Error>> {
+    for_each_instance(true, |g, src, snk, upper, expected, file| {
+        run_maxflow::<BoykovKolmogorov<_, _>>(g, src, snk, upper, expected, file)
+    })
+}
+
+#[test]
+fn test_pushrelabel_no_global_relabelling() -> Result<(), Box<dyn Error>> {
+    for_each_instance(true, |g, src, snk, upper, expected, file| {
+        let mut pr = PushRelabel::new(g);
+        pr.use_global_relabelling = false;
+        pr.solve(g.id2node(src), g.id2node(snk), |e| upper[g.edge_id(e)]);
+        check_maxflow(&pr, src, upper, expected, file);
+    })
+}
+
+#[test]
+fn test_pushrelabel_strategies() -> Result<(), Box<dyn Error>> {
+    for_each_instance(true, |g, src, snk, upper, expected, file| {
+        let s = g.id2node(src);
+        let t = g.id2node(snk);
+        let (_, _, mincut) = maxflow::pushrelabel(g, s, t, |e| upper[g.edge_id(e)]);
+        for &strategy in &[Strategy::HighestLabel, Strategy::Fifo, Strategy::ExcessScaling] {
+            for &freq in &[0.5, 1.0, 4.0] {
+                let mut pr = PushRelabel::new(g);
+                pr.strategy = strategy;
+                pr.global_relabelling_freq = freq;
+                pr.solve(s, t, |e| upper[g.edge_id(e)]);
+                check_maxflow(&pr, src, upper, expected, file);
+                assert_eq!(pr.mincut(), mincut, "Instance: {} ({:?})", file, strategy);
+            }
+        }
+    })
+}
+
+#[test]
+fn test_pushrelabel_mincut_only() -> Result<(), Box<dyn Error>> {
+    for_each_instance(true, |g, src, snk, upper, expected, file| {
+        let s = g.id2node(src);
+        let t = g.id2node(snk);
+        let (_, _, mincut) = maxflow::pushrelabel(g, s, t, |e| upper[g.edge_id(e)]);
+        for &strategy in &[Strategy::HighestLabel, Strategy::Fifo, Strategy::ExcessScaling] {
+            let mut pr = PushRelabel::new(g);
+            pr.strategy = strategy;
+            pr.compute_flow = false;
+            pr.solve(s, t, |e| upper[g.edge_id(e)]);
+            assert_eq!(pr.value(), expected, "Instance: {} ({:?})", file, strategy);
+            assert_eq!(pr.mincut(), mincut, "Instance: {} ({:?})", file, strategy);
+        }
+    })
+}
+
+/// Return modified capacities for the `round`-th warm start test.
+///
+/// Some capacities are decreased, some are increased.
+fn perturbed_upper(upper: &[i32], round: usize) -> Vec<i32> {
+    upper
+        .iter()
+        .enumerate()
+        .map(|(i, &u)| {
+            if (i + round) % 7 == 3 {
+                u / 2
+            } else if (i + round) % 11 == 5 {
+                u * 2 + 1
+            } else {
+                u
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn test_dinic_resolve() -> Result<(), Box<dyn Error>> {
+    for_each_instance(false, |g, src, snk, upper, expected, file| {
+        let s = g.id2node(src);
+        let t = g.id2node(snk);
+        let mut dinic = Dinic::new(g);
+        dinic.solve(s, t, |e| upper[g.edge_id(e)]);
+        check_maxflow(&dinic, src, upper, expected, file);
+
+        let mut upper = upper.to_vec();
+        for round in 0..5 {
+            upper = perturbed_upper(&upper, round);
+            let (value, _, mincut) = maxflow::dinic(g, s, t, |e| upper[g.edge_id(e)]);
+            dinic.resolve(s, t, |e| upper[g.edge_id(e)]);
+            check_maxflow(&dinic, src, &upper, value, file);
+            assert_eq!(dinic.mincut(), mincut, "Instance: {}", file);
+        }
+    })
+}
+
+#[test]
+fn test_pushrelabel_resolve() -> Result<(), Box<dyn Error>> {
+    for_each_instance(false, |g, src, snk, upper, expected, file| {
+        let s = g.id2node(src);
+        let t = g.id2node(snk);
+        let mut pr = PushRelabel::new(g);
+        pr.solve(s, t, |e| upper[g.edge_id(e)]);
+        check_maxflow(&pr, src, upper, expected, file);
+
+        let mut upper = upper.to_vec();
+        for round in 0..5 {
+            upper = perturbed_upper(&upper, round);
+            let (value, _, mincut) = maxflow::pushrelabel(g, s, t, |e| upper[g.edge_id(e)]);
+            pr.resolve(s, t, |e| upper[g.edge_id(e)]);
+            check_maxflow(&pr, src, &upper, value, file);
+            assert_eq!(pr.mincut(), mincut, "Instance: {}", file);
+        }
+    })
+}
+
+/// Return a deterministic pseudo-random number generator.
+///
+/// The returned closure `next` is a linear congruential generator,
+/// `next(max)` returns a number in `0..max`.
+fn lcg(seed: u64) -> impl FnMut(u64) -> u64 {
+    let mut rnd = seed;
+    move |max| {
+        rnd = rnd.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (rnd >> 33) % max
     }
+}
 
-    Ok(())
+/// Create a segmentation-like instance on an `n x m` grid.
+///
+/// Each grid edge is replaced by two opposite edges and each grid node
+/// is connected to an additional source and sink node. The capacities
+/// are pseudo-random.
+fn grid_instance(n: usize, m: usize, seed: u64) -> Instance {
+    let grid: LinkedListGraph = classes::grid(n, m);
+    let mut rnd = lcg(seed);
+    let mut next = move |max: u64| rnd(max) as i32;
+
+    let mut upper = vec![];
+    let g = LinkedListGraph::new_with(|b| {
+        let nodes = b.add_nodes(grid.num_nodes() + 2);
+        let (s, t) = (nodes[grid.num_nodes()], nodes[grid.num_nodes() + 1]);
+        for e in grid.edges() {
+            let (u, v) = (nodes[grid.node_id(grid.src(e))], nodes[grid.node_id(grid.snk(e))]);
+            let cap = next(10) + 1;
+            b.add_edge(u, v);
+            b.add_edge(v, u);
+            upper.push(cap);
+            upper.push(cap);
+        }
+        for &u in &nodes[..grid.num_nodes()] {
+            b.add_edge(s, u);
+            upper.push(next(20));
+            b.add_edge(u, t);
+            upper.push(next(20));
+        }
+    });
+    let src = g.num_nodes() - 2;
+    let snk = g.num_nodes() - 1;
+    (g, src, snk, upper)
 }
 
 #[test]
-fn test_pushrelabel() -> Result<(), Box<dyn Error>> {
-    for &use_relabelling in &[true, false] {
-        for &(file, expected, _) in TESTS {
-            let instance = dimacs::max::read_from_file(file)?;
-            let g: LinkedListGraph = instance.graph;
-            let s = g.id2node(instance.src);
-            let t = g.id2node(instance.snk);
-            let upper = instance.upper;
-
-            let mut pr = PushRelabel::new(&g);
-            pr.use_global_relabelling = use_relabelling;
-            pr.solve(s, t, |e| upper[g.edge_id(e)].to_i32().unwrap());
-            assert_eq!(
-                pr.value(),
-                expected,
-                "Instance: {} relabelling: {}",
-                file,
-                use_relabelling
-            );
+fn test_grid() {
+    for &(n, m) in &[(2, 2), (5, 7), (20, 20), (40, 30)] {
+        for seed in 0..3 {
+            let (g, src, snk, upper) = grid_instance(n, m, seed);
+            let file = format!("grid {}x{} (seed {})", n, m, seed);
+            let s = g.id2node(src);
+            let t = g.id2node(snk);
+            let (value, _, mincut) = maxflow::dinic(&g, s, t, |e| upper[g.edge_id(e)]);
+
+            let mut bk = BoykovKolmogorov::new(&g);
+            bk.solve(s, t, |e| upper[g.edge_id(e)]);
+            check_maxflow(&bk, src, &upper, value, &file);
+            assert_eq!(bk.mincut(), mincut, "Instance: {}", file);
+
+            run_maxflow::<PushRelabel<_, _>>(&g, src, snk, &upper, value, &file);
+            run_maxflow::<EdmondsKarp<_, _>>(&g, src, snk, &upper, value, &file);
         }
     }
+}
 
-    Ok(())
+#[test]
+fn test_lower_bounds() -> Result<(), Box<dyn Error>> {
+    for_each_instance(false, |g, src, snk, upper, expected, file| {
+        let s = g.id2node(src);
+        let t = g.id2node(snk);
+
+        // zero lower bounds yield the usual maximum flow
+        let (value, flow, _) =
+            maxflow::solve_with_lower_bounds::<Dinic<_, _>>(g, s, t, |_| 0, |e| upper[g.edge_id(e)]).unwrap();
+        assert_eq!(value, expected, "Instance: {}", file);
+
+        // requiring half of the current flow as lower bound does not change the value
+        let lower: Vec<_> = flow.iter().map(|&(_, f)| f / 2).collect();
+        let (value, flow, _) =
+            maxflow::solve_with_lower_bounds::<Dinic<_, _>>(g, s, t, |e| lower[g.edge_id(e)], |e| upper[g.edge_id(e)])
+                .unwrap();
+        assert_eq!(value, expected, "Instance: {}", file);
+        for (e, f) in flow {
+            assert!(
+                lower[g.edge_id(e)] <= f && f <= upper[g.edge_id(e)],
+                "Instance: {}",
+                file
+            );
+        }
+    })
+}
+
+#[test]
+fn test_flow_decomposition() -> Result<(), Box<dyn Error>> {
+    for_each_instance(false, |g, src, snk, upper, expected, file| {
+        let (_, flow, _) = maxflow::dinic(g, g.id2node(src), g.id2node(snk), |e| upper[g.edge_id(e)]);
+        let decomp = decompose_flow(g, |e| flow[g.edge_id(e)].1);
+        assert!(
+            decomp.paths.len() + decomp.cycles.len() <= g.num_edges(),
+            "Instance: {}",
+            file
+        );
+
+        let mut total = vec![0; g.num_edges()];
+        let mut value = 0;
+        for (path, f) in &decomp.paths {
+            assert_eq!(g.node_id(g.src(path[0])), src, "Instance: {}", file);
+            assert_eq!(g.node_id(g.snk(path[path.len() - 1])), snk, "Instance: {}", file);
+            value += f;
+        }
+        for (part, f) in decomp.paths.iter().chain(decomp.cycles.iter()) {
+            assert!(*f > 0, "Instance: {}", file);
+            for i in 0..part.len() {
+                total[g.edge_id(part[i])] += f;
+                if i > 0 {
+                    assert_eq!(g.snk(part[i - 1]), g.src(part[i]), "Instance: {}", file);
+                }
+            }
+        }
+        for (path, _) in &decomp.cycles {
+            assert_eq!(g.snk(path[path.len() - 1]), g.src(path[0]), "Instance: {}", file);
+        }
+        assert_eq!(value, expected, "Instance: {}", file);
+        assert_eq!(
+            total,
+            flow.iter().map(|&(_, f)| f).collect::<Vec<_>>(),
+            "Instance: {}",
+            file
+        );
+    })
 }
@@ -21,11 +21,12 @@ use std::fs::read_dir;
 use std::path::Path;
 
 use rs_graph::dimacs;
-use rs_graph::mcf::{NetworkSimplex, SolutionState};
-use rs_graph::{IndexGraph, Net};
+use rs_graph::mcf::simplex::Pricing;
+use rs_graph::mcf::{CapacityScaling, CostScaling, MinCostFlow, NetworkSimplex, SolutionState};
+use rs_graph::traits::*;
+use rs_graph::Net;
 
-#[test]
-fn test_network_simplex() -> Result<(), Box<dyn Error>> {
+fn run_network_simplex(pricing: Pricing) -> Result<(), Box<dyn Error>> {
     let mut values = HashMap::new();
 
     for entry in read_dir(Path::new("tests/mcf"))? {
@@ -50,19 +51,32 @@ fn test_network_simplex() -> Result<(), Box<dyn Error>> {
             let costs = instance.costs;
 
             let mut spx = NetworkSimplex::new(&g);
+            spx.pricing = pricing;
             spx.set_balances(|u| balances[g.node_id(u)]);
             spx.set_lowers(|e| lower[g.edge_id(e)]);
             spx.set_uppers(|e| upper[g.edge_id(e)]);
             spx.set_costs(|e| costs[g.edge_id(e)]);
 
             let state = spx.solve();
-            assert_eq!(state, SolutionState::Optimal);
+            assert_eq!(
+                state,
+                SolutionState::Optimal,
+                "Instance: {:?} pricing: {:?}",
+                entry.path(),
+                pricing
+            );
             if let Some(value) = entry
                 .path()
                 .file_name()
                 .and_then(|s| values.get(s.to_string_lossy().as_ref()))
             {
-                assert_eq!(*value, spx.value());
+                assert_eq!(
+                    *value,
+                    spx.value(),
+                    "Instance: {:?} pricing: {:?}",
+                    entry.path(),
+                    pricing
+                );
             } else {
                 panic!("Can't find solution file for {:?}", entry.path());
             }
@@ -71,3 +85,331 @@ fn test_network_simplex() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+fn test_network_simplex() -> Result<(), Box<dyn Error>> {
+    run_network_simplex(Pricing::Block)
+}
+
+#[test]
+fn test_network_simplex_round_robin() -> Result<(), Box<dyn Error>> {
+    run_network_simplex(Pricing::RoundRobin)
+}
+
+#[test]
+fn test_network_simplex_complete() -> Result<(), Box<dyn Error>> {
+    run_network_simplex(Pricing::Complete)
+}
+
+#[test]
+fn test_network_simplex_multiple_partial() -> Result<(), Box<dyn Error>> {
+    run_network_simplex(Pricing::MultiplePartial)
+}
+
+type Instance = dimacs::min::Instance<Net, isize>;
+
+/// Read all min-cost-flow instances in `tests/mcf`.
+fn read_instances() -> Result<Vec<Instance>, Box<dyn Error>> {
+    let mut instances = vec![];
+    for entry in read_dir(Path::new("tests/mcf"))? {
+        let entry = entry?;
+        if entry.path().extension().map(|ext| ext == "min").unwrap_or(false) {
+            instances.push(dimacs::min::read_from_file(&entry.path().to_string_lossy())?);
+        }
+    }
+    Ok(instances)
+}
+
+/// Solve the problem with a new network simplex instance.
+fn solve_cold(
+    g: &Net,
+    balances: &[isize],
+    lower: &[isize],
+    upper: &[isize],
+    costs: &[isize],
+) -> (SolutionState, isize, usize) {
+    let mut spx = NetworkSimplex::new(g);
+    spx.set_balances(|u| balances[g.node_id(u)]);
+    spx.set_lowers(|e| lower[g.edge_id(e)]);
+    spx.set_uppers(|e| upper[g.edge_id(e)]);
+    spx.set_costs(|e| costs[g.edge_id(e)]);
+    let state = spx.solve();
+    (state, spx.value(), spx.num_iterations())
+}
+
+#[test]
+fn test_network_simplex_warm_start_costs() -> Result<(), Box<dyn Error>> {
+    let mut warm_iterations = 0;
+    let mut cold_iterations = 0;
+
+    for instance in read_instances()? {
+        let g = &instance.graph;
+        let mut costs = instance.costs.clone();
+
+        let mut spx = NetworkSimplex::new(g);
+        spx.set_balances(|u| instance.balances[g.node_id(u)]);
+        spx.set_lowers(|e| instance.lower[g.edge_id(e)]);
+        spx.set_uppers(|e| instance.upper[g.edge_id(e)]);
+        spx.set_costs(|e| costs[g.edge_id(e)]);
+        assert_eq!(spx.solve(), SolutionState::Optimal);
+
+        for round in 0..5 {
+            // change a few costs
+            for eid in (round..g.num_edges()).step_by(37) {
+                costs[eid] += if eid % 2 == 0 { 3 } else { -2 };
+                spx.set_cost(g.id2edge(eid), costs[eid]);
+            }
+            assert_eq!(spx.solution_state(), SolutionState::Unknown);
+
+            let state = spx.solve();
+            let (cold_state, cold_value, cold_niter) =
+                solve_cold(g, &instance.balances, &instance.lower, &instance.upper, &costs);
+            assert_eq!(state, cold_state);
+            assert_eq!(spx.value(), cold_value);
+
+            warm_iterations += spx.num_iterations();
+            cold_iterations += cold_niter;
+        }
+    }
+
+    assert!(
+        warm_iterations < cold_iterations,
+        "warm: {} cold: {}",
+        warm_iterations,
+        cold_iterations
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_network_simplex_warm_start_bounds_and_balances() -> Result<(), Box<dyn Error>> {
+    for instance in read_instances()? {
+        let g = &instance.graph;
+        let mut balances = instance.balances.clone();
+        let lower = instance.lower.clone();
+        let mut upper = instance.upper.clone();
+        let costs = &instance.costs;
+
+        let mut spx = NetworkSimplex::new(g);
+        spx.set_balances(|u| balances[g.node_id(u)]);
+        spx.set_lowers(|e| lower[g.edge_id(e)]);
+        spx.set_uppers(|e| upper[g.edge_id(e)]);
+        spx.set_costs(|e| costs[g.edge_id(e)]);
+        assert_eq!(spx.solve(), SolutionState::Optimal);
+
+        for round in 0..5 {
+            // decrease the capacity of some edges carrying flow and
+            // increase the capacity of others
+            for eid in (round..g.num_edges()).step_by(11) {
+                let e = g.id2edge(eid);
+                let flw = spx.flow(e);
+                upper[eid] = if flw > lower[eid] {
+                    (flw - 1).max(lower[eid])
+                } else {
+                    upper[eid] + 5
+                };
+                spx.set_upper(e, upper[eid]);
+            }
+
+            // move some supply
+            let uid = round % g.num_nodes();
+            let vid = (round * 7 + 3) % g.num_nodes();
+            balances[uid] += 2;
+            balances[vid] -= 2;
+            spx.set_balance(g.id2node(uid), balances[uid]);
+            spx.set_balance(g.id2node(vid), balances[vid]);
+
+            let state = spx.solve();
+            let (cold_state, cold_value, _) = solve_cold(g, &balances, &lower, &upper, costs);
+            assert_eq!(state, cold_state, "round: {}", round);
+            if state == SolutionState::Optimal {
+                assert_eq!(spx.value(), cold_value, "round: {}", round);
+                for u in g.nodes() {
+                    let outflow = g.outedges(u).map(|(e, _)| spx.flow(e)).sum::<isize>();
+                    let inflow = g.inedges(u).map(|(e, _)| spx.flow(e)).sum::<isize>();
+                    assert_eq!(outflow - inflow, balances[g.node_id(u)]);
+                }
+                for e in g.edges() {
+                    assert!(lower[g.edge_id(e)] <= spx.flow(e) && spx.flow(e) <= upper[g.edge_id(e)]);
+                }
+            } else {
+                // restore a feasible problem for the next round
+                balances[uid] -= 2;
+                balances[vid] += 2;
+                spx.set_balance(g.id2node(uid), balances[uid]);
+                spx.set_balance(g.id2node(vid), balances[vid]);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_network_simplex_duals() -> Result<(), Box<dyn Error>> {
+    for instance in read_instances()? {
+        let g = &instance.graph;
+        let lower = &instance.lower;
+        let upper = &instance.upper;
+        let mut costs = instance.costs.clone();
+
+        let mut spx = NetworkSimplex::new(g);
+        spx.set_balances(|u| instance.balances[g.node_id(u)]);
+        spx.set_lowers(|e| lower[g.edge_id(e)]);
+        spx.set_uppers(|e| upper[g.edge_id(e)]);
+        spx.set_costs(|e| costs[g.edge_id(e)]);
+        assert_eq!(spx.solve(), SolutionState::Optimal);
+
+        // complementary slackness
+        for e in g.edges() {
+            let eid = g.edge_id(e);
+            let rc = spx.reduced_cost(e);
+            assert_eq!(rc, costs[eid] + spx.potential(g.src(e)) - spx.potential(g.snk(e)));
+            if spx.is_basic(e) {
+                assert_eq!(rc, 0);
+                assert_eq!(spx.cost_range(e), None);
+            }
+            if rc > 0 {
+                assert_eq!(spx.flow(e), lower[eid]);
+            } else if rc < 0 {
+                assert_eq!(spx.flow(e), upper[eid]);
+            }
+        }
+
+        // move the costs of some non-basic edges to the end of their ranges
+        let value = spx.value();
+        let mut changed = vec![];
+        for e in g.edges().filter(|&e| !spx.is_basic(e)).step_by(13) {
+            let eid = g.edge_id(e);
+            let (lb, ub) = spx.cost_range(e).unwrap();
+            let c = if spx.reduced_cost(e) >= 0 { lb } else { ub };
+            assert!(lb <= costs[eid] && costs[eid] <= ub);
+            changed.push((eid, c - costs[eid], spx.flow(e)));
+        }
+        for &(eid, delta, _) in &changed {
+            costs[eid] += delta;
+            spx.set_cost(g.id2edge(eid), costs[eid]);
+        }
+        let (state, cold_value, _) = solve_cold(g, &instance.balances, lower, upper, &costs);
+        assert_eq!(state, SolutionState::Optimal);
+        assert_eq!(
+            cold_value,
+            value + changed.iter().map(|&(_, delta, flw)| delta * flw).sum::<isize>()
+        );
+    }
+
+    Ok(())
+}
+
+/// Read all min-cost-flow instances in `tests/mcf` with their optimal values.
+fn read_instances_with_values() -> Result<Vec<(Instance, isize)>, Box<dyn Error>> {
+    let mut instances = vec![];
+    for entry in read_dir(Path::new("tests/mcf"))? {
+        let entry = entry?;
+        if entry.path().extension().map(|ext| ext == "min").unwrap_or(false) {
+            let path = entry.path().to_string_lossy().to_string();
+            let (value, _) = dimacs::min::read_solution_from_file::<isize>(&format!("{}.sol", path))?;
+            instances.push((dimacs::min::read_from_file(&path)?, value));
+        }
+    }
+    Ok(instances)
+}
+
+/// Check a min-cost-flow solver on an instance.
+///
+/// The instance is solved as given and after some perturbations of the
+/// bounds and balances, which may make it infeasible. The solution state
+/// and value of the re-solve and of a new solver are compared with the
+/// network simplex, optimal flows are checked for feasibility and the
+/// potentials for complementary slackness.
+fn check_solver<'a, S>(instance: &'a Instance, value: isize)
+where
+    S: MinCostFlow<&'a Net, isize>,
+{
+    let g = &instance.graph;
+    let mut balances = instance.balances.clone();
+    let lower = &instance.lower;
+    let mut upper = instance.upper.clone();
+    let costs = &instance.costs;
+
+    let mut mcf = S::new(g);
+    mcf.set_balances(|u| balances[g.node_id(u)]);
+    mcf.set_lowers(|e| lower[g.edge_id(e)]);
+    mcf.set_uppers(|e| upper[g.edge_id(e)]);
+    mcf.set_costs(|e| costs[g.edge_id(e)]);
+    assert_eq!(mcf.solve(), SolutionState::Optimal);
+    assert_eq!(mcf.value(), value);
+
+    for round in 0..4 {
+        if round > 0 {
+            // perturb the problem
+            for eid in (round..g.num_edges()).step_by(7) {
+                upper[eid] = (upper[eid] - 3 + round as isize * 2).max(lower[eid]);
+                mcf.set_upper(g.id2edge(eid), upper[eid]);
+            }
+            let uid = (round * 5) % g.num_nodes();
+            let vid = (round * 11 + 1) % g.num_nodes();
+            balances[uid] += 3;
+            balances[vid] -= 3;
+            mcf.set_balance(g.id2node(uid), balances[uid]);
+            mcf.set_balance(g.id2node(vid), balances[vid]);
+            assert_eq!(mcf.solution_state(), SolutionState::Unknown);
+            mcf.solve();
+        }
+
+        let (state, value, _) = solve_cold(g, &balances, lower, &upper, costs);
+        assert_eq!(mcf.solution_state(), state, "round: {}", round);
+
+        let mut cold = S::new(g);
+        cold.set_balances(|u| balances[g.node_id(u)]);
+        cold.set_lowers(|e| lower[g.edge_id(e)]);
+        cold.set_uppers(|e| upper[g.edge_id(e)]);
+        cold.set_costs(|e| costs[g.edge_id(e)]);
+        assert_eq!(cold.solve(), state, "round: {}", round);
+
+        if state != SolutionState::Optimal {
+            continue;
+        }
+
+        assert_eq!(mcf.value(), value, "round: {}", round);
+        assert_eq!(cold.value(), value, "round: {}", round);
+        for u in g.nodes() {
+            let outflow = g.outedges(u).map(|(e, _)| mcf.flow(e)).sum::<isize>();
+            let inflow = g.inedges(u).map(|(e, _)| mcf.flow(e)).sum::<isize>();
+            assert_eq!(outflow - inflow, balances[g.node_id(u)]);
+        }
+        for e in g.edges() {
+            let eid = g.edge_id(e);
+            let flw = mcf.flow(e);
+            assert!(lower[eid] <= flw && flw <= upper[eid]);
+            let rc = costs[eid] + mcf.potential(g.src(e)) - mcf.potential(g.snk(e));
+            assert!(rc <= 0 || flw == lower[eid], "round: {} edge: {}", round, eid);
+            assert!(rc >= 0 || flw == upper[eid], "round: {} edge: {}", round, eid);
+        }
+    }
+}
+
+#[test]
+fn test_mcf_network_simplex() -> Result<(), Box<dyn Error>> {
+    for (instance, value) in read_instances_with_values()? {
+        check_solver::<NetworkSimplex<_, _>>(&instance, value);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_mcf_cost_scaling() -> Result<(), Box<dyn Error>> {
+    for (instance, value) in read_instances_with_values()? {
+        check_solver::<CostScaling<_, _>>(&instance, value);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_mcf_capacity_scaling() -> Result<(), Box<dyn Error>> {
+    for (instance, value) in read_instances_with_values()? {
+        check_solver::<CapacityScaling<_, _>>(&instance, value);
+    }
+    Ok(())
+}